@@ -13,9 +13,12 @@ use relation_server::{
     graph::vertex::contract::ContractLoadFn,
     graph::vertex::FromToLoadFn,
     graph::vertex::IdentityLoadFn,
+    graph::vertex::PrimaryNameLoadFn,
+    graph::vertex::TokenLoadFn,
+    upstream,
 };
 // use aragog::{AuthMode, DatabaseConnection, OperationOptions};
-use std::{convert::Infallible, net::SocketAddr};
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
 use tracing::{info, warn};
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 use warp::{http::Response as HttpResponse, Filter, Rejection};
@@ -50,6 +53,36 @@ async fn main() -> Result<()> {
         .build()
         .await?;
 
+    // Scheduled prefetch (e.g. SybilList import)
+    let prefetch_interval = C.upstream.sybil_service.prefetch_interval;
+    if prefetch_interval > 0 {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(prefetch_interval));
+            loop {
+                interval.tick().await;
+                if let Err(err) = upstream::prefetch().await {
+                    warn!("Scheduled prefetch failed: {}", err);
+                }
+            }
+        });
+        info!("Prefetch scheduled every {}s", prefetch_interval);
+    }
+
+    // Scheduled on-chain transfer log indexing
+    let index_interval = C.upstream.log_indexer.interval;
+    if index_interval > 0 {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(index_interval));
+            loop {
+                interval.tick().await;
+                if let Err(err) = upstream::index_logs().await {
+                    warn!("Scheduled log indexing failed: {}", err);
+                }
+            }
+        });
+        info!("Log indexing scheduled every {}s", index_interval);
+    }
+
     // Runtime::Tokio1
     let pool = new_connection_pool().await?;
     let contract_loader_fn = ContractLoadFn {
@@ -61,6 +94,12 @@ async fn main() -> Result<()> {
     let from_to_loader_fn = FromToLoadFn {
         pool: pool.to_owned(),
     };
+    let primary_name_loader_fn = PrimaryNameLoadFn {
+        pool: pool.to_owned(),
+    };
+    let token_loader_fn = TokenLoadFn {
+        pool: pool.to_owned(),
+    };
     // HOLD ON: Specify the batch size number
     let contract_loader = Loader::new(contract_loader_fn)
         .with_max_batch_size(100)
@@ -71,12 +110,20 @@ async fn main() -> Result<()> {
     let from_to_loader = Loader::new(from_to_loader_fn)
         .with_max_batch_size(100)
         .with_yield_count(10);
+    let primary_name_loader = Loader::new(primary_name_loader_fn)
+        .with_max_batch_size(100)
+        .with_yield_count(10);
+    let token_loader = Loader::new(token_loader_fn)
+        .with_max_batch_size(100)
+        .with_yield_count(10);
 
     let schema = Schema::build(Query::default(), EmptyMutation, EmptySubscription)
         .data(pool)
         .data(contract_loader)
         .data(identity_loader)
         .data(from_to_loader)
+        .data(primary_name_loader)
+        .data(token_loader)
         .finish();
 
     let graphql_post = async_graphql_warp::graphql(schema)
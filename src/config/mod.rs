@@ -1,8 +1,11 @@
 mod env;
 
 use crate::error::Error;
-use config::Config;
+use crate::graph::edge::resolve::DomainNameSystem;
+use crate::graph::vertex::contract::{Chain, ChainInfo, ContractCategory};
+use config::{Config, ConfigError};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 use self::env::ENV;
 
@@ -20,6 +23,10 @@ lazy_static! {
             parse().unwrap()
         }
     };
+
+    /// Chain registry, i.e. `[[chains]]` in config. Overrides built-in chains of the same name, or adds new ones.
+    /// Parsed apart from `C`, since chains in `C` are checked against the registry.
+    pub static ref CHAINS: Vec<ChainInfo> = parse_chains().unwrap();
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -27,6 +34,30 @@ pub struct KVConfig {
     pub db: ConfigDB,
     pub web: ConfigWeb,
     pub upstream: Upstream,
+    #[serde(default)]
+    pub primary_name: ConfigPrimaryName,
+}
+
+/// How `displayName` of a wallet is picked among its primary names.
+#[derive(Clone, Deserialize)]
+pub struct ConfigPrimaryName {
+    /// Name systems in order of preference. Names in systems not listed are never used.
+    pub precedence: Vec<DomainNameSystem>,
+}
+
+impl Default for ConfigPrimaryName {
+    fn default() -> Self {
+        Self {
+            precedence: vec![
+                DomainNameSystem::ENS,
+                DomainNameSystem::SpaceId,
+                DomainNameSystem::UnstoppableDomains,
+                DomainNameSystem::DotBit,
+                DomainNameSystem::Lens,
+                DomainNameSystem::SNS,
+            ],
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -42,8 +73,18 @@ pub struct Upstream {
     pub dotbit_service: ConfigDotbitService,
     pub lens_api: ConfigLensAPI,
     pub unstoppable_api: ConfigUnstoppableDomainsAPI,
-    pub datamgr_api: ConfigDataMgrAPI,
+    pub farcaster_hub: ConfigFarcasterHub,
     pub spaceid_api: ConfigSpaceIdAPI,
+    pub crossbell_api: ConfigCrossbellAPI,
+    pub sns_api: ConfigSNSAPI,
+    pub dns_resolver: ConfigDNSResolver,
+    pub poap_api: ConfigPOAPAPI,
+    #[serde(default)]
+    pub log_indexer: ConfigLogIndexer,
+    #[serde(default)]
+    pub contract_wallet: ConfigContractWallet,
+    #[serde(default)]
+    pub token_uri: ConfigTokenUri,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -74,11 +115,18 @@ pub struct ConfigKeybaseService {
 #[derive(Clone, Deserialize, Default)]
 pub struct ConfigAggregationService {
     pub url: String,
+    /// Disabled unless set explicitly.
+    #[serde(default)]
+    pub enabled: bool,
 }
 
 #[derive(Clone, Deserialize, Default)]
 pub struct ConfigSybilService {
     pub url: String,
+    /// Re-import the list every this many seconds in standalone server.
+    /// `0` (default) disables it.
+    #[serde(default)]
+    pub prefetch_interval: u64,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -118,13 +166,86 @@ pub struct ConfigUnstoppableDomainsAPI {
 }
 
 #[derive(Clone, Deserialize, Default)]
-pub struct ConfigDataMgrAPI {
+pub struct ConfigFarcasterHub {
     pub url: String,
 }
 
 #[derive(Clone, Deserialize, Default)]
 pub struct ConfigSpaceIdAPI {
     pub url: String,
+    /// Subgraph (in ENS subgraph schema) of each TLD, e.g. `bnb`, to list every name a wallet owns.
+    /// TLDs without one only have the primary name of a wallet found.
+    #[serde(default)]
+    pub subgraphs: HashMap<String, String>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigCrossbellAPI {
+    pub url: String,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigSNSAPI {
+    pub url: String,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigDNSResolver {
+    /// DNS-over-HTTPS JSON API endpoint.
+    pub url: String,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigPOAPAPI {
+    pub url: String,
+    pub api_key: String,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigLogIndexer {
+    /// Index transfer logs every this many seconds in standalone server.
+    /// `0` (default) disables it.
+    #[serde(default)]
+    pub interval: u64,
+    /// How many latest blocks to index again when a reorg is detected. `0` for default (12).
+    #[serde(default)]
+    pub reorg_depth: u64,
+    /// Max blocks covered by one `eth_getLogs` request. `0` for default (2000).
+    #[serde(default)]
+    pub batch_size: u64,
+    #[serde(default)]
+    pub contracts: Vec<ConfigIndexedContract>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigContractWallet {
+    /// Chains to check wallets on. Their `rpc_url` is taken from `[[chains]]`.
+    /// Empty (default) disables it.
+    #[serde(default)]
+    pub chains: Vec<Chain>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+pub struct ConfigTokenUri {
+    /// Chains to read `tokenURI()` / `uri()` of NFTs on. Their `rpc_url` is taken from `[[chains]]`.
+    /// Empty (default) disables it.
+    #[serde(default)]
+    pub chains: Vec<Chain>,
+}
+
+/// An ERC-721 / ERC-1155 contract whose transfer logs are indexed.
+#[derive(Clone, Deserialize, Debug)]
+pub struct ConfigIndexedContract {
+    pub chain: Chain,
+    pub address: String,
+    pub category: ContractCategory,
+    pub symbol: Option<String>,
+    /// JSON-RPC endpoint of `chain`. Falls back to `rpc_url` of `chain` in `[[chains]]`.
+    #[serde(default)]
+    pub rpc_url: String,
+    /// Block to start indexing from, usually the block the contract is deployed.
+    #[serde(default)]
+    pub start_block: u64,
 }
 
 #[derive(Clone, Deserialize)]
@@ -151,6 +272,19 @@ pub fn app_env() -> ENV {
 
 /// Parse config from local file or ENV.
 pub fn parse() -> Result<KVConfig, Error> {
+    source()?.try_deserialize().map_err(|e| e.into())
+}
+
+/// Parse `[[chains]]` only, from the same sources as `parse()`.
+pub fn parse_chains() -> Result<Vec<ChainInfo>, Error> {
+    match source()?.get::<Vec<ChainInfo>>("chains") {
+        Err(ConfigError::NotFound(_)) => Ok(vec![]),
+        result => result.map_err(|e| e.into()),
+    }
+}
+
+/// Config sources: local files, then ENV.
+fn source() -> Result<Config, Error> {
     let s = Config::builder()
         // Default
         .add_source(config::File::with_name(CONFIG_FILE_PATH).required(false))
@@ -167,7 +301,7 @@ pub fn parse() -> Result<KVConfig, Error> {
         )
         .build()?;
 
-    s.try_deserialize().map_err(|e| e.into())
+    Ok(s)
 }
 
 /// `AWS_SECRET_NAME` and `AWS_SECRET_REGION` is needed.
@@ -1,7 +1,25 @@
-use crate::graph::vertex::contract::{Chain, ContractCategory, ContractRecord};
-use async_graphql::Object;
+use crate::{
+    error::{Error, Result},
+    graph::{
+        vertex::contract::{
+            BuiltinChain, Contract, ContractCategory, ContractHolder, ContractHolders,
+            ContractRecord,
+        },
+        vertex::IdentityRecord,
+        ConnectionPool,
+    },
+    upstream::{AssetId, ChainId},
+};
+use async_graphql::{Context, Object};
+use deadpool::managed::Object as PoolObject;
+use tracing::debug;
 use uuid::Uuid;
 
+/// Holders per page if `first` is not given.
+const DEFAULT_HOLDERS_PAGE_SIZE: u64 = 20;
+/// At most this many holders per page.
+const MAX_HOLDERS_PAGE_SIZE: u64 = 100;
+
 #[Object]
 impl ContractRecord {
     /// UUID of this record.
@@ -19,9 +37,24 @@ impl ContractRecord {
         self.address.clone()
     }
 
-    /// On which chain?
-    async fn chain(&self) -> Chain {
-        self.chain
+    /// On which chain? `unknown` for chains added only in config, see `chainName`.
+    async fn chain(&self) -> BuiltinChain {
+        self.chain.into()
+    }
+
+    /// Name of `chain` in the chain registry, chains added in config included.
+    async fn chain_name(&self) -> String {
+        self.chain.to_string()
+    }
+
+    /// CAIP-2 ID of `chain`, e.g. `eip155:1`.
+    async fn chain_id(&self) -> Option<String> {
+        ChainId::of_chain(&self.chain).map(|id| id.to_string())
+    }
+
+    /// CAIP-19 asset type of this contract, e.g. `eip155:1/erc721:0x...`.
+    async fn asset_type(&self) -> Option<String> {
+        AssetId::of_contract(&self.record).map(|id| id.to_string())
     }
 
     /// Token symbol
@@ -29,8 +62,127 @@ impl ContractRecord {
         self.symbol.clone()
     }
 
+    /// Name of this contract (collection).
+    async fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    /// Total supply in decimal. `null` if the contract doesn't tell.
+    async fn total_supply(&self) -> Option<String> {
+        self.total_supply.clone()
+    }
+
+    /// Address which deployed this contract, if known.
+    async fn deployer(&self) -> Option<String> {
+        self.deployer.clone()
+    }
+
+    /// If this is a known contract, i.e. its address is built in or configured by RelationService,
+    /// rather than picked up from upstreams.
+    async fn verified(&self) -> bool {
+        self.verified
+    }
+
+    /// Identities currently holding tokens of this contract, in the order of their IDs.
+    async fn holders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "How many holders to return. 20 by default, 100 at most.")] first: Option<
+            u64,
+        >,
+        #[graphql(desc = "`endCursor` of the previous page.")] after: Option<String>,
+    ) -> Result<HolderConnection> {
+        let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
+        debug!("Connection pool status: {:?}", pool.status());
+        let offset = match after {
+            None => 0,
+            Some(after) => after
+                .parse::<u64>()
+                .map_err(|_| Error::ParamError(format!("Invalid cursor: {}", after)))?,
+        };
+        let limit = first
+            .unwrap_or(DEFAULT_HOLDERS_PAGE_SIZE)
+            .min(MAX_HOLDERS_PAGE_SIZE);
+        let page = self.find_holders(pool, offset, limit).await?;
+        Ok(HolderConnection { offset, page })
+    }
+
     /// When this connection is fetched by us RelationService.
     async fn updated_at(&self) -> i64 {
         self.updated_at.timestamp()
     }
 }
+
+#[Object]
+impl ContractHolder {
+    /// Who holds.
+    async fn identity(&self) -> IdentityRecord {
+        self.identity.clone()
+    }
+
+    /// How many tokens of this contract it holds.
+    async fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// A page of `holders` of a contract.
+pub struct HolderConnection {
+    offset: u64,
+    page: ContractHolders,
+}
+
+#[Object]
+impl HolderConnection {
+    /// How many distinct holders in total.
+    async fn total_count(&self) -> u64 {
+        self.page.total
+    }
+
+    /// Holders in this page.
+    async fn holders(&self) -> Vec<ContractHolder> {
+        self.page.holders.clone()
+    }
+
+    /// Pass it as `after` to get the next page. `null` if this page is empty.
+    async fn end_cursor(&self) -> Option<String> {
+        if self.page.holders.is_empty() {
+            None
+        } else {
+            Some((self.offset + self.page.holders.len() as u64).to_string())
+        }
+    }
+
+    /// If there are more holders after this page.
+    async fn has_next_page(&self) -> bool {
+        self.offset + (self.page.holders.len() as u64) < self.page.total
+    }
+}
+
+#[derive(Default)]
+pub struct ContractQuery {}
+
+#[Object]
+impl ContractQuery {
+    /// Query a contract (i.e. an NFT collection) by its address.
+    async fn contract(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "On which chain this contract is.")] chain: Option<BuiltinChain>,
+        #[graphql(
+            desc = "Name of the chain, instead of `chain`. See `availableChains` for all values supported by RelationService, chains added in config included."
+        )]
+        chain_name: Option<String>,
+        #[graphql(desc = "Contract address. Usually `0xHEX_STRING`.")] address: String,
+    ) -> Result<Option<ContractRecord>> {
+        let chain = BuiltinChain::or_named(chain, chain_name)?;
+        let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
+        debug!("Connection pool status: {:?}", pool.status());
+        let conn = pool
+            .get()
+            .await
+            .map_err(|err| Error::PoolError(err.to_string()))?;
+        let db = PoolObject::take(conn);
+        Ok(Contract::find_by_chain_address(&db, &chain, &address.to_lowercase()).await?)
+    }
+}
@@ -3,12 +3,12 @@ use crate::{
     graph::{
         edge::{Hold, HoldRecord},
         vertex::{
-            contract::{Chain, ContractCategory, ContractLoadFn, ContractRecord},
-            IdentityLoadFn, IdentityRecord,
+            contract::{BuiltinChain, Chain, ContractCategory, ContractLoadFn, ContractRecord},
+            IdentityLoadFn, IdentityRecord, TokenLoadFn, TokenRecord,
         },
         ConnectionPool,
     },
-    upstream::{fetch_all, DataFetcher, DataSource, Target},
+    upstream::{fetch_all, AssetId, ChainId, DataFetcher, DataSource, Target},
 };
 use async_graphql::{Context, Object};
 // use dataloader::cached::Loader;
@@ -66,18 +66,51 @@ impl HoldRecord {
         }
     }
 
-    /// On which chain?
-    /// See `availableChains` for all chains supported by RelationService.
-    async fn chain(&self, ctx: &Context<'_>) -> Result<Chain> {
+    /// On which chain? `unknown` for chains added only in config, see `chainName`.
+    async fn chain(&self, ctx: &Context<'_>) -> Result<BuiltinChain> {
         let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.id.clone()).await {
-            Some(contract) => Ok(contract.chain),
+            Some(contract) => Ok(contract.chain.into()),
             None => Err(Error::GraphQLError("contract no found.".to_string())),
             // None => Ok(Chain::Unknown),
         }
     }
 
+    /// Name of `chain` in the chain registry, chains added in config included.
+    /// See `availableChains` for all chains supported by RelationService.
+    async fn chain_name(&self, ctx: &Context<'_>) -> Result<String> {
+        let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.id.clone()).await {
+            Some(contract) => Ok(contract.chain.to_string()),
+            None => Err(Error::GraphQLError("contract no found.".to_string())),
+        }
+    }
+
+    /// CAIP-2 ID of the chain, e.g. `eip155:1`.
+    async fn chain_id(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.id.clone()).await {
+            Some(contract) => Ok(ChainId::of_chain(&contract.chain).map(|id| id.to_string())),
+            None => Err(Error::GraphQLError("contract no found.".to_string())),
+        }
+    }
+
+    /// CAIP-19 asset ID of the held token, e.g. `eip155:1/erc721:0x.../1`.
+    /// `null` if it has none (i.e. ENS names, whose token ID is a hash of the label).
+    async fn asset_id(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.id.clone()).await {
+            Some(contract) => {
+                Ok(AssetId::of_token(&contract.record, &self.id).map(|id| id.to_string()))
+            }
+            None => Err(Error::GraphQLError("contract no found.".to_string())),
+        }
+    }
+
     /// Contract address of this Contract. Usually `0xHEX_STRING`.
     async fn address(&self, ctx: &Context<'_>) -> Result<String> {
         let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
@@ -98,6 +131,13 @@ impl HoldRecord {
         }
     }
 
+    /// Metadata (name, image, traits, ...) of the held token. `null` if not fetched yet.
+    async fn token(&self, ctx: &Context<'_>) -> Result<Option<TokenRecord>> {
+        let loader: &Loader<(String, String), Option<TokenRecord>, TokenLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        Ok(loader.load((self.id_to().clone(), self.id.clone())).await)
+    }
+
     /// Which `Identity` does this NFT belong to.
     async fn owner(&self, ctx: &Context<'_>) -> Result<IdentityRecord> {
         let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
@@ -113,6 +153,48 @@ impl HoldRecord {
     async fn fetcher(&self) -> DataFetcher {
         self.fetcher
     }
+
+    /// ID of the event this token is issued for (i.e. POAP event ID), if any.
+    async fn event_id(&self) -> Option<String> {
+        self.event_id.clone()
+    }
+
+    /// Name of the event this token is issued for, if any.
+    async fn event_name(&self) -> Option<String> {
+        self.event_name.clone()
+    }
+
+    /// When the held object left this holder (transferred out / burned). `null` if it is still held.
+    async fn ended_at(&self) -> Option<i64> {
+        self.ended_at.map(|dt| dt.timestamp())
+    }
+
+    /// When the held object (i.e. a domain) expires. `null` if it never expires, or upstream doesn't tell.
+    async fn expires_at(&self) -> Option<i64> {
+        self.expired_at.map(|dt| dt.timestamp())
+    }
+
+    /// If the held object (i.e. a domain) is expired.
+    async fn is_expired(&self) -> bool {
+        self.is_expired()
+    }
+
+    /// How many of the held object, in its smallest unit (i.e. wei), as a decimal string.
+    /// `null` if upstream doesn't tell, which is counted as one.
+    /// For now only contracts indexed from chain logs have their balances recorded.
+    async fn amount(&self) -> Option<String> {
+        self.amount.clone()
+    }
+
+    /// Decimals of `amount`, for fungible tokens (i.e. ERC20).
+    async fn decimals(&self) -> Option<u8> {
+        self.decimals
+    }
+
+    /// `amount` in whole units, i.e. divided by `10^decimals`.
+    async fn balance(&self) -> f64 {
+        self.balance()
+    }
 }
 
 #[derive(Default)]
@@ -120,9 +202,9 @@ pub struct HoldQuery {}
 
 #[Object]
 impl HoldQuery {
-    /// List of all chains supported by RelationService.
+    /// List of all chains supported by RelationService, chains added in config included.
     async fn available_chains(&self) -> Vec<String> {
-        Chain::iter().map(|c| c.to_string()).collect()
+        Chain::all().iter().map(|c| c.to_string()).collect()
     }
 
     /// List of all Contract Categoris supported by RelationService.
@@ -134,30 +216,38 @@ impl HoldQuery {
     async fn nft(
         &self,
         ctx: &Context<'_>,
+        #[graphql(desc = "On which chain this NFT is.")] chain: Option<BuiltinChain>,
         #[graphql(
-            desc = "On which chain this NFT is. See `availableChains` for all values supported by RelationService."
+            desc = "Name of the chain, instead of `chain`. See `availableChains` for all values supported by RelationService, chains added in config included."
         )]
-        chain: Chain,
+        chain_name: Option<String>,
         #[graphql(
             desc = "What kind of this NFT is. See `availableNftCategoris` for all categories supported by RelationService."
         )]
         category: ContractCategory,
         #[graphql(
-            desc = "ID of this NFT. For ENS, this is the name of the token (abc.eth). For other NFT, this is the NFT_ID in contract."
+            desc = "ID of this NFT. For ENS, this is the name of the token (abc.eth, or pay.abc.eth for subnames). For other NFT, this is the NFT_ID in contract."
         )]
         id: String,
         #[graphql(
-            desc = "Contract address of this NFT. Usually `0xHEX_STRING`. For `category: \"ENS\"`, this can be omitted."
+            desc = "Contract address of this NFT. Usually `0xHEX_STRING`. For `category: \"ENS\"`, this can be omitted, both the base registrar and NameWrapper are looked up."
         )]
         address: Option<String>,
     ) -> Result<Option<HoldRecord>> {
+        let chain = BuiltinChain::or_named(chain, chain_name)?;
         let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
         debug!("Connection pool status: {:?}", pool.status());
-        let contract_address = address
-            .or(category.default_contract_address())
+        // Wrapped ENS names (and subnames) are held by NameWrapper, others by the base registrar.
+        let contract_addresses = match address {
+            Some(address) => vec![address],
+            None => category.contract_addresses(),
+        };
+        let contract_address = contract_addresses
+            .first()
+            .cloned()
             .ok_or(Error::GraphQLError("Contract address is required.".into()))?;
-        let target = Target::NFT(chain, category, contract_address.clone(), id.clone());
-        match Hold::find_by_id_chain_address_merge(pool, &id, &chain, &contract_address).await? {
+        let target = Target::NFT(chain, category, contract_address, id.clone());
+        match find_nft_hold(pool, &id, &chain, &contract_addresses).await? {
             Some(hold) => {
                 if hold.is_outdated() {
                     // Refetch in the background
@@ -168,8 +258,23 @@ impl HoldQuery {
 
             None => {
                 let _ = fetch_all(target).await;
-                Hold::find_by_id_chain_address_merge(pool, &id, &chain, &contract_address).await
+                find_nft_hold(pool, &id, &chain, &contract_addresses).await
             }
         }
     }
 }
+
+/// Finds the hold of NFT `id` in the first contract of `addresses` which has it.
+async fn find_nft_hold(
+    pool: &ConnectionPool,
+    id: &str,
+    chain: &Chain,
+    addresses: &[String],
+) -> Result<Option<HoldRecord>> {
+    for address in addresses.iter() {
+        if let Some(hold) = Hold::find_by_id_chain_address_merge(pool, id, chain, address).await? {
+            return Ok(Some(hold));
+        }
+    }
+    Ok(None)
+}
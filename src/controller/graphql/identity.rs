@@ -1,11 +1,19 @@
+use crate::config::C;
 use crate::controller::vec_string_to_vec_platform;
 use crate::error::{Error, Result};
 use crate::graph::edge::{HoldRecord, IdentityFromToRecord};
-use crate::graph::vertex::contract::ContractCategory;
-use crate::graph::vertex::{Identity, IdentityRecord, IdentityWithSource, Vertex};
+use crate::graph::vertex::contract::{BuiltinChain, ContractCategory};
+use crate::graph::vertex::{
+    Identity, IdentityRecord, IdentityWithSource, PrimaryName, PrimaryNameLoadFn, Vertex,
+};
 use crate::graph::ConnectionPool;
-use crate::upstream::{fetch_all, DataSource, Platform, Target};
+use crate::upstream::{
+    farcaster_fid_by_username, fetch_all, is_valid_address, nostr_nip05_to_hex, AccountId,
+    DataSource, Platform, Target,
+};
+use crate::util::nostr_pubkey_to_hex;
 use async_graphql::{Context, Object};
+use dataloader::non_cached::Loader;
 use deadpool::managed::Object;
 use strum::IntoEnumIterator;
 use tracing::{debug, Level, event};
@@ -79,9 +87,21 @@ impl IdentityRecord {
 
     /// Usually user-friendly screen name.  e.g. for `Twitter`, this
     /// is the user's `screen_name`.
+    /// For wallets, this is the most preferred one of `primaryNames`
+    /// (see `primary_name.precedence` in config) if there is any.
     /// Note: both `null` and `""` should be treated as "no value".
-    async fn display_name(&self) -> Option<String> {
-        self.display_name.clone()
+    async fn display_name(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let names = self.load_primary_names(ctx).await?;
+        match PrimaryName::pick(&names, &C.primary_name.precedence) {
+            Some(primary_name) => Ok(Some(primary_name.name.clone())),
+            None => Ok(self.display_name.clone()),
+        }
+    }
+
+    /// Primary names (reverse records) of this wallet, at most one per domain name system.
+    /// Only names which also resolve to this wallet, and are not expired, are returned.
+    async fn primary_names(&self, ctx: &Context<'_>) -> Result<Vec<PrimaryName>> {
+        self.load_primary_names(ctx).await
     }
 
     /// URL to target identity profile page on `platform` (if any).
@@ -117,6 +137,31 @@ impl IdentityRecord {
         self.updated_at.timestamp()
     }
 
+    /// Chains this wallet is seen on by upstreams.
+    /// Empty for non-wallet identities, or if no upstream tells.
+    /// Chains added only in config are `unknown` here, see `chainNames`.
+    async fn chains(&self) -> Vec<BuiltinChain> {
+        self.chains.iter().map(|chain| (*chain).into()).collect()
+    }
+
+    /// Names of `chains` in the chain registry, chains added in config included.
+    async fn chain_names(&self) -> Vec<String> {
+        self.chains.iter().map(|chain| chain.to_string()).collect()
+    }
+
+    /// CAIP-10 account IDs of this wallet (e.g. `eip155:137:0xabc...`),
+    /// one per chain it is seen on.
+    /// Empty for non-wallet identities.
+    async fn caip10(&self) -> Vec<String> {
+        self.account_ids().iter().map(|a| a.to_string()).collect()
+    }
+
+    /// Is this wallet a contract (e.g. a Safe multisig) rather than an EOA?
+    /// `null` if not checked yet, or not a wallet.
+    async fn is_contract(&self) -> Option<bool> {
+        self.is_contract
+    }
+
     /// Neighbor identity from current. Flattened.
     // FIXME: <2023-04-23 SUN> broken of high CPU / bandwidth consumption. Maybe something is wrong with SQL.
     async fn neighbor(
@@ -152,7 +197,12 @@ impl IdentityRecord {
     }
 
     /// there's only `platform: lens` identity `ownedBy` is not null
-    async fn owned_by(&self, ctx: &Context<'_>) -> Result<Option<IdentityRecord>> {
+    async fn owned_by(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Also return the owner if the domain is expired. `false` if omitted.")]
+        include_expired: Option<bool>,
+    ) -> Result<Option<IdentityRecord>> {
         if vec![
             Platform::Lens,
             Platform::Dotbit,
@@ -164,7 +214,21 @@ impl IdentityRecord {
         }
         let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
         debug!("Connection pool status: {:?}", pool.status());
-        self.domain_owned_by(pool).await
+        self.domain_owned_by(pool, include_expired.unwrap_or(false)).await
+    }
+
+    /// Sub-domains of this domain, e.g. `sub.abc.bit` of `abc.bit`.
+    async fn subdomains(&self, ctx: &Context<'_>) -> Result<Vec<IdentityRecord>> {
+        let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
+        debug!("Connection pool status: {:?}", pool.status());
+        self.find_subdomains(pool).await
+    }
+
+    /// Parent domain of this sub-domain. `null` if this is not a sub-domain.
+    async fn parent_domain(&self, ctx: &Context<'_>) -> Result<Option<IdentityRecord>> {
+        let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
+        debug!("Connection pool status: {:?}", pool.status());
+        self.find_parent_domain(pool).await
     }
 
     /// NFTs owned by this identity.
@@ -177,10 +241,38 @@ impl IdentityRecord {
             desc = "Filter condition for ContractCategory. If not provided or empty array, all category NFTs will be returned."
         )]
         category: Option<Vec<ContractCategory>>,
+        #[graphql(
+            desc = "Only NFTs held at least this many of. Holdings without a known amount count as one."
+        )]
+        min_balance: Option<f64>,
     ) -> Result<Vec<HoldRecord>> {
         let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
         debug!("Connection pool status: {:?}", pool.status());
-        self.nfts(pool, category).await
+        self.nfts(pool, category, min_balance).await
+    }
+
+    /// Fungible tokens (ERC20) held by this identity.
+    async fn tokens(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "Only tokens held at least this many of, in whole units (i.e. divided by decimals)."
+        )]
+        min_balance: Option<f64>,
+    ) -> Result<Vec<HoldRecord>> {
+        let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
+        self.fungible_tokens(pool, min_balance).await
+    }
+}
+
+impl IdentityRecord {
+    async fn load_primary_names(&self, ctx: &Context<'_>) -> Result<Vec<PrimaryName>> {
+        if self.key().is_empty() {
+            return Ok(vec![]);
+        }
+        let loader: &Loader<String, Vec<PrimaryName>, PrimaryNameLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        Ok(loader.load(self.id().to_string()).await)
     }
 }
 
@@ -200,12 +292,16 @@ impl IdentityQuery {
     }
 
     /// Query an `identity` by given `platform` and `identity`.
+    /// A wallet can also be queried by its CAIP-10 account ID, with `platform: "caip10"`.
     #[tracing::instrument(level = "trace", skip(self, ctx))]
     async fn identity(
         &self,
         ctx: &Context<'_>,
-        #[graphql(desc = "Platform to query")] platform: String,
-        #[graphql(desc = "Identity on target Platform")] identity: String,
+        #[graphql(desc = "Platform to query, or `caip10`")] platform: String,
+        #[graphql(
+            desc = "Identity on target Platform, or a CAIP-10 account ID (e.g. `eip155:1:0xabc...`)"
+        )]
+        identity: String,
     ) -> Result<Option<IdentityRecord>> {
         // let db: &DatabaseConnection = ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
@@ -217,8 +313,65 @@ impl IdentityQuery {
             .map_err(|err| Error::PoolError(err.to_string()))?;
         let db = Object::take(conn);
 
-        let platform: Platform = platform.parse()?;
-        let target = Target::Identity(platform, identity.clone());
+        let (platform, identity) = if platform == "caip10" {
+            identity
+                .parse::<AccountId>()?
+                .platform_identity()
+                .ok_or_else(|| {
+                    Error::ParamError(format!("Unsupported CAIP-10 chain: {}", identity))
+                })?
+        } else {
+            (platform.parse::<Platform>()?, identity)
+        };
+        if !is_valid_address(&platform, &identity) {
+            return Err(Error::ParamError(format!(
+                "Invalid {} address: {}",
+                platform, identity
+            )));
+        }
+        // Nostr identities are stored in hex. A NIP-05 identifier is resolved to its pubkey,
+        // but still fetched as is, since only the identifier can be verified.
+        let mut nip05 = None;
+        let identity = match platform {
+            Platform::Nostr => match nostr_pubkey_to_hex(&identity) {
+                Some(hex) => hex,
+                None => {
+                    nip05 = Some(identity.clone());
+                    match nostr_nip05_to_hex(&identity).await {
+                        Ok(hex) => hex.unwrap_or(identity),
+                        Err(err) => {
+                            event!(
+                                Level::WARN,
+                                identity,
+                                err = err.to_string(),
+                                "Failed to resolve NIP-05"
+                            );
+                            identity
+                        }
+                    }
+                }
+            },
+            _ => identity,
+        };
+        // Farcaster identities are stored as fid, username is also accepted.
+        let identity = match platform {
+            Platform::Farcaster if identity.parse::<i64>().is_err() => {
+                match farcaster_fid_by_username(&identity).await {
+                    Ok(fid) => fid.unwrap_or(identity),
+                    Err(err) => {
+                        event!(
+                            Level::WARN,
+                            identity,
+                            err = err.to_string(),
+                            "Failed to resolve Farcaster username"
+                        );
+                        identity
+                    }
+                }
+            }
+            _ => identity,
+        };
+        let target = Target::Identity(platform, nip05.unwrap_or_else(|| identity.clone()));
         // FIXME: Still kinda dirty. Should be in an background queue/worker-like shape.
         match Identity::find_by_platform_identity(&db, &platform, &identity).await? {
             None => {
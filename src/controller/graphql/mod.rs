@@ -3,7 +3,11 @@ mod hold;
 mod identity;
 mod proof;
 mod resolve;
-use self::{hold::HoldQuery, identity::IdentityQuery, proof::ProofQuery, resolve::ResolveQuery};
+mod token;
+use self::{
+    contract::ContractQuery, hold::HoldQuery, identity::IdentityQuery, proof::ProofQuery,
+    resolve::ResolveQuery,
+};
 use async_graphql::{MergedObject, Object};
 const API_VERSION: &str = "0.1";
 
@@ -15,6 +19,7 @@ pub struct Query(
     ResolveQuery,
     ProofQuery,
     HoldQuery,
+    ContractQuery,
 );
 
 #[derive(Default)]
@@ -1,17 +1,14 @@
 use crate::{
     error::{Error, Result},
     graph::{
-        edge::{
-            resolve::{DomainNameSystem, ResolveEdge},
-            Resolve,
-        },
+        edge::resolve::{DomainNameSystem, ResolveEdge},
         vertex::{
             contract::{Chain, ContractCategory},
-            IdentityRecord,
+            Domain, IdentityRecord, PrimaryName,
         },
         ConnectionPool,
     },
-    upstream::{fetch_all, DataFetcher, DataSource, Target},
+    upstream::{fetch_all, space_id_tld, DataFetcher, DataSource, Target},
 };
 use async_graphql::{Context, Object};
 use strum::IntoEnumIterator;
@@ -64,6 +61,86 @@ impl ResolveEdge {
             Some(owner) => Ok(owner),
         }
     }
+
+    /// `reverseOf`: Wallets whose reverse record (primary name) is this domain.
+    async fn reverse_of(&self) -> Vec<IdentityRecord> {
+        self.reverse_of.clone()
+    }
+
+    /// Top-level domain (e.g., `eth`, `bit`)
+    async fn tld(&self) -> Option<String> {
+        self.domain.as_ref().map(|d| d.tld.clone())
+    }
+
+    /// When this domain expires. `null` if it never expires, or upstream doesn't tell.
+    async fn expires_at(&self) -> Option<i64> {
+        self.domain
+            .as_ref()
+            .and_then(|d| d.expired_at)
+            .map(|dt| dt.timestamp())
+    }
+
+    /// Until when the owner can still renew this domain after it expires.
+    async fn grace_period_ends_at(&self) -> Option<i64> {
+        self.domain
+            .as_ref()
+            .and_then(|d| d.grace_period_ended_at())
+            .map(|dt| dt.timestamp())
+    }
+
+    /// If this domain is expired.
+    async fn is_expired(&self) -> bool {
+        self.domain.as_ref().map_or(false, |d| d.is_expired())
+    }
+
+    /// Token ID of this domain as an NFT (if any).
+    async fn token_id(&self) -> Option<String> {
+        self.domain.as_ref().and_then(|d| d.token_id.clone())
+    }
+
+    /// Name of the parent domain (e.g., `vitalik.eth` of `pay.vitalik.eth`). `null` if this is not a subdomain.
+    async fn parent(&self) -> Option<String> {
+        self.parent
+            .as_ref()
+            .map(|d| d.name.clone())
+            .or_else(|| self.domain.as_ref().and_then(|d| d.parent_name()))
+    }
+
+    /// Names of known subdomains of this domain.
+    async fn subdomains(&self) -> Vec<String> {
+        self.subdomains.iter().map(|d| d.name.clone()).collect()
+    }
+
+    /// If this domain is wrapped by ENS NameWrapper.
+    async fn is_wrapped(&self) -> bool {
+        self.domain.as_ref().map_or(false, |d| d.is_wrapped())
+    }
+
+    /// Fuses burned by ENS NameWrapper. `null` if this domain is not wrapped.
+    async fn fuses(&self) -> Option<u32> {
+        self.domain.as_ref().and_then(|d| d.fuses)
+    }
+
+    /// If the owner of the parent domain can still replace or delete this subdomain.
+    /// Always `false` if this is not a subdomain.
+    async fn parent_can_control(&self) -> bool {
+        self.domain
+            .as_ref()
+            .map_or(false, |d| d.parent_can_control())
+    }
+}
+
+#[Object]
+impl PrimaryName {
+    /// Domain Name system
+    async fn system(&self) -> DomainNameSystem {
+        self.system
+    }
+
+    /// Name of domain (e.g., `vitalik.eth`, `dotbit.bit`)
+    async fn name(&self) -> String {
+        self.name.clone()
+    }
 }
 
 #[derive(Default)]
@@ -85,62 +162,52 @@ impl ResolveQuery {
         )]
         domain_system: DomainNameSystem,
         #[graphql(
-            desc = "Name of domain. For example the name is (name: \"abc.eth\") or (name: \"abc.bit\") or (name: \"abc.bnb\")"
+            desc = "Name of domain. For example the name is (name: \"abc.eth\") or (name: \"pay.abc.eth\") or (name: \"abc.bit\") or (name: \"abc.bnb\") or (name: \"abc.arb\") or (name: \"abc.sol\")"
         )]
         name: String,
+        #[graphql(desc = "Also return the domain if it is expired. `false` if omitted.")]
+        include_expired: Option<bool>,
     ) -> Result<Option<ResolveEdge>> {
         let pool: &ConnectionPool = ctx.data().map_err(|err| Error::PoolError(err.message))?;
         debug!("Connection pool status: {:?}", pool.status());
 
-        match domain_system {
-            DomainNameSystem::ENS => {
-                let target = Target::NFT(
-                    Chain::Ethereum,
-                    ContractCategory::ENS,
-                    ContractCategory::ENS.default_contract_address().unwrap(),
-                    name.clone(),
-                );
-                match Resolve::find_by_ens_name(&pool, &name).await? {
-                    None => {
-                        let _ = fetch_all(target).await;
-                        Resolve::find_by_ens_name(&pool, &name).await
-                    }
-                    Some(resolve) => {
-                        if resolve.is_outdated() {
-                            tokio::spawn(fetch_all(target));
-                        }
-                        Ok(Some(resolve))
-                    }
-                }
+        let target = match domain_system {
+            DomainNameSystem::ENS => Target::NFT(
+                Chain::Ethereum,
+                ContractCategory::ENS,
+                ContractCategory::ENS.default_contract_address().unwrap(),
+                name.clone(),
+            ),
+            // SPACE ID serves several TLDs on different chains, `.bnb` and `.arb` for now.
+            DomainNameSystem::SpaceId if space_id_tld(&name).is_none() => {
+                return Err(Error::ParamError(format!(
+                    "SPACE ID does not support the TLD of {}",
+                    name
+                )))
             }
             DomainNameSystem::DotBit
             | DomainNameSystem::Lens
             | DomainNameSystem::UnstoppableDomains
-            | DomainNameSystem::SpaceId => {
-                let platform = domain_system.into();
-                let target = Target::Identity(platform, name.clone());
-                match Resolve::find_by_domain_platform_name(&pool, &name, &domain_system, &platform)
-                    .await?
-                {
-                    None => {
-                        let _ = fetch_all(target).await;
-                        Resolve::find_by_domain_platform_name(
-                            &pool,
-                            &name,
-                            &domain_system,
-                            &platform,
-                        )
-                        .await
-                    }
-                    Some(resolve) => {
-                        if resolve.is_outdated() {
-                            tokio::spawn(fetch_all(target));
-                        }
-                        Ok(Some(resolve))
-                    }
+            | DomainNameSystem::SpaceId
+            | DomainNameSystem::SNS => Target::Identity(domain_system.into(), name.clone()),
+            _ => return Ok(None),
+        };
+        let found = match Domain::find_resolve_edge(pool, &domain_system, &name).await? {
+            None => {
+                let _ = fetch_all(target).await;
+                Domain::find_resolve_edge(pool, &domain_system, &name).await?
+            }
+            Some(resolve) => {
+                if resolve.is_outdated() {
+                    // Maybe renewed since then.
+                    tokio::spawn(fetch_all(target));
                 }
+                Some(resolve)
             }
-            _ => Ok(None),
-        }
+        };
+        let include_expired = include_expired.unwrap_or(false);
+        Ok(found.filter(|resolve| {
+            include_expired || !resolve.domain.as_ref().map_or(false, |d| d.is_expired())
+        }))
     }
 }
@@ -0,0 +1,82 @@
+use crate::{
+    graph::vertex::{contract::BuiltinChain, TokenAttribute, TokenRecord},
+    upstream::DataSource,
+};
+use async_graphql::Object;
+use uuid::Uuid;
+
+#[Object]
+impl TokenRecord {
+    /// UUID of this record.
+    async fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    /// On which chain? `unknown` for chains added only in config, see `chainName`.
+    async fn chain(&self) -> BuiltinChain {
+        self.chain.into()
+    }
+
+    /// Name of `chain` in the chain registry, chains added in config included.
+    async fn chain_name(&self) -> String {
+        self.chain.to_string()
+    }
+
+    /// Contract address of this token.
+    async fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    /// Token ID in contract. Same as `id` of the `Hold`.
+    async fn token_id(&self) -> String {
+        self.token_id.clone()
+    }
+
+    /// Name of this token.
+    async fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    /// Description of this token.
+    async fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    /// Image URL of this token.
+    async fn image(&self) -> Option<String> {
+        self.image.clone()
+    }
+
+    /// Traits of this token.
+    async fn attributes(&self) -> Vec<TokenAttribute> {
+        self.attributes.clone()
+    }
+
+    /// Where the metadata is served, i.e. `tokenURI()` of ERC721 or `uri()` of ERC1155.
+    async fn token_uri(&self) -> Option<String> {
+        self.token_uri.clone()
+    }
+
+    /// Data source (upstream) which provides this info.
+    async fn source(&self) -> DataSource {
+        self.source
+    }
+
+    /// When this data is fetched by RelationService.
+    async fn updated_at(&self) -> i64 {
+        self.updated_at.timestamp()
+    }
+}
+
+#[Object]
+impl TokenAttribute {
+    /// Name of this trait, e.g. `Background`.
+    async fn trait_type(&self) -> String {
+        self.trait_type.clone()
+    }
+
+    /// Value of this trait, e.g. `Blue`.
+    async fn value(&self) -> String {
+        self.value.clone()
+    }
+}
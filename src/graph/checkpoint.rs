@@ -0,0 +1,92 @@
+use crate::{error::Error, util::naive_now};
+use aragog::{
+    query::{Comparison, QueryResult},
+    DatabaseConnection, DatabaseRecord, Record,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Progress of an incremental import (e.g. SybilList), so that the next run
+/// only needs to deal with what has changed since then.
+#[derive(Debug, Clone, Deserialize, Serialize, Record)]
+#[collection_name = "Checkpoints"]
+pub struct Checkpoint {
+    /// Unique name of the import, e.g. `sybil_list`.
+    pub name: String,
+    /// `ETag` header of the last downloaded content (if upstream gives one).
+    pub etag: Option<String>,
+    /// Hash of the last downloaded content.
+    pub content_hash: Option<String>,
+    /// What was imported last time. Its format is defined by each importer.
+    pub snapshot: Value,
+    /// When this checkpoint is saved.
+    pub updated_at: NaiveDateTime,
+}
+
+impl Default for Checkpoint {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            etag: None,
+            content_hash: None,
+            snapshot: Value::Null,
+            updated_at: naive_now(),
+        }
+    }
+}
+
+impl Checkpoint {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub async fn find_by_name(
+        db: &DatabaseConnection,
+        name: &str,
+    ) -> Result<Option<DatabaseRecord<Checkpoint>>, Error> {
+        let query = Checkpoint::query().filter(Comparison::field("name").equals_str(name).into());
+        let result: QueryResult<Checkpoint> = query.call(db).await?;
+        Ok(result.first().cloned())
+    }
+
+    /// Create or replace the checkpoint with the same `name`.
+    pub async fn upsert(
+        &self,
+        db: &DatabaseConnection,
+    ) -> Result<DatabaseRecord<Checkpoint>, Error> {
+        let mut checkpoint = self.clone();
+        checkpoint.updated_at = naive_now();
+        match Self::find_by_name(db, &self.name).await? {
+            Some(mut found) => {
+                *found = checkpoint;
+                found.save(db).await?;
+                Ok(found)
+            }
+            None => Ok(DatabaseRecord::create(checkpoint, db).await?),
+        }
+    }
+
+    /// Cursor of a paginated fetch saved by `save_cursor`, to resume from.
+    pub async fn load_cursor(db: &DatabaseConnection, name: &str) -> Result<Option<String>, Error> {
+        Ok(Self::find_by_name(db, name)
+            .await?
+            .and_then(|found| found.snapshot["cursor"].as_str().map(String::from)))
+    }
+
+    /// Saves where a paginated fetch stops at its page cap, so the next fetch resumes from `cursor`.
+    /// `None` if it reached the last page, i.e. the next fetch starts over.
+    pub async fn save_cursor(
+        db: &DatabaseConnection,
+        name: &str,
+        cursor: Option<&str>,
+    ) -> Result<(), Error> {
+        let mut checkpoint = Self::new(name);
+        checkpoint.snapshot = json!({ "cursor": cursor });
+        checkpoint.upsert(db).await?;
+        Ok(())
+    }
+}
@@ -37,6 +37,7 @@ pub struct Hold {
     /// NFT_ID in contract / ENS domain / anything can be used as an unique ID to specify the held object.
     /// It must be one here.
     /// Tips: NFT_ID of ENS is a hash of domain. So domain can be used as NFT_ID.
+    /// Fungible tokens (ERC20) have no ID, the contract address is used instead.
     pub id: String,
     /// When the transaction happened. May not be provided by upstream.
     pub created_at: Option<NaiveDateTime>,
@@ -45,6 +46,26 @@ pub struct Hold {
     /// Who collects this data.
     /// It works as a "data cleansing" or "proxy" between `source`s and us.
     pub fetcher: DataFetcher,
+    /// ID of the event this token is issued for (i.e. POAP event ID), if any.
+    pub event_id: Option<String>,
+    /// Name of the event this token is issued for, if any.
+    pub event_name: Option<String>,
+    /// When the held object left this holder (transferred out / burned).
+    /// `None` if it is still held.
+    #[serde(default)]
+    pub ended_at: Option<NaiveDateTime>,
+    /// When the held object (i.e. a domain) expires, if it ever does.
+    #[serde(default)]
+    pub expired_at: Option<NaiveDateTime>,
+    /// How many of the held object, in its smallest unit (i.e. wei), as a decimal string.
+    /// `None` if upstream doesn't tell, which is counted as one.
+    /// Only balances read from chain (i.e. by `log_indexer`) are recorded,
+    /// quantities of single transfers (i.e. RSS3 `value`) are not balances.
+    #[serde(default)]
+    pub amount: Option<String>,
+    /// Decimals of `amount`, for fungible tokens (i.e. ERC20).
+    #[serde(default)]
+    pub decimals: Option<u8>,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -104,7 +125,8 @@ impl Hold {
         }
 
         let filter = Filter::new(Comparison::field("id").equals_str(id))
-            .and(Comparison::field("_to").equals_str(contract.unwrap().id()));
+            .and(Comparison::field("_to").equals_str(contract.unwrap().id()))
+            .and(Comparison::field("ended_at").is_null());
         let query = EdgeRecord::<Hold>::query().filter(filter);
         let result: QueryResult<EdgeRecord<Self>> = query.call(db).await?;
         if result.len() == 0 {
@@ -132,7 +154,7 @@ impl Hold {
         let aql_str = r"FOR c IN @@collection_name
             FILTER c.address == @address AND c.chain == @chain
             FOR vertex, edge IN 1..1 INBOUND c GRAPH @graph_name
-            FILTER edge.id == @id
+            FILTER edge.id == @id AND edge.ended_at == null
             RETURN edge
         ";
         let aql = AqlQuery::new(aql_str)
@@ -152,6 +174,152 @@ impl Hold {
         }
     }
 
+    /// Closes every still-open hold of `id` in `to` which started no later than `ended_at`,
+    /// i.e. the object is transferred out or burned at `ended_at`.
+    /// Hold of `except` (the new holder, if any) is kept open.
+    /// Returns how many holds are closed.
+    pub async fn close<T: Record + std::marker::Sync>(
+        db: &DatabaseConnection,
+        to: &DatabaseRecord<T>,
+        id: &str,
+        except: Option<&DatabaseRecord<Identity>>,
+        ended_at: NaiveDateTime,
+    ) -> Result<usize, Error> {
+        let filter = Filter::new(Comparison::field("_to").equals_str(to.id()))
+            .and(Comparison::field("id").equals_str(id))
+            .and(Comparison::field("ended_at").is_null());
+        let result: QueryResult<EdgeRecord<Self>> =
+            EdgeRecord::<Self>::query().filter(filter).call(db).await?;
+        let mut closed = 0;
+        for found in result.iter() {
+            if except.map_or(false, |e| found.id_from() == e.id()) {
+                continue;
+            }
+            if found.created_at.map_or(false, |c| c > ended_at) {
+                continue;
+            }
+            let mut found = found.clone();
+            found.ended_at = Some(ended_at);
+            found.updated_at = naive_now();
+            found.save(db).await?;
+            closed += 1;
+        }
+        Ok(closed)
+    }
+
+    /// Ends the hold of `id` in `to` from `from`, if it is still open.
+    pub async fn close_of<T: Record + std::marker::Sync>(
+        db: &DatabaseConnection,
+        from: &DatabaseRecord<Identity>,
+        to: &DatabaseRecord<T>,
+        id: &str,
+        ended_at: NaiveDateTime,
+    ) -> Result<bool, Error> {
+        match Self::find_by_from_to_id(db, from, to, id).await? {
+            Some(mut found) if found.ended_at.is_none() => {
+                found.ended_at = Some(ended_at);
+                found.updated_at = naive_now();
+                found.save(db).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Undoes what happened to holds of `to` since `since`, e.g. blocks dropped by a chain reorg:
+    /// holds started since then by `source` are removed, holds ended since then are open again.
+    /// Returns how many holds are changed.
+    pub async fn rollback<T: Record + std::marker::Sync>(
+        db: &DatabaseConnection,
+        to: &DatabaseRecord<T>,
+        source: &DataSource,
+        since: NaiveDateTime,
+    ) -> Result<usize, Error> {
+        let filter = Filter::new(Comparison::field("_to").equals_str(to.id()));
+        let result: QueryResult<EdgeRecord<Self>> =
+            EdgeRecord::<Self>::query().filter(filter).call(db).await?;
+        let mut changed = 0;
+        for found in result.iter() {
+            let mut found = found.clone();
+            if found.source == *source && found.created_at.map_or(false, |c| c >= since) {
+                found.delete(db).await?;
+            } else if found.ended_at.map_or(false, |e| e >= since) {
+                found.ended_at = None;
+                found.updated_at = naive_now();
+                found.save(db).await?;
+            } else {
+                continue;
+            }
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Re-opens a closed hold if the object is acquired again after it was closed.
+    async fn reopen(
+        &self,
+        db: &DatabaseConnection,
+        mut found: HoldRecord,
+    ) -> Result<HoldRecord, Error> {
+        let closed_at = match found.ended_at {
+            Some(closed_at) => closed_at,
+            None => return Ok(found),
+        };
+        if self.ended_at.is_none() && self.created_at.map_or(true, |c| c >= closed_at) {
+            found.ended_at = None;
+            found.created_at = self.created_at;
+            found.transaction = self.transaction.clone();
+            found.updated_at = naive_now();
+            found.save(db).await?;
+        }
+        Ok(found)
+    }
+
+    /// Records a renewed (or newly known) expiry date of the held object.
+    async fn renew(
+        &self,
+        db: &DatabaseConnection,
+        mut found: HoldRecord,
+    ) -> Result<HoldRecord, Error> {
+        if self.expired_at.is_some() && found.expired_at != self.expired_at {
+            found.expired_at = self.expired_at;
+            found.updated_at = naive_now();
+            found.save(db).await?;
+        }
+        Ok(found)
+    }
+
+    /// Records the latest balance of the held object, if upstream tells.
+    async fn rebalance(
+        &self,
+        db: &DatabaseConnection,
+        mut found: HoldRecord,
+    ) -> Result<HoldRecord, Error> {
+        if self.amount.is_some() && found.amount != self.amount {
+            found.amount = self.amount.clone();
+            found.decimals = self.decimals.or(found.decimals);
+            found.updated_at = naive_now();
+            found.save(db).await?;
+        }
+        Ok(found)
+    }
+
+    /// `amount` in whole units, i.e. divided by `10^decimals`.
+    /// Holding without a known amount counts as one.
+    pub fn balance(&self) -> f64 {
+        let amount: f64 = match self.amount.as_ref() {
+            Some(amount) => amount.parse().unwrap_or_default(),
+            None => return 1.0,
+        };
+        amount / 10f64.powi(self.decimals.unwrap_or_default() as i32)
+    }
+
+    /// If the held object is expired.
+    pub fn is_expired(&self) -> bool {
+        self.expired_at
+            .map_or(false, |expired_at| expired_at.lt(&naive_now()))
+    }
+
     pub fn is_outdated(&self) -> bool {
         let outdated_in = Duration::hours(8);
         self.updated_at
@@ -177,7 +345,11 @@ impl<T: Record + std::marker::Sync> Edge<Identity, T, HoldRecord> for Hold {
     ) -> Result<HoldRecord, Error> {
         let found = Self::find_by_from_to_id(db, from, to, &self.id).await?;
         match found {
-            Some(edge) => Ok(edge),
+            Some(edge) => {
+                let edge = self.reopen(db, edge).await?;
+                let edge = self.renew(db, edge).await?;
+                self.rebalance(db, edge).await
+            }
             None => Ok(DatabaseRecord::link(from, to, db, self.clone())
                 .await?
                 .into()),
@@ -232,6 +404,12 @@ mod tests {
                 created_at: Some(naive_now()),
                 updated_at: naive_now(),
                 fetcher: Default::default(),
+                event_id: None,
+                event_name: None,
+                ended_at: None,
+                expired_at: None,
+                amount: None,
+                decimals: None,
             }
         }
     }
@@ -259,4 +437,101 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_close_and_reopen() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let old_owner = Identity::create_dummy(&db).await?;
+        let new_owner = Identity::create_dummy(&db).await?;
+        let contract = Contract::create_dummy(&db).await?;
+        let transferred_at = naive_now() - Duration::days(1);
+
+        let mut hold: Hold = Faker.fake();
+        hold.created_at = Some(transferred_at - Duration::days(1));
+        hold.connect(&db, &old_owner, &contract).await?;
+        let new_hold = Hold {
+            uuid: Uuid::new_v4(),
+            created_at: Some(transferred_at),
+            ..hold.clone()
+        };
+        new_hold.connect(&db, &new_owner, &contract).await?;
+
+        let closed = Hold::close(
+            &db,
+            &contract.0,
+            &hold.id,
+            Some(&new_owner.0),
+            transferred_at,
+        )
+        .await?;
+        assert_eq!(closed, 1);
+        let found = Hold::find_by_from_to_id(&db, &old_owner, &contract, &hold.id)
+            .await?
+            .expect("Closed hold should be kept");
+        assert_eq!(found.ended_at, Some(transferred_at));
+        let current =
+            Hold::find_by_id_chain_address(&db, &hold.id, &contract.chain, &contract.address)
+                .await?
+                .expect("Should find the open hold");
+        assert_eq!(current.id_from(), new_owner.id());
+
+        // Transferred back to the old owner.
+        let back = Hold {
+            uuid: Uuid::new_v4(),
+            created_at: Some(naive_now()),
+            ..hold.clone()
+        };
+        let reopened = back.connect(&db, &old_owner, &contract).await?;
+        assert_eq!(reopened.ended_at, None);
+        assert_eq!(reopened.key(), found.key());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_renew() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let owner = Identity::create_dummy(&db).await?;
+        let domain = Identity::create_dummy(&db).await?;
+
+        let mut hold: Hold = Faker.fake();
+        hold.expired_at = Some(naive_now() - Duration::days(1));
+        let created = hold.connect(&db, &owner, &domain).await?;
+        assert!(created.is_expired());
+
+        // Renewed.
+        hold.expired_at = Some(naive_now() + Duration::days(365));
+        let renewed = hold.connect(&db, &owner, &domain).await?;
+        assert_eq!(renewed.key(), created.key());
+        assert!(!renewed.is_expired());
+
+        // Upstream doesn't tell this time.
+        hold.expired_at = None;
+        let kept = hold.connect(&db, &owner, &domain).await?;
+        assert!(kept.expired_at.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebalance() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let owner = Identity::create_dummy(&db).await?;
+        let contract = Contract::create_dummy(&db).await?;
+
+        let mut hold: Hold = Faker.fake();
+        let created = hold.connect(&db, &owner, &contract).await?;
+        assert_eq!(created.balance(), 1.0);
+
+        hold.amount = Some("1500000000000000000".into());
+        hold.decimals = Some(18);
+        let updated = hold.connect(&db, &owner, &contract).await?;
+        assert_eq!(updated.key(), created.key());
+        assert_eq!(updated.balance(), 1.5);
+
+        // Upstream doesn't tell this time.
+        hold.amount = None;
+        let kept = hold.connect(&db, &owner, &contract).await?;
+        assert_eq!(kept.amount, Some("1500000000000000000".into()));
+        Ok(())
+    }
 }
@@ -1,11 +1,19 @@
 pub mod hold;
+pub mod own;
 pub mod proof;
 pub mod resolve;
+pub mod resolves_to;
+pub mod reverse_of;
+pub mod subdomain;
 // mod pubkey_derivation;
 
 pub use hold::{Hold, HoldRecord};
+pub use own::{Own, OwnRecord};
 pub use proof::{IdentityFromToRecord, Proof, ProofRecord};
 pub use resolve::{Resolve, ResolveRecord};
+pub use resolves_to::{ResolvesTo, ResolvesToRecord};
+pub use reverse_of::{ReverseOf, ReverseOfRecord};
+pub use subdomain::{Subdomain, SubdomainRecord};
 
 use aragog::{DatabaseConnection, DatabaseRecord, Record};
 use async_trait::async_trait;
@@ -0,0 +1,127 @@
+use crate::{
+    error::Error,
+    graph::{
+        vertex::{Domain, Identity},
+        Edge,
+    },
+    upstream::{DataFetcher, DataSource},
+    util::naive_now,
+};
+use aragog::{
+    query::{Comparison, Filter, QueryResult},
+    DatabaseConnection, DatabaseRecord, EdgeRecord, Record,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Edge from the owner `Identity` to a `Domain` it owns.
+/// A domain has only one owner, so connecting a new owner removes the former one.
+#[derive(Clone, Serialize, Deserialize, Record, Debug)]
+#[collection_name = "Owns"]
+pub struct Own {
+    /// UUID of this record.
+    pub uuid: Uuid,
+    /// Data source (upstream) which provides this connection info.
+    pub source: DataSource,
+    /// Who collects this data.
+    /// It works as a "data cleansing" or "proxy" between `source`s and us.
+    pub fetcher: DataFetcher,
+    /// When this connection is fetched by us RelationService.
+    pub updated_at: NaiveDateTime,
+}
+
+impl Default for Own {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            source: Default::default(),
+            fetcher: Default::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct OwnRecord(DatabaseRecord<EdgeRecord<Own>>);
+impl std::ops::Deref for OwnRecord {
+    type Target = DatabaseRecord<EdgeRecord<Own>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl std::ops::DerefMut for OwnRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<DatabaseRecord<EdgeRecord<Own>>> for OwnRecord {
+    fn from(record: DatabaseRecord<EdgeRecord<Own>>) -> Self {
+        OwnRecord(record)
+    }
+}
+
+#[async_trait::async_trait]
+impl Edge<Identity, Domain, OwnRecord> for Own {
+    fn uuid(&self) -> Option<Uuid> {
+        Some(self.uuid)
+    }
+
+    async fn connect(
+        &self,
+        db: &DatabaseConnection,
+        from: &DatabaseRecord<Identity>,
+        to: &DatabaseRecord<Domain>,
+    ) -> Result<OwnRecord, Error> {
+        let query = EdgeRecord::<Own>::query()
+            .filter(Filter::new(Comparison::field("_to").equals_str(to.id())));
+        let result: QueryResult<EdgeRecord<Self>> = query.call(db).await?;
+        let mut found: Option<OwnRecord> = None;
+        for edge in result.iter() {
+            if edge.id_from() == from.id() && found.is_none() {
+                let mut edge: OwnRecord = edge.clone().into();
+                edge.source = self.source;
+                edge.updated_at = naive_now();
+                edge.save(db).await?;
+                found = Some(edge);
+            } else {
+                // Transferred to another owner.
+                edge.clone().delete(db).await?;
+            }
+        }
+        match found {
+            Some(edge) => Ok(edge),
+            None => Ok(DatabaseRecord::link(from, to, db, self.clone())
+                .await?
+                .into()),
+        }
+    }
+
+    /// Owner is never owned by its domain.
+    async fn two_way_binding(
+        &self,
+        _db: &DatabaseConnection,
+        _from: &DatabaseRecord<Identity>,
+        _to: &DatabaseRecord<Domain>,
+    ) -> Result<(OwnRecord, OwnRecord), Error> {
+        Err(Error::ParamError("Own edge is one-way only".to_string()))
+    }
+
+    async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &Uuid,
+    ) -> Result<Option<OwnRecord>, Error> {
+        let result: QueryResult<EdgeRecord<Self>> = EdgeRecord::<Self>::query()
+            .filter(Comparison::field("uuid").equals_str(uuid).into())
+            .call(db)
+            .await?;
+
+        if result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.first().unwrap().to_owned().into()))
+        }
+    }
+}
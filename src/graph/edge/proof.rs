@@ -8,7 +8,10 @@ use uuid::Uuid;
 
 use crate::{
     error::Error,
-    graph::{vertex::Identity, Edge},
+    graph::{
+        vertex::{Identity, IdentityRecord},
+        Edge,
+    },
     upstream::{DataFetcher, DataSource},
     util::naive_now,
 };
@@ -34,6 +37,10 @@ pub struct Proof {
     /// Who collects this data.
     /// It works as a "data cleansing" or "proxy" between `source`s and us.
     pub fetcher: DataFetcher,
+    /// When this connection is found removed from upstream (tombstone).
+    /// Tombstoned connections are excluded from neighbor queries.
+    #[serde(default)]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 impl Default for Proof {
@@ -45,6 +52,7 @@ impl Default for Proof {
             created_at: None,
             updated_at: naive_now(),
             fetcher: Default::default(),
+            deleted_at: None,
         }
     }
 }
@@ -73,6 +81,62 @@ impl Proof {
         }
     }
 
+    /// Tombstone all alive connections given by `source` with `record_id`.
+    /// Returns amount of connections tombstoned.
+    pub async fn tombstone(
+        db: &DatabaseConnection,
+        source: &DataSource,
+        record_id: &str,
+    ) -> Result<usize, Error> {
+        let filter = Filter::new(Comparison::field("source").equals_str(source))
+            .and(Comparison::field("record_id").equals_str(record_id))
+            .and(Comparison::field("deleted_at").is_null());
+        let result: QueryResult<EdgeRecord<Self>> =
+            EdgeRecord::<Self>::query().filter(filter).call(db).await?;
+        for found in result.iter() {
+            let mut found = found.clone();
+            found.deleted_at = Some(naive_now());
+            found.save(db).await?;
+        }
+        Ok(result.len())
+    }
+
+    /// Tombstone alive connections given by `source` with `record_id`, except those from `kept`.
+    /// Returns amount of connections tombstoned.
+    pub async fn tombstone_except(
+        db: &DatabaseConnection,
+        source: &DataSource,
+        record_id: &str,
+        kept: &[IdentityRecord],
+    ) -> Result<usize, Error> {
+        let filter = Filter::new(Comparison::field("source").equals_str(source))
+            .and(Comparison::field("record_id").equals_str(record_id))
+            .and(Comparison::field("deleted_at").is_null());
+        let result: QueryResult<EdgeRecord<Self>> =
+            EdgeRecord::<Self>::query().filter(filter).call(db).await?;
+        let mut tombstoned = 0;
+        for found in result.iter() {
+            if kept.iter().any(|k| k.id() == found.id_from()) {
+                continue;
+            }
+            let mut found = found.clone();
+            found.deleted_at = Some(naive_now());
+            found.save(db).await?;
+            tombstoned += 1;
+        }
+        Ok(tombstoned)
+    }
+
+    /// Connection fetched again from upstream is not deleted anymore.
+    async fn revive(db: &DatabaseConnection, mut found: ProofRecord) -> Result<ProofRecord, Error> {
+        if found.deleted_at.is_some() {
+            found.deleted_at = None;
+            found.updated_at = naive_now();
+            found.save(db).await?;
+        }
+        Ok(found)
+    }
+
     pub fn is_outdated(&self) -> bool {
         let outdated_in = Duration::days(1);
         self.updated_at
@@ -114,7 +178,7 @@ impl Edge<Identity, Identity, ProofRecord> for Proof {
     ) -> Result<ProofRecord, Error> {
         let found = Self::find_by_from_to(db, from, to, &self.source, &self.record_id).await?;
         match found {
-            Some(edge) => Ok(edge),
+            Some(edge) => Self::revive(db, edge).await,
             None => Ok(DatabaseRecord::link(from, to, db, self.clone())
                 .await?
                 .into()),
@@ -130,7 +194,7 @@ impl Edge<Identity, Identity, ProofRecord> for Proof {
     ) -> Result<(ProofRecord, ProofRecord), Error> {
         let forward =
             match Self::find_by_from_to(db, from, to, &self.source, &self.record_id).await? {
-                Some(edge) => edge,
+                Some(edge) => Self::revive(db, edge).await?,
                 None => DatabaseRecord::link(from, to, db, self.clone())
                     .await?
                     .into(),
@@ -138,7 +202,7 @@ impl Edge<Identity, Identity, ProofRecord> for Proof {
 
         let reverse =
             match Self::find_by_from_to(db, to, from, &self.source, &self.record_id).await? {
-                Some(edge) => edge,
+                Some(edge) => Self::revive(db, edge).await?,
                 None => DatabaseRecord::link(to, from, db, self.clone())
                     .await?
                     .into(),
@@ -159,6 +223,12 @@ impl std::ops::Deref for ProofRecord {
     }
 }
 
+impl std::ops::DerefMut for ProofRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 impl From<DatabaseRecord<EdgeRecord<Proof>>> for ProofRecord {
     fn from(record: DatabaseRecord<EdgeRecord<Proof>>) -> Self {
         ProofRecord(record)
@@ -212,6 +282,7 @@ mod tests {
                 created_at: Some(config.fake()),
                 updated_at: naive_now(),
                 fetcher: Default::default(),
+                deleted_at: None,
             }
         }
     }
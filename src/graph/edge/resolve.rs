@@ -1,14 +1,13 @@
 use crate::{
     error::Error,
-    graph::edge::{Hold, HoldRecord},
-    graph::vertex::{Identity, IdentityRecord},
-    graph::{ConnectionPool, Edge},
+    graph::vertex::{DomainRecord, IdentityRecord},
+    graph::Edge,
     upstream::{DataFetcher, DataSource, Platform},
     util::naive_now,
 };
 use aragog::{
     query::{Comparison, Filter, QueryResult},
-    AqlQuery, DatabaseAccess, DatabaseConnection, DatabaseRecord, EdgeRecord, Record,
+    DatabaseConnection, DatabaseRecord, EdgeRecord, Record,
 };
 use chrono::{Duration, NaiveDateTime};
 use serde::{Deserialize, Serialize};
@@ -56,12 +55,20 @@ pub enum DomainNameSystem {
     #[graphql(name = "unstoppabledomains")]
     UnstoppableDomains,
 
+    /// SPACE ID, `.bnb` on BSC and `.arb` on Arbitrum.
     /// https://api.prd.space.id/
     #[strum(serialize = "space_id")]
     #[serde(rename = "space_id")]
     #[graphql(name = "space_id")]
     SpaceId,
 
+    /// Solana Name Service (`.sol`)
+    /// https://www.sns.id
+    #[strum(serialize = "sns")]
+    #[serde(rename = "sns")]
+    #[graphql(name = "sns")]
+    SNS,
+
     #[default]
     #[strum(serialize = "unknown")]
     #[serde(rename = "unknown")]
@@ -76,20 +83,41 @@ impl From<DomainNameSystem> for Platform {
             DomainNameSystem::UnstoppableDomains => Platform::UnstoppableDomains,
             DomainNameSystem::Lens => Platform::Lens,
             DomainNameSystem::SpaceId => Platform::SpaceId,
+            DomainNameSystem::SNS => Platform::SNS,
             _ => Platform::Unknown,
         }
     }
 }
 
-/// Edge to identify which `Identity(Ethereum)` a `Contract` is resolving to.
-/// Basiclly this is served for `ENS` only.
-/// There're 3 kinds of relation between an `Identity(Ethereum)` and `Contract(ENS)` :
-/// - `Own` relation: defined in `graph/edge/own.rs`
-///   In our system: create `Own` edge from `Identity(Ethereum)` to `Contract(ENS)`.
-/// - `Resolve` relation: Find an Ethereum wallet using ENS name (like DNS).
-///   In our system: create `Resolve` edge from `Contract(ENS)` to `Identity(Ethereum)`.
-/// - `ReverseLookup` relation: Find an ENS name using an Ethereum wallet (like reverse DNS lookup).
-///   In our system: set `display_name` for the `Identity(Ethereum)`.
+impl DomainNameSystem {
+    /// Which system a domain `Identity` of `platform` belongs to.
+    pub fn of_platform(platform: &Platform) -> Option<Self> {
+        match platform {
+            Platform::Dotbit => Some(DomainNameSystem::DotBit),
+            Platform::UnstoppableDomains => Some(DomainNameSystem::UnstoppableDomains),
+            Platform::Lens => Some(DomainNameSystem::Lens),
+            Platform::SpaceId => Some(DomainNameSystem::SpaceId),
+            Platform::SNS => Some(DomainNameSystem::SNS),
+            _ => None,
+        }
+    }
+
+    /// How long an expired domain is still kept for its owner to renew.
+    pub fn grace_period(&self) -> Duration {
+        match self {
+            DomainNameSystem::ENS | DomainNameSystem::SpaceId | DomainNameSystem::DotBit => {
+                Duration::days(90)
+            }
+            _ => Duration::zero(),
+        }
+    }
+}
+
+/// Edge to identify which `Identity` a domain is resolving to, as reported by upstreams.
+/// Relations of a domain are unified on the `Domain` vertex (`graph/vertex/domain.rs`):
+/// - `Own`: `Identity` => `Domain`, defined in `graph/edge/own.rs`
+/// - `ResolvesTo`: `Domain` => `Identity` (like DNS), defined in `graph/edge/resolves_to.rs`
+/// - `ReverseOf`: `Domain` => `Identity` (like reverse DNS lookup), defined in `graph/edge/reverse_of.rs`
 #[derive(Clone, Serialize, Deserialize, Record, Debug)]
 #[collection_name = "Resolves"]
 pub struct Resolve {
@@ -141,158 +169,6 @@ impl Resolve {
         }
     }
 
-    pub async fn find_by_ens_name(
-        pool: &ConnectionPool,
-        name: &str,
-    ) -> Result<Option<ResolveEdge>, Error> {
-        let conn = pool
-            .get()
-            .await
-            .map_err(|err| Error::PoolError(err.to_string()))?;
-        let db = conn.database();
-
-        let aql_str = r###"
-            FOR r IN @@resolves
-                FILTER r.system == @system AND 
-                r.name == @name AND
-                CONTAINS(r._from, "Identities") AND
-                CONTAINS(r._to, "Contracts") SORT r.updated_at DESC
-                LET resolved = FIRST(FOR c IN @@identities FILTER c._id == r._from RETURN c)
-            FOR h IN @@holds
-                FILTER h.id == @name SORT h.updated_at DESC
-                LET owner = FIRST(FOR c IN @@identities FILTER c._id == h._from RETURN c)
-            RETURN {"record": r, "resolved": resolved, "owner": owner}"###;
-
-        let aql = AqlQuery::new(aql_str)
-            .bind_var("@resolves", Resolve::COLLECTION_NAME)
-            .bind_var("@holds", Hold::COLLECTION_NAME)
-            .bind_var("@identities", Identity::COLLECTION_NAME)
-            .bind_var("system", DomainNameSystem::ENS.to_string())
-            .bind_var("name", name.clone())
-            .batch_size(1)
-            .count(false);
-
-        let result: Vec<ResolveEdge> = db.aql_query(aql).await?;
-        if result.len() == 0 {
-            let aql_str = r###"
-            FOR h IN @@holds FILTER h.id == @name
-                LET owner = FIRST(FOR c IN @@identities FILTER c._id == h._from RETURN c)
-            RETURN {"record": h, "owner": owner}"###;
-
-            let aql = AqlQuery::new(aql_str)
-                .bind_var("@holds", Hold::COLLECTION_NAME)
-                .bind_var("@identities", Identity::COLLECTION_NAME)
-                .bind_var("name", name.clone())
-                .batch_size(1)
-                .count(false);
-
-            let res: Vec<HoldEdge> = db.aql_query(aql).await?;
-            if res.len() > 0 {
-                let r = res.first().unwrap().to_owned();
-                let mut resolve_edge = ResolveEdge::from(Resolve {
-                    uuid: r.record.uuid,
-                    source: r.record.source,
-                    system: DomainNameSystem::ENS,
-                    name: name.to_string(),
-                    fetcher: r.record.fetcher,
-                    updated_at: r.record.updated_at,
-                });
-                resolve_edge.owner = res.first().unwrap().to_owned().owner;
-                resolve_edge.resolved = None;
-                Ok(Some(resolve_edge))
-            } else {
-                Ok(None)
-            }
-        } else {
-            Ok(Some(result.first().unwrap().to_owned().into()))
-        }
-    }
-
-    pub async fn find_by_domain_platform_name(
-        pool: &ConnectionPool,
-        name: &str,
-        domain_system: &DomainNameSystem,
-        platform: &Platform,
-    ) -> Result<Option<ResolveEdge>, Error> {
-        let conn = pool
-            .get()
-            .await
-            .map_err(|err| Error::PoolError(err.to_string()))?;
-        let db = conn.database();
-
-        let aql = r###"
-        FOR r IN @@resolves
-            FILTER r.system == @system AND r.name == @name
-            LET resolved = FIRST(FOR c IN @@identities FILTER c._id == r._to RETURN c)
-        LET owner = FIRST(FOR i IN @@identities
-            FILTER i.platform == @platform AND i.identity == @identity
-            LIMIT 1
-            FOR vertex, edge, path
-                IN 1..1 ANY i @@holds
-                FILTER NOT CONTAINS(path.edges[*]._to, "Contracts")
-                RETURN DISTINCT vertex
-            )
-        RETURN {"record": r, "resolved": resolved, "owner": owner}"###;
-
-        let aql = AqlQuery::new(aql)
-            .bind_var("@resolves", Resolve::COLLECTION_NAME)
-            .bind_var("@holds", Hold::COLLECTION_NAME)
-            .bind_var("@identities", Identity::COLLECTION_NAME)
-            .bind_var("system", domain_system.to_string())
-            .bind_var("name", name.clone())
-            .bind_var("platform", platform.to_string())
-            .bind_var("identity", name.clone())
-            .batch_size(1)
-            .count(false);
-
-        let result: Vec<ResolveEdge> = db.aql_query(aql).await?;
-        if result.len() == 0 {
-            let aql_str = r###"
-            FOR i IN @@identities
-            FILTER i.platform == @platform AND i.identity == @identity
-            LIMIT 1
-            FOR vertex, edge, path
-                IN 1..1 ANY i @@holds
-                FILTER path.edges[*].source ALL == @platform
-                RETURN {"record": edge, "owner": i}"###;
-
-            let aql = AqlQuery::new(aql_str)
-                .bind_var("@holds", Hold::COLLECTION_NAME)
-                .bind_var("@identities", Identity::COLLECTION_NAME)
-                .bind_var("platform", platform.to_string())
-                .bind_var("identity", name.clone())
-                .batch_size(1)
-                .count(false);
-
-            let res: Vec<HoldEdge> = db.aql_query(aql).await?;
-            if res.len() > 0 {
-                let record = res.first().unwrap().to_owned().record;
-                let mut resolve_edge = ResolveEdge::from(Resolve {
-                    uuid: record.uuid,
-                    source: record.source,
-                    system: DomainNameSystem::DotBit,
-                    name: res
-                        .first()
-                        .unwrap()
-                        .to_owned()
-                        .owner
-                        .unwrap()
-                        .identity
-                        .clone(),
-                    fetcher: record.fetcher,
-                    updated_at: record.updated_at,
-                });
-                resolve_edge.owner = res.first().unwrap().to_owned().owner;
-                resolve_edge.resolved = None;
-                Ok(Some(resolve_edge))
-            } else {
-                Ok(None)
-            }
-        } else {
-            Ok(Some(result.first().unwrap().to_owned().into()))
-        }
-    }
-
     pub fn is_outdated(&self) -> bool {
         let outdated_in = Duration::days(1);
         self.updated_at
@@ -307,12 +183,16 @@ pub struct ResolveEdge {
     pub record: Resolve,
     pub resolved: Option<IdentityRecord>,
     pub owner: Option<IdentityRecord>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct HoldEdge {
-    pub record: HoldRecord,
-    pub owner: Option<IdentityRecord>,
+    /// Identities whose reverse record (primary name) is this domain.
+    #[serde(default)]
+    pub reverse_of: Vec<IdentityRecord>,
+    #[serde(default)]
+    pub domain: Option<DomainRecord>,
+    /// Parent domain, if this is a subdomain.
+    #[serde(default)]
+    pub parent: Option<DomainRecord>,
+    #[serde(default)]
+    pub subdomains: Vec<DomainRecord>,
 }
 
 impl std::ops::Deref for ResolveEdge {
@@ -333,6 +213,10 @@ impl From<Resolve> for ResolveEdge {
             record,
             resolved: None,
             owner: None,
+            reverse_of: vec![],
+            domain: None,
+            parent: None,
+            subdomains: vec![],
         }
     }
 }
@@ -444,33 +328,23 @@ impl<T1: Record + std::marker::Sync, T2: Record + std::marker::Sync> Edge<T1, T2
 
 #[cfg(test)]
 mod tests {
-    use crate::error::Error;
-    use crate::graph::arangopool::new_connection_pool;
     use crate::graph::edge::resolve::DomainNameSystem;
-    use crate::graph::edge::Resolve;
     use crate::upstream::Platform;
 
-    #[tokio::test]
-    async fn test_find_by_ens_name() -> Result<(), Error> {
-        let pool = new_connection_pool().await?;
-        let name = "zzfzz.eth";
-        let a = Resolve::find_by_ens_name(&pool, &name).await?;
-        print!("result: {:?}", a);
-        Ok(())
-    }
-
-    #[tokio::test]
-    async fn test_find_by_dotbit_name() -> Result<(), Error> {
-        let pool = new_connection_pool().await?;
-        let name = "thefuzezhong.bit";
-        let a = Resolve::find_by_domain_platform_name(
-            &pool,
-            &name,
-            &DomainNameSystem::DotBit,
-            &Platform::Dotbit,
-        )
-        .await?;
-        print!("result: {:?}", a);
-        Ok(())
+    #[test]
+    fn test_of_platform() {
+        for system in [
+            DomainNameSystem::DotBit,
+            DomainNameSystem::Lens,
+            DomainNameSystem::UnstoppableDomains,
+            DomainNameSystem::SpaceId,
+            DomainNameSystem::SNS,
+        ] {
+            assert_eq!(
+                DomainNameSystem::of_platform(&Platform::from(system)),
+                Some(system)
+            );
+        }
+        assert_eq!(DomainNameSystem::of_platform(&Platform::Ethereum), None);
     }
 }
@@ -0,0 +1,130 @@
+use crate::{
+    error::Error,
+    graph::{
+        vertex::{Domain, Identity},
+        Edge,
+    },
+    upstream::{DataFetcher, DataSource},
+    util::naive_now,
+};
+use aragog::{
+    query::{Comparison, Filter, QueryResult},
+    DatabaseConnection, DatabaseRecord, EdgeRecord, Record,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Edge from a `Domain` to the `Identity` it resolves to (like DNS).
+/// A domain resolves to only one address, so connecting a new one removes the former one.
+#[derive(Clone, Serialize, Deserialize, Record, Debug)]
+#[collection_name = "ResolvesTo"]
+pub struct ResolvesTo {
+    /// UUID of this record.
+    pub uuid: Uuid,
+    /// Data source (upstream) which provides this connection info.
+    pub source: DataSource,
+    /// Who collects this data.
+    /// It works as a "data cleansing" or "proxy" between `source`s and us.
+    pub fetcher: DataFetcher,
+    /// When this connection is fetched by us RelationService.
+    pub updated_at: NaiveDateTime,
+}
+
+impl Default for ResolvesTo {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            source: Default::default(),
+            fetcher: Default::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ResolvesToRecord(DatabaseRecord<EdgeRecord<ResolvesTo>>);
+impl std::ops::Deref for ResolvesToRecord {
+    type Target = DatabaseRecord<EdgeRecord<ResolvesTo>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl std::ops::DerefMut for ResolvesToRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<DatabaseRecord<EdgeRecord<ResolvesTo>>> for ResolvesToRecord {
+    fn from(record: DatabaseRecord<EdgeRecord<ResolvesTo>>) -> Self {
+        ResolvesToRecord(record)
+    }
+}
+
+#[async_trait::async_trait]
+impl Edge<Domain, Identity, ResolvesToRecord> for ResolvesTo {
+    fn uuid(&self) -> Option<Uuid> {
+        Some(self.uuid)
+    }
+
+    async fn connect(
+        &self,
+        db: &DatabaseConnection,
+        from: &DatabaseRecord<Domain>,
+        to: &DatabaseRecord<Identity>,
+    ) -> Result<ResolvesToRecord, Error> {
+        let query = EdgeRecord::<ResolvesTo>::query().filter(Filter::new(
+            Comparison::field("_from").equals_str(from.id()),
+        ));
+        let result: QueryResult<EdgeRecord<Self>> = query.call(db).await?;
+        let mut found: Option<ResolvesToRecord> = None;
+        for edge in result.iter() {
+            if edge.id_to() == to.id() && found.is_none() {
+                let mut edge: ResolvesToRecord = edge.clone().into();
+                edge.source = self.source;
+                edge.updated_at = naive_now();
+                edge.save(db).await?;
+                found = Some(edge);
+            } else {
+                // Resolves to another address now.
+                edge.clone().delete(db).await?;
+            }
+        }
+        match found {
+            Some(edge) => Ok(edge),
+            None => Ok(DatabaseRecord::link(from, to, db, self.clone())
+                .await?
+                .into()),
+        }
+    }
+
+    /// Use `ReverseOf` for the reverse direction.
+    async fn two_way_binding(
+        &self,
+        _db: &DatabaseConnection,
+        _from: &DatabaseRecord<Domain>,
+        _to: &DatabaseRecord<Identity>,
+    ) -> Result<(ResolvesToRecord, ResolvesToRecord), Error> {
+        Err(Error::ParamError(
+            "ResolvesTo edge is one-way only".to_string(),
+        ))
+    }
+
+    async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &Uuid,
+    ) -> Result<Option<ResolvesToRecord>, Error> {
+        let result: QueryResult<EdgeRecord<Self>> = EdgeRecord::<Self>::query()
+            .filter(Comparison::field("uuid").equals_str(uuid).into())
+            .call(db)
+            .await?;
+
+        if result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.first().unwrap().to_owned().into()))
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    error::Error,
+    graph::{
+        edge::resolve::DomainNameSystem,
+        vertex::{Domain, Identity},
+        Edge,
+    },
+    upstream::{DataFetcher, DataSource},
+    util::naive_now,
+};
+use aragog::{
+    query::{Comparison, Filter, QueryResult},
+    DatabaseConnection, DatabaseRecord, EdgeRecord, Record,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Edge from a `Domain` to the `Identity` whose reverse record (primary name) is this domain
+/// (like reverse DNS lookup).
+/// An address has only one reverse record in each `DomainNameSystem`, so connecting a new one
+/// removes the former one.
+#[derive(Clone, Serialize, Deserialize, Record, Debug)]
+#[collection_name = "ReverseOf"]
+pub struct ReverseOf {
+    /// UUID of this record.
+    pub uuid: Uuid,
+    /// Data source (upstream) which provides this connection info.
+    pub source: DataSource,
+    /// Domain Name system
+    pub system: DomainNameSystem,
+    /// Who collects this data.
+    /// It works as a "data cleansing" or "proxy" between `source`s and us.
+    pub fetcher: DataFetcher,
+    /// When this connection is fetched by us RelationService.
+    pub updated_at: NaiveDateTime,
+}
+
+impl Default for ReverseOf {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            source: Default::default(),
+            system: Default::default(),
+            fetcher: Default::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ReverseOfRecord(DatabaseRecord<EdgeRecord<ReverseOf>>);
+impl std::ops::Deref for ReverseOfRecord {
+    type Target = DatabaseRecord<EdgeRecord<ReverseOf>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl std::ops::DerefMut for ReverseOfRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<DatabaseRecord<EdgeRecord<ReverseOf>>> for ReverseOfRecord {
+    fn from(record: DatabaseRecord<EdgeRecord<ReverseOf>>) -> Self {
+        ReverseOfRecord(record)
+    }
+}
+
+impl ReverseOf {
+    /// Removes reverse records of `to` in `system`, i.e. they are reset to empty.
+    /// Returns how many edges are removed.
+    pub async fn clear(
+        db: &DatabaseConnection,
+        to: &DatabaseRecord<Identity>,
+        system: &DomainNameSystem,
+    ) -> Result<usize, Error> {
+        let filter = Filter::new(Comparison::field("_to").equals_str(to.id()))
+            .and(Comparison::field("system").equals_str(system));
+        let result: QueryResult<EdgeRecord<Self>> =
+            EdgeRecord::<Self>::query().filter(filter).call(db).await?;
+        for found in result.iter() {
+            found.clone().delete(db).await?;
+        }
+        Ok(result.len())
+    }
+}
+
+#[async_trait::async_trait]
+impl Edge<Domain, Identity, ReverseOfRecord> for ReverseOf {
+    fn uuid(&self) -> Option<Uuid> {
+        Some(self.uuid)
+    }
+
+    async fn connect(
+        &self,
+        db: &DatabaseConnection,
+        from: &DatabaseRecord<Domain>,
+        to: &DatabaseRecord<Identity>,
+    ) -> Result<ReverseOfRecord, Error> {
+        let filter = Filter::new(Comparison::field("_to").equals_str(to.id()))
+            .and(Comparison::field("system").equals_str(&self.system));
+        let query = EdgeRecord::<ReverseOf>::query().filter(filter);
+        let result: QueryResult<EdgeRecord<Self>> = query.call(db).await?;
+        let mut found: Option<ReverseOfRecord> = None;
+        for edge in result.iter() {
+            if edge.id_from() == from.id() && found.is_none() {
+                let mut edge: ReverseOfRecord = edge.clone().into();
+                edge.source = self.source;
+                edge.updated_at = naive_now();
+                edge.save(db).await?;
+                found = Some(edge);
+            } else {
+                // Reverse record is set to another domain.
+                edge.clone().delete(db).await?;
+            }
+        }
+        match found {
+            Some(edge) => Ok(edge),
+            None => Ok(DatabaseRecord::link(from, to, db, self.clone())
+                .await?
+                .into()),
+        }
+    }
+
+    /// Use `ResolvesTo` for the forward direction.
+    async fn two_way_binding(
+        &self,
+        _db: &DatabaseConnection,
+        _from: &DatabaseRecord<Domain>,
+        _to: &DatabaseRecord<Identity>,
+    ) -> Result<(ReverseOfRecord, ReverseOfRecord), Error> {
+        Err(Error::ParamError(
+            "ReverseOf edge is one-way only".to_string(),
+        ))
+    }
+
+    async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &Uuid,
+    ) -> Result<Option<ReverseOfRecord>, Error> {
+        let result: QueryResult<EdgeRecord<Self>> = EdgeRecord::<Self>::query()
+            .filter(Comparison::field("uuid").equals_str(uuid).into())
+            .call(db)
+            .await?;
+
+        if result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.first().unwrap().to_owned().into()))
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    error::Error,
+    graph::edge::resolve::DomainNameSystem,
+    graph::Edge,
+    upstream::{DataFetcher, DataSource},
+    util::naive_now,
+};
+use aragog::{
+    query::{Comparison, Filter, QueryResult},
+    DatabaseConnection, DatabaseRecord, EdgeRecord, Record,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Edge from a parent domain to one of its sub-domains.
+/// e.g. `Identity(Dotbit, abc.bit)` => `Identity(Dotbit, sub.abc.bit)`.
+#[derive(Clone, Serialize, Deserialize, Record, Debug)]
+#[collection_name = "Subdomains"]
+pub struct Subdomain {
+    /// UUID of this record. Generated by us to provide a better
+    /// global-uniqueness for future P2P-network data exchange
+    /// scenario.
+    pub uuid: Uuid,
+    /// Data source (upstream) which provides this connection info.
+    pub source: DataSource,
+    /// Domain Name system
+    pub system: DomainNameSystem,
+    /// Full name of the sub-domain (e.g., `sub.abc.bit`)
+    pub name: String,
+    /// Who collects this data.
+    /// It works as a "data cleansing" or "proxy" between `source`s and us.
+    pub fetcher: DataFetcher,
+    /// When this connection is fetched by us RelationService.
+    pub updated_at: NaiveDateTime,
+}
+
+impl Default for Subdomain {
+    fn default() -> Self {
+        Self {
+            uuid: Default::default(),
+            source: Default::default(),
+            system: Default::default(),
+            name: Default::default(),
+            fetcher: Default::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct SubdomainRecord(DatabaseRecord<EdgeRecord<Subdomain>>);
+impl std::ops::Deref for SubdomainRecord {
+    type Target = DatabaseRecord<EdgeRecord<Subdomain>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl std::ops::DerefMut for SubdomainRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<DatabaseRecord<EdgeRecord<Subdomain>>> for SubdomainRecord {
+    fn from(record: DatabaseRecord<EdgeRecord<Subdomain>>) -> Self {
+        SubdomainRecord(record)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T1: Record + std::marker::Sync, T2: Record + std::marker::Sync> Edge<T1, T2, SubdomainRecord>
+    for Subdomain
+{
+    fn uuid(&self) -> Option<Uuid> {
+        Some(self.uuid)
+    }
+
+    async fn connect(
+        &self,
+        db: &DatabaseConnection,
+        from: &DatabaseRecord<T1>,
+        to: &DatabaseRecord<T2>,
+    ) -> Result<SubdomainRecord, Error> {
+        let filter = Filter::new(Comparison::field("_from").equals_str(from.id()))
+            .and(Comparison::field("_to").equals_str(to.id()))
+            .and(Comparison::field("system").equals_str(&self.system));
+        let query = EdgeRecord::<Subdomain>::query().filter(filter);
+        let result: QueryResult<EdgeRecord<Self>> = query.call(db).await?;
+        if result.len() == 0 {
+            Ok(DatabaseRecord::link(from, to, db, self.clone())
+                .await?
+                .into())
+        } else {
+            Ok(result.first().unwrap().clone().into())
+        }
+    }
+
+    /// A parent domain is never a sub-domain of its sub-domain.
+    async fn two_way_binding(
+        &self,
+        _db: &DatabaseConnection,
+        _from: &DatabaseRecord<T1>,
+        _to: &DatabaseRecord<T2>,
+    ) -> Result<(SubdomainRecord, SubdomainRecord), Error> {
+        Err(Error::ParamError(
+            "Subdomain edge is one-way only".to_string(),
+        ))
+    }
+
+    async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &Uuid,
+    ) -> Result<Option<SubdomainRecord>, Error> {
+        let result: QueryResult<EdgeRecord<Self>> = EdgeRecord::<Self>::query()
+            .filter(Comparison::field("uuid").equals_str(uuid).into())
+            .call(db)
+            .await?;
+
+        if result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.first().unwrap().to_owned().into()))
+        }
+    }
+}
@@ -1,11 +1,12 @@
 pub mod arangopool;
+pub mod checkpoint;
 pub mod edge;
 mod tests;
 pub mod vertex;
 use std::collections::HashMap;
 
 use crate::{config::C, error::Error};
-use aragog::{AuthMode, DatabaseConnection, OperationOptions};
+use aragog::{AuthMode, DatabaseConnection, DatabaseRecord, OperationOptions};
 pub use arangopool::ConnectionPool;
 use arangors_lite::{
     view::ArangoSearchViewLink, view::ArangoSearchViewPropertiesOptions, view::ViewDescription,
@@ -16,8 +17,11 @@ use serde::Deserialize;
 pub use vertex::Vertex;
 
 use self::{
-    edge::{Hold, HoldRecord, Proof, Resolve},
-    vertex::{Contract, ContractRecord, Identity, IdentityRecord},
+    edge::{
+        resolve::DomainNameSystem, Hold, HoldRecord, Own, Proof, Resolve, ResolvesTo, ReverseOf,
+        Subdomain,
+    },
+    vertex::{Contract, ContractRecord, Domain, Identity, IdentityRecord},
 };
 
 // TODO: move this under `vertex/`
@@ -94,6 +98,7 @@ pub async fn create_identity_to_identity_hold_record(
     let from_record = from.create_or_update(db).await?;
     let to_record = to.create_or_update(db).await?;
     hold.connect(db, &from_record, &to_record).await?;
+    connect_domain_owner(db, &from_record, &to_record, hold).await?;
     Ok(())
 }
 
@@ -106,9 +111,121 @@ pub async fn create_domain_resolve_record(
     let from_record = from.create_or_update(db).await?;
     let to_record = to.create_or_update(db).await?;
     resolve.connect(db, &from_record, &to_record).await?;
+    connect_domain_resolve(db, &from_record, &to_record, resolve).await?;
     Ok(())
 }
 
+/// Connects `owner` to the `Domain` it owns.
+pub async fn create_domain_own_record(
+    db: &DatabaseConnection,
+    owner: &DatabaseRecord<Identity>,
+    domain: &Domain,
+    own: &Own,
+) -> Result<(), Error> {
+    let domain_record = domain.create_or_update(db).await?;
+    own.connect(db, owner, &domain_record).await?;
+    Ok(())
+}
+
+/// Connects the `Domain` to the `Identity` it resolves to.
+pub async fn create_domain_resolves_to_record(
+    db: &DatabaseConnection,
+    domain: &Domain,
+    resolved: &DatabaseRecord<Identity>,
+    resolves_to: &ResolvesTo,
+) -> Result<(), Error> {
+    let domain_record = domain.create_or_update(db).await?;
+    resolves_to.connect(db, &domain_record, resolved).await?;
+    Ok(())
+}
+
+/// Connects the `Domain` to the `Identity` whose reverse record is this domain.
+pub async fn create_domain_reverse_of_record(
+    db: &DatabaseConnection,
+    domain: &Domain,
+    reverse: &DatabaseRecord<Identity>,
+    reverse_of: &ReverseOf,
+) -> Result<(), Error> {
+    let domain_record = domain.create_or_update(db).await?;
+    reverse_of.connect(db, &domain_record, reverse).await?;
+    Ok(())
+}
+
+/// Connects the parent `Domain` to its subdomain.
+pub async fn create_domain_subdomain_record(
+    db: &DatabaseConnection,
+    parent: &Domain,
+    sub: &Domain,
+    subdomain: &Subdomain,
+) -> Result<(), Error> {
+    let parent_record = parent.create_or_update(db).await?;
+    let sub_record = sub.create_or_update(db).await?;
+    subdomain.connect(db, &parent_record, &sub_record).await?;
+    Ok(())
+}
+
+/// If `domain` is an `Identity` of a domain platform (e.g. `Identity(Dotbit, abc.bit)`),
+/// connects the `owner` holding it to its `Domain`.
+pub async fn connect_domain_owner(
+    db: &DatabaseConnection,
+    owner: &DatabaseRecord<Identity>,
+    domain: &DatabaseRecord<Identity>,
+    hold: &Hold,
+) -> Result<(), Error> {
+    let system = match DomainNameSystem::of_platform(&domain.platform) {
+        Some(system) => system,
+        None => return Ok(()),
+    };
+    if hold.ended_at.is_some() {
+        return Ok(());
+    }
+    let own = Own {
+        source: hold.source,
+        fetcher: hold.fetcher,
+        ..Default::default()
+    };
+    let domain = Domain {
+        expired_at: hold.expired_at,
+        ..Domain::new(system, &domain.identity)
+    };
+    create_domain_own_record(db, owner, &domain, &own).await
+}
+
+/// Mirrors a `Resolve` between a domain `Identity` and an address onto the `Domain`:
+/// `domain => address` becomes `ResolvesTo`, `address => domain` becomes `ReverseOf`.
+pub async fn connect_domain_resolve(
+    db: &DatabaseConnection,
+    from: &DatabaseRecord<Identity>,
+    to: &DatabaseRecord<Identity>,
+    resolve: &Resolve,
+) -> Result<(), Error> {
+    let domain = Domain::new(resolve.system, &resolve.name);
+    if is_domain_of(from, resolve) {
+        let resolves_to = ResolvesTo {
+            source: resolve.source,
+            fetcher: resolve.fetcher,
+            ..Default::default()
+        };
+        create_domain_resolves_to_record(db, &domain, to, &resolves_to).await
+    } else if is_domain_of(to, resolve) {
+        let reverse_of = ReverseOf {
+            source: resolve.source,
+            system: resolve.system,
+            fetcher: resolve.fetcher,
+            ..Default::default()
+        };
+        create_domain_reverse_of_record(db, &domain, from, &reverse_of).await
+    } else {
+        Ok(())
+    }
+}
+
+/// If `identity` is the domain `resolve` is about.
+fn is_domain_of(identity: &Identity, resolve: &Resolve) -> bool {
+    DomainNameSystem::of_platform(&identity.platform) == Some(resolve.system)
+        && identity.identity.eq_ignore_ascii_case(&resolve.name)
+}
+
 // Create a row database connection instance for arangodb
 pub async fn new_raw_db_connection() -> Result<Database, Error> {
     let conn = Connection::establish_basic_auth(&C.db.host, &C.db.username, &C.db.password).await?;
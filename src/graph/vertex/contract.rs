@@ -1,6 +1,8 @@
 use crate::{
+    config::CHAINS,
     error::Error,
     graph::edge::Hold,
+    graph::vertex::IdentityRecord,
     graph::{ConnectionPool, Vertex},
     util::naive_now,
 };
@@ -11,188 +13,313 @@ use aragog::{
 // use arangors_lite::AqlQuery;
 use chrono::{Duration, NaiveDateTime};
 use dataloader::BatchFn;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, value::Value};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 use tracing::debug;
 use uuid::Uuid;
 
 /// List of chains supported by RelationService.
-#[derive(
-    Default,
-    Clone,
-    Copy,
-    Serialize,
-    Deserialize,
-    Debug,
-    Display,
-    PartialEq,
-    Eq,
-    async_graphql::Enum,
-    EnumString,
-    EnumIter,
-    Hash,
-)]
+/// Chains added only in `[[chains]]` config are `Chain::Custom`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, EnumIter, Hash)]
 pub enum Chain {
     /// The Blockchain.
-    #[serde(rename = "ethereum")]
-    #[strum(serialize = "ethereum")]
-    #[graphql(name = "ethereum")]
     Ethereum,
 
     /// Deprecated since `The Merge`.
-    #[serde(rename = "rinkeby")]
-    #[strum(serialize = "rinkeby")]
-    #[graphql(name = "rinkeby")]
     Rinkeby,
 
     /// Deprecated since `The Merge`.
-    #[serde(rename = "ropsten")]
-    #[strum(serialize = "ropsten")]
-    #[graphql(name = "ropsten")]
     Ropsten,
 
     /// Deprecated since `The Merge`.
-    #[serde(rename = "kovan")]
-    #[strum(serialize = "kovan")]
-    #[graphql(name = "kovan")]
     Kovan,
 
     /// A cross-client proof-of-authority testing network for Ethereum.
     /// https://goerli.net
-    #[serde(rename = "goerli")]
-    #[strum(serialize = "goerli")]
-    #[graphql(name = "goerli")]
     Goerli,
 
     /// Sepolia is expected to undergo `The Merge` to proof-of-stake in summer 2022.
     /// https://sepolia.dev
-    #[serde(rename = "sepolia")]
-    #[strum(serialize = "sepolia")]
-    #[graphql(name = "sepolia")]
     Sepolia,
 
     /// BNB Smart Chain (BSC) (Previously Binance Smart Chain) - EVM compatible, consensus layers, and with hubs to multi-chains.
     /// https://www.binance.com/en/support/announcement/854415cf3d214371a7b60cf01ead0918
-    #[serde(rename = "bsc")]
-    #[strum(serialize = "bsc", serialize = "binance_smart_chain")]
-    #[graphql(name = "bsc")]
     BNBSmartChain,
 
     /// Polygon is a decentralised Ethereum scaling platform that enables developers to build scalable user-friendly dApps with low transaction fees without ever sacrificing on security.
     /// https://polygon.technology
-    #[serde(rename = "polygon")]
-    #[strum(serialize = "polygon")]
-    #[graphql(name = "polygon")]
     Polygon,
 
     /// Polygon Testnet
     /// https://mumbai.polygonscan.com
-    #[serde(rename = "mumbai")]
-    #[strum(serialize = "mumbai")]
-    #[graphql(name = "mumbai")]
     Mumbai,
 
     /// Solana is a decentralized blockchain built to enable scalable, user-friendly apps for the world.
     /// https://solana.com
-    #[serde(rename = "solana")]
-    #[strum(serialize = "solana")]
-    #[graphql(name = "solana")]
     Solana,
 
     /// Conflux is a new secure and reliable public blockchain with very high performance and scalability.
     /// https://developer.confluxnetwork.org
-    #[serde(rename = "conflux")]
-    #[strum(serialize = "conflux")]
-    #[graphql(name = "conflux")]
     Conflux,
 
     /// Conflux has a virtual machine that is similar to the EVM.
     /// https://evm.confluxscan.io
     /// https://developer.confluxnetwork.org/conflux-doc/docs/EVM-Space/intro_of_evm_space
-    #[serde(rename = "conflux_espace")]
-    #[strum(serialize = "conflux_espace")]
-    #[graphql(name = "conflux_espace")]
     ConfluxESpace,
 
-    #[serde(rename = "ethereum_classic")]
-    #[strum(serialize = "ethereum_classic")]
-    #[graphql(name = "ethereum_classic")]
     EthereumClassic,
 
     /// https://zksync.io
-    #[serde(rename = "zksync")]
-    #[strum(serialize = "zksync")]
-    #[graphql(name = "zksync")]
     ZKSync,
 
-    #[serde(rename = "xdai")]
-    #[strum(serialize = "xdai")]
-    #[graphql(name = "xdai")]
     Xdai,
     /// Gnosis Chain provides stability, scalability and an extendable beacon chain framework.
     /// Established in 2018 as the xDai Chain, the updated Gnosis Chain gives devs the tools and resources they need to create enhanced user experiences and optimized applications.
     /// https://developers.gnosischain.com
-    #[serde(rename = "gnosis")]
-    #[strum(serialize = "gnosis")]
-    #[graphql(name = "gnosis")]
     Gnosis,
 
     /// Arweave enables you to store documents and applications forever.
     /// https://www.arweave.org
-    #[serde(rename = "arweave")]
-    #[strum(serialize = "arweave")]
-    #[graphql(name = "arweave")]
     Arweave,
 
     /// Arbitrum One
     /// http://arbiscan.io
-    #[serde(rename = "arbitrum")]
-    #[strum(serialize = "arbitrum")]
-    #[graphql(name = "arbitrum")]
     Arbitrum,
 
     /// Optimism is a low-cost and lightning-fast Ethereum L2 blockchain.
     /// https://www.optimism.io
-    #[serde(rename = "optimism")]
-    #[strum(serialize = "optimism")]
-    #[graphql(name = "optimism")]
     Optimism,
 
-    #[serde(rename = "crossbell")]
-    #[strum(serialize = "crossbell")]
-    #[graphql(name = "crossbell")]
     Crossbell,
 
     /// Avalanche is an open, programmable smart contracts platform for decentralized applications.
     /// https://www.avax.com/
-    #[serde(rename = "avalanche")]
-    #[strum(serialize = "avalanche")]
-    #[graphql(name = "avalanche")]
     Avalanche,
 
     /// Fantom is a highly scalable blockchain platform for DeFi, crypto dApps, and enterprise applications.
     /// https://fantom.foundation/
-    #[serde(rename = "fantom")]
-    #[strum(serialize = "fantom")]
-    #[graphql(name = "fantom")]
     Fantom,
 
     /// Celo is the carbon-negative, mobile-first, EVM-compatible blockchain ecosystem leading a thriving new digital economy for all.
     /// https://celo.org/
-    #[serde(rename = "celo")]
-    #[strum(serialize = "celo")]
-    #[graphql(name = "celo")]
     Celo,
 
     #[default]
-    #[serde(rename = "unknown")]
-    #[strum(serialize = "unknown")]
+    Unknown,
+
+    /// A chain in the chain registry which has no variant of its own.
+    /// Holds its name, borrowed from the registry.
+    Custom(&'static str),
+}
+
+impl Chain {
+    /// Name of this chain, i.e. its key in the chain registry.
+    pub fn name(&self) -> &'static str {
+        use Chain::*;
+        match self {
+            Ethereum => "ethereum",
+            Rinkeby => "rinkeby",
+            Ropsten => "ropsten",
+            Kovan => "kovan",
+            Goerli => "goerli",
+            Sepolia => "sepolia",
+            BNBSmartChain => "bsc",
+            Polygon => "polygon",
+            Mumbai => "mumbai",
+            Solana => "solana",
+            Conflux => "conflux",
+            ConfluxESpace => "conflux_espace",
+            EthereumClassic => "ethereum_classic",
+            ZKSync => "zksync",
+            Xdai => "xdai",
+            Gnosis => "gnosis",
+            Arweave => "arweave",
+            Arbitrum => "arbitrum",
+            Optimism => "optimism",
+            Crossbell => "crossbell",
+            Avalanche => "avalanche",
+            Fantom => "fantom",
+            Celo => "celo",
+            Unknown => "unknown",
+            Custom(name) => *name,
+        }
+    }
+
+    /// All chains which have a variant of their own, i.e. all but `Chain::Custom`.
+    pub fn builtin() -> impl Iterator<Item = Chain> {
+        Chain::iter().filter(|chain| !matches!(chain, Chain::Custom(_)))
+    }
+
+    /// All chains in the chain registry, plus `Chain::Unknown`.
+    pub fn all() -> Vec<Chain> {
+        let mut custom: Vec<Chain> = CHAIN_REGISTRY
+            .keys()
+            .filter_map(|name| Chain::from_str(name).ok())
+            .filter(|chain| matches!(chain, Chain::Custom(_)))
+            .collect();
+        custom.sort_by_key(|chain| chain.name());
+        Chain::builtin().chain(custom).collect()
+    }
+
+    /// Builtin chain of this name (or alias).
+    fn builtin_by_name(name: &str) -> Option<Chain> {
+        if name == "binance_smart_chain" {
+            return Some(Chain::BNBSmartChain);
+        }
+        Chain::builtin().find(|chain| chain.name() == name)
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Chain {
+    type Err = strum::ParseError;
+
+    /// Parses a builtin chain, or a chain added in `[[chains]]` config.
+    /// Names not in the chain registry are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(chain) = Chain::builtin_by_name(s) {
+            return Ok(chain);
+        }
+        CHAIN_REGISTRY
+            .get_key_value(s)
+            .map(|(name, _)| Chain::Custom(name.as_str()))
+            .ok_or(strum::ParseError::VariantNotFound)
+    }
+}
+
+impl Serialize for Chain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Chain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Chain::from_str(&name)
+            .map_err(|_| serde::de::Error::custom(format!("unknown chain: {}", name)))
+    }
+}
+
+/// `Chain` in GraphQL schema, i.e. builtin chains.
+/// Chains added only in `[[chains]]` config are `unknown` here, and are given by name instead.
+#[derive(
+    Default, Clone, Copy, Debug, PartialEq, Eq, async_graphql::Enum, EnumString, Display, EnumIter,
+)]
+#[graphql(name = "Chain")]
+pub enum BuiltinChain {
+    #[graphql(name = "ethereum")]
+    #[strum(serialize = "ethereum")]
+    Ethereum,
+    #[graphql(name = "rinkeby")]
+    #[strum(serialize = "rinkeby")]
+    Rinkeby,
+    #[graphql(name = "ropsten")]
+    #[strum(serialize = "ropsten")]
+    Ropsten,
+    #[graphql(name = "kovan")]
+    #[strum(serialize = "kovan")]
+    Kovan,
+    #[graphql(name = "goerli")]
+    #[strum(serialize = "goerli")]
+    Goerli,
+    #[graphql(name = "sepolia")]
+    #[strum(serialize = "sepolia")]
+    Sepolia,
+    #[graphql(name = "bsc")]
+    #[strum(serialize = "bsc")]
+    BNBSmartChain,
+    #[graphql(name = "polygon")]
+    #[strum(serialize = "polygon")]
+    Polygon,
+    #[graphql(name = "mumbai")]
+    #[strum(serialize = "mumbai")]
+    Mumbai,
+    #[graphql(name = "solana")]
+    #[strum(serialize = "solana")]
+    Solana,
+    #[graphql(name = "conflux")]
+    #[strum(serialize = "conflux")]
+    Conflux,
+    #[graphql(name = "conflux_espace")]
+    #[strum(serialize = "conflux_espace")]
+    ConfluxESpace,
+    #[graphql(name = "ethereum_classic")]
+    #[strum(serialize = "ethereum_classic")]
+    EthereumClassic,
+    #[graphql(name = "zksync")]
+    #[strum(serialize = "zksync")]
+    ZKSync,
+    #[graphql(name = "xdai")]
+    #[strum(serialize = "xdai")]
+    Xdai,
+    #[graphql(name = "gnosis")]
+    #[strum(serialize = "gnosis")]
+    Gnosis,
+    #[graphql(name = "arweave")]
+    #[strum(serialize = "arweave")]
+    Arweave,
+    #[graphql(name = "arbitrum")]
+    #[strum(serialize = "arbitrum")]
+    Arbitrum,
+    #[graphql(name = "optimism")]
+    #[strum(serialize = "optimism")]
+    Optimism,
+    #[graphql(name = "crossbell")]
+    #[strum(serialize = "crossbell")]
+    Crossbell,
+    #[graphql(name = "avalanche")]
+    #[strum(serialize = "avalanche")]
+    Avalanche,
+    #[graphql(name = "fantom")]
+    #[strum(serialize = "fantom")]
+    Fantom,
+    #[graphql(name = "celo")]
+    #[strum(serialize = "celo")]
+    Celo,
+    #[default]
     #[graphql(name = "unknown")]
+    #[strum(serialize = "unknown")]
     Unknown,
 }
 
+impl From<BuiltinChain> for Chain {
+    fn from(chain: BuiltinChain) -> Self {
+        Chain::builtin_by_name(&chain.to_string()).unwrap_or_default()
+    }
+}
+
+impl From<Chain> for BuiltinChain {
+    fn from(chain: Chain) -> Self {
+        BuiltinChain::from_str(chain.name()).unwrap_or_default()
+    }
+}
+
+impl BuiltinChain {
+    /// Chain given by a pair of GraphQL arguments, `chain` or `chainName`.
+    /// `chainName` is any chain in the registry, chains added in config included.
+    pub fn or_named(chain: Option<BuiltinChain>, name: Option<String>) -> Result<Chain, Error> {
+        match (chain, name) {
+            (_, Some(name)) => Chain::from_str(&name)
+                .map_err(|_| Error::GraphQLError(format!("Unknown chain: {}", name))),
+            (Some(chain), None) => Ok(chain.into()),
+            (None, None) => Err(Error::GraphQLError(
+                "Either chain or chainName is required.".into(),
+            )),
+        }
+    }
+}
+
 /// Internal chain implementation / framework.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum ChainType {
@@ -206,43 +333,278 @@ pub enum ChainType {
     /// Basiclly an EVM, but with different address serializer, transaction packaging and genesis contracts.
     Conflux,
 }
-impl Default for ChainType {
-    fn default() -> Self {
-        Chain::default().chain_type()
+
+/// Family of a chain in the chain registry. Chains of an unsupported family are `Other`.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainFamily {
+    EVM,
+    Solana,
+    ZKSync,
+    Arweave,
+    Conflux,
+    #[serde(other)]
+    Other,
+}
+
+/// An entry of the chain registry, i.e. `[[chains]]` in config.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+pub struct ChainInfo {
+    /// Name of the chain. Same as the name of its `Chain`, e.g. `ethereum`, `bsc`.
+    pub name: String,
+    /// CAIP-2 chain ID, e.g. `eip155:1`.
+    pub chain_id: String,
+    pub family: ChainFamily,
+    /// JSON-RPC endpoint.
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    /// Block explorer, e.g. `https://etherscan.io`.
+    #[serde(default)]
+    pub explorer_url: Option<String>,
+    /// Symbol of the native token, e.g. `ETH`.
+    #[serde(default)]
+    pub native_symbol: Option<String>,
+}
+
+/// Built-in chain registry: `(name, CAIP-2 chain ID, family, explorer URL, native symbol)`.
+const BUILTIN_CHAINS: &[(&str, &str, ChainFamily, &str, &str)] = &[
+    (
+        "ethereum",
+        "eip155:1",
+        ChainFamily::EVM,
+        "https://etherscan.io",
+        "ETH",
+    ),
+    (
+        "rinkeby",
+        "eip155:4",
+        ChainFamily::EVM,
+        "https://rinkeby.etherscan.io",
+        "ETH",
+    ),
+    (
+        "ropsten",
+        "eip155:3",
+        ChainFamily::EVM,
+        "https://ropsten.etherscan.io",
+        "ETH",
+    ),
+    (
+        "kovan",
+        "eip155:42",
+        ChainFamily::EVM,
+        "https://kovan.etherscan.io",
+        "ETH",
+    ),
+    (
+        "goerli",
+        "eip155:5",
+        ChainFamily::EVM,
+        "https://goerli.etherscan.io",
+        "ETH",
+    ),
+    (
+        "sepolia",
+        "eip155:11155111",
+        ChainFamily::EVM,
+        "https://sepolia.etherscan.io",
+        "ETH",
+    ),
+    (
+        "bsc",
+        "eip155:56",
+        ChainFamily::EVM,
+        "https://bscscan.com",
+        "BNB",
+    ),
+    (
+        "polygon",
+        "eip155:137",
+        ChainFamily::EVM,
+        "https://polygonscan.com",
+        "MATIC",
+    ),
+    (
+        "mumbai",
+        "eip155:80001",
+        ChainFamily::EVM,
+        "https://mumbai.polygonscan.com",
+        "MATIC",
+    ),
+    // Truncated genesis hash of mainnet.
+    (
+        "solana",
+        "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+        ChainFamily::Solana,
+        "https://explorer.solana.com",
+        "SOL",
+    ),
+    (
+        "conflux",
+        "cfx:1029",
+        ChainFamily::Conflux,
+        "https://confluxscan.io",
+        "CFX",
+    ),
+    (
+        "conflux_espace",
+        "eip155:71",
+        ChainFamily::EVM,
+        "https://evm.confluxscan.io",
+        "CFX",
+    ),
+    (
+        "ethereum_classic",
+        "eip155:61",
+        ChainFamily::EVM,
+        "https://blockscout.com/etc/mainnet",
+        "ETC",
+    ),
+    // zkSync Era
+    (
+        "zksync",
+        "eip155:324",
+        ChainFamily::ZKSync,
+        "https://explorer.zksync.io",
+        "ETH",
+    ),
+    (
+        "xdai",
+        "eip155:100",
+        ChainFamily::EVM,
+        "https://gnosisscan.io",
+        "xDAI",
+    ),
+    (
+        "gnosis",
+        "eip155:100",
+        ChainFamily::EVM,
+        "https://gnosisscan.io",
+        "xDAI",
+    ),
+    (
+        "arweave",
+        "arweave:mainnet",
+        ChainFamily::Arweave,
+        "https://viewblock.io/arweave",
+        "AR",
+    ),
+    (
+        "arbitrum",
+        "eip155:42161",
+        ChainFamily::EVM,
+        "https://arbiscan.io",
+        "ETH",
+    ),
+    (
+        "optimism",
+        "eip155:10",
+        ChainFamily::EVM,
+        "https://optimistic.etherscan.io",
+        "ETH",
+    ),
+    (
+        "crossbell",
+        "eip155:3737",
+        ChainFamily::EVM,
+        "https://scan.crossbell.io",
+        "CSB",
+    ),
+    (
+        "avalanche",
+        "eip155:43114",
+        ChainFamily::EVM,
+        "https://snowtrace.io",
+        "AVAX",
+    ),
+    (
+        "fantom",
+        "eip155:250",
+        ChainFamily::EVM,
+        "https://ftmscan.com",
+        "FTM",
+    ),
+    (
+        "celo",
+        "eip155:42220",
+        ChainFamily::EVM,
+        "https://celoscan.io",
+        "CELO",
+    ),
+];
+
+lazy_static! {
+    /// Built-in chains, overridden or extended by `[[chains]]` in config.
+    static ref CHAIN_REGISTRY: HashMap<String, ChainInfo> = chain_registry(&CHAINS);
+}
+
+/// Merges `configured` chains into built-in ones. Configured ones win on name conflicts.
+fn chain_registry(configured: &[ChainInfo]) -> HashMap<String, ChainInfo> {
+    let mut registry: HashMap<String, ChainInfo> = BUILTIN_CHAINS
+        .iter()
+        .map(|(name, chain_id, family, explorer_url, native_symbol)| {
+            let info = ChainInfo {
+                name: name.to_string(),
+                chain_id: chain_id.to_string(),
+                family: *family,
+                rpc_url: None,
+                explorer_url: Some(explorer_url.to_string()),
+                native_symbol: Some(native_symbol.to_string()),
+            };
+            (info.name.clone(), info)
+        })
+        .collect();
+    for info in configured {
+        registry.insert(info.name.clone(), info.clone());
+    }
+    registry
+}
+
+impl ChainInfo {
+    /// Finds a chain in the registry by its name.
+    /// Chains added in config without a `Chain` of their own can be found here.
+    pub fn by_name(name: &str) -> Option<&'static ChainInfo> {
+        CHAIN_REGISTRY.get(name)
+    }
+
+    /// Chain implementation / framework. `None` if the family is not supported.
+    pub fn chain_type(&self) -> Option<ChainType> {
+        match self.family {
+            ChainFamily::EVM => self
+                .chain_id
+                .strip_prefix("eip155:")
+                .and_then(|id| id.parse().ok())
+                .map(ChainType::EVM),
+            ChainFamily::Solana => Some(ChainType::Solana),
+            ChainFamily::ZKSync => Some(ChainType::ZKSync),
+            ChainFamily::Arweave => Some(ChainType::Arweave),
+            ChainFamily::Conflux => Some(ChainType::Conflux),
+            ChainFamily::Other => None,
+        }
     }
 }
 
 impl Chain {
+    /// Registry entry of this chain. `None` for `Chain::Unknown`.
+    pub fn info(&self) -> Option<&'static ChainInfo> {
+        if *self == Chain::Unknown {
+            return None;
+        }
+        ChainInfo::by_name(self.name())
+    }
+
     /// Returns chain implementation / framework.
-    pub fn chain_type(&self) -> ChainType {
-        use Chain::*;
+    pub fn chain_type(&self) -> Option<ChainType> {
+        self.info()?.chain_type()
+    }
 
-        match self {
-            Ethereum => ChainType::EVM(1),
-            Rinkeby => ChainType::EVM(4),
-            Ropsten => ChainType::EVM(3),
-            Kovan => ChainType::EVM(42),
-            Goerli => ChainType::EVM(5),
-            Sepolia => ChainType::EVM(11155111),
-            BNBSmartChain => ChainType::EVM(56),
-            Polygon => ChainType::EVM(137),
-            Mumbai => ChainType::EVM(80001),
-            Solana => ChainType::Solana,
-            Conflux => ChainType::Conflux,
-            ConfluxESpace => ChainType::EVM(71),
-            EthereumClassic => ChainType::EVM(61),
-            ZKSync => ChainType::ZKSync,
-            Xdai => ChainType::EVM(100),
-            Gnosis => ChainType::EVM(100),
-            Arweave => ChainType::Arweave,
-            Arbitrum => ChainType::EVM(42161),
-            Optimism => ChainType::EVM(10),
-            Crossbell => ChainType::EVM(3737),
-            Avalanche => ChainType::EVM(43114),
-            Fantom => ChainType::EVM(250),
-            Celo => ChainType::EVM(42220),
-            Unknown => todo!(),
-        }
+    /// Finds a chain by its EVM chain ID, chains added in config included.
+    pub fn from_evm_chain_id(id: u128) -> Option<Chain> {
+        Chain::all()
+            .into_iter()
+            // `Xdai` is the former name of `Gnosis`.
+            .filter(|chain| *chain != Chain::Xdai)
+            .find(|chain| matches!(chain.chain_type(), Some(ChainType::EVM(chain_id)) if chain_id == id))
     }
 }
 
@@ -277,6 +639,12 @@ pub enum ContractCategory {
     #[graphql(name = "ERC1155")]
     ERC1155,
 
+    /// Fungible tokens. Not an NFT, but held in amounts the same way.
+    #[strum(serialize = "ERC20", serialize = "ERC-20")]
+    #[serde(rename = "ERC20")]
+    #[graphql(name = "ERC20")]
+    ERC20,
+
     #[strum(serialize = "POAP")]
     #[serde(rename = "POAP")]
     #[graphql(name = "POAP")]
@@ -289,26 +657,50 @@ pub enum ContractCategory {
     Unknown,
 }
 
+/// ENS NameWrapper. Wrapped names (and their subnames) are held by it as ERC1155 tokens,
+/// instead of by the base registrar.
+pub const ENS_NAME_WRAPPER: &str = "0xD4416b13d2b3a9aBae7AcD5D6C2BbDBE25686401";
+
 impl ContractCategory {
     pub fn default_contract_address(&self) -> Option<String> {
         use ContractCategory::*;
         match self {
             // TODO: ENS has a complicated contract structure, which cannot determine the "main" contract easily.
             ENS => Some("0x57f1887a8BF19b14fC0dF6Fd9B2acc9Af147eA85".to_lowercase()),
+            // Same address on both Ethereum and Gnosis.
+            POAP => Some("0x22C1f6050E56d2876009903609a2cC3fEf83B415".to_lowercase()),
             _ => None,
         }
     }
 
+    /// All contracts known to hold tokens of this category, the default one first.
+    pub fn contract_addresses(&self) -> Vec<String> {
+        use ContractCategory::*;
+        match self {
+            ENS => vec![
+                self.default_contract_address().unwrap(),
+                ENS_NAME_WRAPPER.to_lowercase(),
+            ],
+            _ => self.default_contract_address().into_iter().collect(),
+        }
+    }
+
     pub fn default_chain(&self) -> Option<Chain> {
         use ContractCategory::*;
         match self {
             ENS => Some(Chain::Ethereum),
             ERC721 => Some(Chain::Ethereum),
             ERC1155 => Some(Chain::Ethereum),
+            ERC20 => Some(Chain::Ethereum),
             POAP => Some(Chain::Ethereum),
             _ => None,
         }
     }
+
+    /// If tokens of this category are fungible, i.e. have no token ID.
+    pub fn is_fungible(&self) -> bool {
+        *self == ContractCategory::ERC20
+    }
 }
 
 pub struct ContractLoadFn {
@@ -391,6 +783,19 @@ pub struct Contract {
     pub chain: Chain,
     /// Token symbol
     pub symbol: Option<String>,
+    /// Name of this contract (collection), i.e. `name()` of ERC721 / ERC20.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Total supply in decimal, i.e. `totalSupply()`. `None` if the contract doesn't tell.
+    #[serde(default)]
+    pub total_supply: Option<String>,
+    /// Address which deployed this contract, if known.
+    #[serde(default)]
+    pub deployer: Option<String>,
+    /// If this is a known contract, i.e. its address is built in or configured by operator,
+    /// rather than picked up from upstreams.
+    #[serde(default)]
+    pub verified: bool,
     /// When this data is fetched by RelationService.
     pub updated_at: NaiveDateTime,
 }
@@ -403,6 +808,10 @@ impl Default for Contract {
             address: Default::default(),
             chain: Default::default(),
             symbol: Default::default(),
+            name: None,
+            total_supply: None,
+            deployer: None,
+            verified: false,
             updated_at: naive_now(),
         }
     }
@@ -446,6 +855,10 @@ impl Vertex<ContractRecord> for Contract {
             Some(mut found) => {
                 found.updated_at = naive_now();
                 found.symbol = self.symbol.clone();
+                found.name = self.name.clone().or(found.name.clone());
+                found.total_supply = self.total_supply.clone().or(found.total_supply.clone());
+                found.deployer = self.deployer.clone().or(found.deployer.clone());
+                found.verified = self.verified || found.verified;
                 found.save(db).await?;
                 Ok(found)
             }
@@ -507,11 +920,74 @@ impl From<DatabaseRecord<Contract>> for ContractRecord {
     }
 }
 
+/// An `Identity` currently holding tokens of a `Contract`.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ContractHolder {
+    pub identity: IdentityRecord,
+    /// How many tokens (i.e. open `Hold`s) it holds.
+    pub count: u64,
+}
+
+/// A page of holders of a `Contract`.
+#[derive(Clone, Deserialize, Serialize, Default, Debug)]
+pub struct ContractHolders {
+    /// How many distinct holders in total.
+    pub total: u64,
+    pub holders: Vec<ContractHolder>,
+}
+
+impl ContractRecord {
+    /// Distinct `Identity`s currently holding tokens of this contract, in the order of their IDs,
+    /// so that paging goes along the `HoldsContractHolder` index rather than sorting every holder.
+    /// Skips the first `offset` holders and returns at most `limit` of the rest.
+    pub async fn find_holders(
+        &self,
+        pool: &ConnectionPool,
+        offset: u64,
+        limit: u64,
+    ) -> Result<ContractHolders, Error> {
+        let conn = pool
+            .get()
+            .await
+            .map_err(|err| Error::PoolError(err.to_string()))?;
+        let db = conn.database();
+
+        let aql = r###"
+        RETURN {
+            "total": COUNT(
+                FOR edge IN @@holds
+                    FILTER edge._to == @contract AND edge.ended_at == null
+                    COLLECT holder = edge._from
+                    RETURN 1
+            ),
+            "holders": (
+                FOR edge IN @@holds
+                    FILTER edge._to == @contract AND edge.ended_at == null
+                    COLLECT holder = edge._from WITH COUNT INTO count
+                    SORT holder
+                    LIMIT @offset, @limit
+                    RETURN {"identity": DOCUMENT(holder), "count": count}
+            )
+        }"###;
+        let aql = AqlQuery::new(aql)
+            .bind_var("contract", self.id().as_str())
+            .bind_var("@holds", Hold::COLLECTION_NAME)
+            .bind_var("offset", offset)
+            .bind_var("limit", limit)
+            .batch_size(1)
+            .count(false);
+
+        let result: Vec<ContractHolders> = db.aql_query(aql).await?;
+        Ok(result.into_iter().next().unwrap_or_default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::graph::arangopool::new_connection_pool;
-    use crate::graph::new_db_connection;
+    use crate::graph::vertex::Identity;
+    use crate::graph::{new_db_connection, Edge};
     use fake::{Dummy, Fake, Faker};
 
     impl Contract {
@@ -533,6 +1009,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chain_registry() {
+        let configured = vec![
+            ChainInfo {
+                name: "ethereum".into(),
+                chain_id: "eip155:1".into(),
+                family: ChainFamily::EVM,
+                rpc_url: Some("https://eth.llamarpc.com".into()),
+                explorer_url: None,
+                native_symbol: Some("ETH".into()),
+            },
+            ChainInfo {
+                name: "base".into(),
+                chain_id: "eip155:8453".into(),
+                family: ChainFamily::EVM,
+                rpc_url: None,
+                explorer_url: Some("https://basescan.org".into()),
+                native_symbol: Some("ETH".into()),
+            },
+        ];
+        let registry = chain_registry(&configured);
+        assert_eq!(registry["ethereum"], configured[0]);
+        assert!(matches!(
+            registry["base"].chain_type(),
+            Some(ChainType::EVM(8453))
+        ));
+        assert_eq!(registry["bsc"].chain_id, "eip155:56");
+        assert_eq!(registry.len(), BUILTIN_CHAINS.len() + 1);
+
+        // Every `Chain` but `Unknown` is built in.
+        assert!(Chain::builtin()
+            .filter(|chain| *chain != Chain::Unknown)
+            .all(|chain| chain_registry(&[]).contains_key(chain.name())));
+        assert_eq!(Chain::from_evm_chain_id(100), Some(Chain::Gnosis));
+        assert!(Chain::Unknown.chain_type().is_none());
+    }
+
+    #[test]
+    fn test_chain_names() {
+        assert_eq!(Chain::from_str("bsc").unwrap(), Chain::BNBSmartChain);
+        assert_eq!(
+            Chain::from_str("binance_smart_chain").unwrap(),
+            Chain::BNBSmartChain
+        );
+        assert_eq!(Chain::BNBSmartChain.to_string(), "bsc");
+        assert!(Chain::from_str("not_a_chain").is_err());
+
+        // Chains only in config are saved by their names. Names not in the registry are rejected.
+        assert_eq!(
+            serde_json::to_string(&Chain::Custom("base")).unwrap(),
+            "\"base\""
+        );
+        assert!(serde_json::from_str::<Chain>("\"not_a_chain\"").is_err());
+        let ethereum: Chain = serde_json::from_str("\"ethereum\"").unwrap();
+        assert_eq!(ethereum, Chain::Ethereum);
+
+        for chain in Chain::builtin() {
+            assert_eq!(Chain::from(BuiltinChain::from(chain)), chain);
+        }
+        assert_eq!(
+            BuiltinChain::from(Chain::Custom("base")),
+            BuiltinChain::Unknown
+        );
+    }
+
     #[tokio::test]
     async fn test_creation() -> Result<(), Error> {
         let db = new_db_connection().await?;
@@ -553,6 +1094,68 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_create_or_update_metadata() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let contract = Contract {
+            name: Some("Test Collection".into()),
+            total_supply: Some("10000".into()),
+            verified: true,
+            ..Faker.fake()
+        };
+        let created = contract.create_or_update(&db).await?;
+
+        // Missing info doesn't erase the known one.
+        let updated = Contract {
+            name: None,
+            total_supply: Some("10001".into()),
+            verified: false,
+            ..contract
+        }
+        .create_or_update(&db)
+        .await?;
+        assert_eq!(updated.key(), created.key());
+        assert_eq!(updated.name, Some("Test Collection".into()));
+        assert_eq!(updated.total_supply, Some("10001".into()));
+        assert!(updated.verified);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_holders() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let pool = new_connection_pool().await?;
+        let contract = Contract::create_dummy(&db).await?;
+        let alice = Identity::create_dummy(&db).await?;
+        let bob = Identity::create_dummy(&db).await?;
+        for (holder, id) in [(&alice, "1"), (&alice, "2"), (&bob, "3")] {
+            let hold: Hold = Hold {
+                id: id.into(),
+                ..Faker.fake()
+            };
+            hold.connect(&db, holder, &contract).await?;
+        }
+
+        let first = contract.find_holders(&pool, 0, 1).await?;
+        assert_eq!(first.total, 2);
+        assert_eq!(first.holders.len(), 1);
+        let second = contract.find_holders(&pool, 1, 1).await?;
+        assert_eq!(second.holders.len(), 1);
+        assert!(contract.find_holders(&pool, 2, 1).await?.holders.is_empty());
+
+        // In the order of holder IDs.
+        let (expected_first, expected_second) = if alice.id() < bob.id() {
+            ((&alice, 2), (&bob, 1))
+        } else {
+            ((&bob, 1), (&alice, 2))
+        };
+        assert_eq!(first.holders[0].identity.key(), expected_first.0.key());
+        assert_eq!(first.holders[0].count, expected_first.1);
+        assert_eq!(second.holders[0].identity.key(), expected_second.0.key());
+        assert_eq!(second.holders[0].count, expected_second.1);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_get_contracts_hashmap() -> Result<(), Error> {
         let pool = new_connection_pool().await?;
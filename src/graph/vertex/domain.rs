@@ -0,0 +1,614 @@
+use crate::{
+    error::Error,
+    graph::{
+        edge::{
+            resolve::{DomainNameSystem, ResolveEdge},
+            Own, Resolve, ResolvesTo, ReverseOf, Subdomain,
+        },
+        vertex::{Identity, IdentityRecord, Vertex},
+        ConnectionPool,
+    },
+    upstream::{DataFetcher, DataSource},
+    util::naive_now,
+};
+use aragog::{
+    query::{Comparison, Filter},
+    AqlQuery, DatabaseAccess, DatabaseConnection, DatabaseRecord, Record,
+};
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDateTime};
+use dataloader::BatchFn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+use uuid::Uuid;
+
+/// A name in a `DomainNameSystem`, e.g. `vitalik.eth`, `abc.bit`.
+/// Connected with `Identity`s by `Own` (owner => domain), `ResolvesTo` (domain => address)
+/// and `ReverseOf` (domain => address whose reverse record is this domain) edges.
+/// Parent domains are connected to their subdomains by `Subdomain` edges.
+#[derive(Clone, Serialize, Deserialize, Record, Debug)]
+#[collection_name = "Domains"]
+pub struct Domain {
+    /// UUID of this record.
+    pub uuid: Uuid,
+    /// Domain Name system
+    pub system: DomainNameSystem,
+    /// Full name of the domain (e.g., `vitalik.eth`)
+    pub name: String,
+    /// Top-level domain (e.g., `eth`)
+    pub tld: String,
+    /// When this domain expires. `None` if it never expires, or upstream doesn't tell.
+    pub expired_at: Option<NaiveDateTime>,
+    /// Token ID of this domain as an NFT (if any).
+    pub token_id: Option<String>,
+    /// Fuses burned by ENS NameWrapper. `None` if this domain is not wrapped.
+    #[serde(default)]
+    pub fuses: Option<u32>,
+    /// When this data is fetched by RelationService.
+    pub updated_at: NaiveDateTime,
+}
+
+/// NameWrapper fuse: the owner of the parent domain can no longer replace or delete this subname.
+/// See also: https://docs.ens.domains/wrapper/fuses
+pub const PARENT_CANNOT_CONTROL: u32 = 1 << 16;
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            system: Default::default(),
+            name: Default::default(),
+            tld: Default::default(),
+            expired_at: None,
+            token_id: None,
+            fuses: None,
+            updated_at: naive_now(),
+        }
+    }
+}
+
+/// A `Domain` with all `Identity`s connected to it.
+#[derive(Clone, Deserialize, Debug)]
+struct DomainRelations {
+    domain: DomainRecord,
+    owner: Option<IdentityRecord>,
+    owned: Option<Own>,
+    resolved: Option<IdentityRecord>,
+    resolves_to: Option<ResolvesTo>,
+    reverse_of: Vec<IdentityRecord>,
+    parent: Option<DomainRecord>,
+    #[serde(default)]
+    subdomains: Vec<DomainRecord>,
+}
+
+impl Domain {
+    pub fn new(system: DomainNameSystem, name: &str) -> Self {
+        let name = name.to_lowercase();
+        Self {
+            system,
+            tld: name.rsplit('.').next().unwrap_or_default().to_string(),
+            name,
+            ..Default::default()
+        }
+    }
+
+    /// Name of the parent domain (e.g., `vitalik.eth` of `pay.vitalik.eth`).
+    /// `None` if this domain is registered right under its TLD.
+    pub fn parent_name(&self) -> Option<String> {
+        let (_, parent) = self.name.split_once('.')?;
+        if parent.contains('.') {
+            Some(parent.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// If this domain is wrapped by ENS NameWrapper.
+    pub fn is_wrapped(&self) -> bool {
+        self.fuses.is_some()
+    }
+
+    /// If the owner of the parent domain can still replace or delete this subdomain,
+    /// i.e. it is not emancipated by burning `PARENT_CANNOT_CONTROL`.
+    pub fn parent_can_control(&self) -> bool {
+        self.parent_name().is_some()
+            && self
+                .fuses
+                .map_or(true, |fuses| fuses & PARENT_CANNOT_CONTROL == 0)
+    }
+
+    /// If this domain is expired. Note that it may still be renewed by its owner
+    /// until `grace_period_ended_at`.
+    pub fn is_expired(&self) -> bool {
+        self.expired_at
+            .map_or(false, |expired_at| expired_at.lt(&naive_now()))
+    }
+
+    /// Until when the owner can still renew this expired domain.
+    pub fn grace_period_ended_at(&self) -> Option<NaiveDateTime> {
+        self.expired_at
+            .map(|expired_at| expired_at + self.system.grace_period())
+    }
+
+    pub async fn find_by_system_name(
+        db: &DatabaseConnection,
+        system: &DomainNameSystem,
+        name: &str,
+    ) -> Result<Option<DomainRecord>, Error> {
+        let query = Self::query().filter(
+            Filter::new(Comparison::field("system").equals_str(system))
+                .and(Comparison::field("name").equals_str(name)),
+        );
+        let result = Self::get(&query, db).await?;
+        if result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.first().unwrap().to_owned().into()))
+        }
+    }
+
+    /// Finds a domain with its owner, resolved address, reverse record holders,
+    /// parent domain and subdomains, in the same shape for every `DomainNameSystem`.
+    pub async fn find_resolve_edge(
+        pool: &ConnectionPool,
+        system: &DomainNameSystem,
+        name: &str,
+    ) -> Result<Option<ResolveEdge>, Error> {
+        let conn = pool
+            .get()
+            .await
+            .map_err(|err| Error::PoolError(err.to_string()))?;
+        let db = conn.database();
+
+        let aql_str = r###"
+        WITH @@identities, @@domains
+        FOR d IN @@domains
+            FILTER d.system == @system AND d.name == @name
+            LIMIT 1
+            LET owned = FIRST(FOR v, e IN 1..1 INBOUND d @@owns SORT e.updated_at DESC RETURN {"vertex": v, "edge": e})
+            LET resolved = FIRST(FOR v, e IN 1..1 OUTBOUND d @@resolves_to SORT e.updated_at DESC RETURN {"vertex": v, "edge": e})
+            LET reverse_of = (FOR v IN 1..1 OUTBOUND d @@reverse_of RETURN DISTINCT v)
+            LET parent = FIRST(FOR v IN 1..1 INBOUND d @@subdomains RETURN v)
+            LET subdomains = (FOR v IN 1..1 OUTBOUND d @@subdomains SORT v.name RETURN v)
+            RETURN {
+                "domain": d,
+                "owner": owned.vertex,
+                "owned": owned.edge,
+                "resolved": resolved.vertex,
+                "resolves_to": resolved.edge,
+                "reverse_of": reverse_of,
+                "parent": parent,
+                "subdomains": subdomains
+            }"###;
+        let aql = AqlQuery::new(aql_str)
+            .bind_var("@identities", Identity::COLLECTION_NAME)
+            .bind_var("@domains", Domain::COLLECTION_NAME)
+            .bind_var("@owns", Own::COLLECTION_NAME)
+            .bind_var("@resolves_to", ResolvesTo::COLLECTION_NAME)
+            .bind_var("@reverse_of", ReverseOf::COLLECTION_NAME)
+            .bind_var("@subdomains", Subdomain::COLLECTION_NAME)
+            .bind_var("system", system.to_string())
+            .bind_var("name", name.to_lowercase())
+            .batch_size(1)
+            .count(false);
+
+        let result: Vec<DomainRelations> = db.aql_query(aql).await?;
+        let found = match result.into_iter().next() {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        // Where the domain info comes from: resolution first, then ownership.
+        let (source, fetcher) = match (&found.resolves_to, &found.owned) {
+            (Some(edge), _) => (edge.source, edge.fetcher),
+            (None, Some(edge)) => (edge.source, edge.fetcher),
+            (None, None) => (DataSource::default(), DataFetcher::default()),
+        };
+        let mut resolve_edge = ResolveEdge::from(Resolve {
+            uuid: found.domain.uuid,
+            source,
+            system: found.domain.system,
+            name: found.domain.name.clone(),
+            fetcher,
+            updated_at: found.domain.updated_at,
+        });
+        resolve_edge.owner = found.owner;
+        resolve_edge.resolved = found.resolved;
+        resolve_edge.reverse_of = found.reverse_of;
+        resolve_edge.parent = found.parent;
+        resolve_edge.subdomains = found.subdomains;
+        resolve_edge.domain = Some(found.domain);
+        Ok(Some(resolve_edge))
+    }
+}
+
+#[async_trait]
+impl Vertex<DomainRecord> for Domain {
+    fn uuid(&self) -> Option<Uuid> {
+        Some(self.uuid)
+    }
+
+    /// Create or update a domain by (system, name).
+    async fn create_or_update(&self, db: &DatabaseConnection) -> Result<DomainRecord, Error> {
+        let found = Self::find_by_system_name(db, &self.system, &self.name).await?;
+        match found {
+            None => {
+                let mut to_be_created = self.clone();
+                to_be_created.updated_at = naive_now();
+                let created = DatabaseRecord::create(to_be_created, db).await?;
+                Ok(created.into())
+            }
+            Some(mut found) => {
+                found.expired_at = self.expired_at.or(found.expired_at);
+                found.token_id = self.token_id.clone().or(found.token_id.clone());
+                found.fuses = self.fuses.or(found.fuses);
+                found.updated_at = naive_now();
+                found.save(db).await?;
+                Ok(found)
+            }
+        }
+    }
+
+    async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: Uuid,
+    ) -> Result<Option<DomainRecord>, Error> {
+        let query = Domain::query().filter(Comparison::field("uuid").equals_str(uuid).into());
+        let query_result = Domain::get(&query, db).await?;
+        if query_result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(query_result.first().unwrap().to_owned().into()))
+        }
+    }
+
+    /// Outdated in 1 day
+    fn is_outdated(&self) -> bool {
+        let outdated_in = Duration::days(1);
+        self.updated_at
+            .checked_add_signed(outdated_in)
+            .unwrap()
+            .lt(&naive_now())
+    }
+}
+
+/// Primary name (reverse record) of a wallet in a `DomainNameSystem`,
+/// which also resolves back to the wallet.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PrimaryName {
+    pub system: DomainNameSystem,
+    pub name: String,
+}
+
+impl PrimaryName {
+    /// Picks the one of the most preferred system in `precedence`.
+    /// Systems not in `precedence` are never picked.
+    pub fn pick<'a>(
+        names: &'a [PrimaryName],
+        precedence: &[DomainNameSystem],
+    ) -> Option<&'a PrimaryName> {
+        precedence
+            .iter()
+            .find_map(|system| names.iter().find(|name| name.system == *system))
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+struct IdentityPrimaryNames {
+    id: String,
+    names: Vec<PrimaryName>,
+}
+
+pub struct PrimaryNameLoadFn {
+    pub pool: ConnectionPool,
+}
+
+#[async_trait]
+impl BatchFn<String, Vec<PrimaryName>> for PrimaryNameLoadFn {
+    async fn load(&mut self, ids: &[String]) -> HashMap<String, Vec<PrimaryName>> {
+        debug!("Loading primary names for: {:?}", ids);
+        match get_primary_names(&self.pool, ids.to_vec()).await {
+            Ok(names) => names,
+            Err(_) => ids.iter().map(|k| (k.to_owned(), vec![])).collect(),
+        }
+    }
+}
+
+/// Primary names of each `Identity` (by `_id`): domains which are reverse records of it
+/// and resolve to it at the same time, expired ones excluded.
+async fn get_primary_names(
+    pool: &ConnectionPool,
+    ids: Vec<String>,
+) -> Result<HashMap<String, Vec<PrimaryName>>, Error> {
+    let conn = pool
+        .get()
+        .await
+        .map_err(|err| Error::PoolError(err.to_string()))?;
+    let db = conn.database();
+
+    let aql_str = r###"
+    WITH @@identities, @@domains
+    FOR id IN @ids
+        LET names = (
+            FOR d, r IN 1..1 INBOUND id @@reverse_of
+                FILTER d.expired_at == null OR d.expired_at > @now
+                LET resolved = FIRST(FOR v IN 1..1 OUTBOUND d @@resolves_to RETURN v._id)
+                FILTER resolved == id
+                SORT r.updated_at DESC
+                RETURN {"system": d.system, "name": d.name}
+        )
+        RETURN {"id": id, "names": names}"###;
+    let aql = AqlQuery::new(aql_str)
+        .bind_var("@identities", Identity::COLLECTION_NAME)
+        .bind_var("@domains", Domain::COLLECTION_NAME)
+        .bind_var("@reverse_of", ReverseOf::COLLECTION_NAME)
+        .bind_var("@resolves_to", ResolvesTo::COLLECTION_NAME)
+        .bind_var("ids", ids.clone())
+        .bind_var("now", naive_now().format("%Y-%m-%dT%H:%M:%S").to_string())
+        .batch_size(1)
+        .count(false);
+
+    let result: Vec<IdentityPrimaryNames> = db.aql_query(aql).await?;
+    let mut names: HashMap<String, Vec<PrimaryName>> =
+        result.into_iter().map(|r| (r.id, r.names)).collect();
+    for id in ids.into_iter() {
+        names.entry(id).or_default();
+    }
+    Ok(names)
+}
+
+#[derive(Clone, Deserialize, Serialize, Default, Debug)]
+pub struct DomainRecord(pub DatabaseRecord<Domain>);
+
+impl std::ops::Deref for DomainRecord {
+    type Target = DatabaseRecord<Domain>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for DomainRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<DatabaseRecord<Domain>> for DomainRecord {
+    fn from(record: DatabaseRecord<Domain>) -> Self {
+        Self(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graph::{
+            arangopool::new_connection_pool, create_domain_resolve_record,
+            create_domain_subdomain_record, create_identity_to_identity_hold_record, edge::Hold,
+            new_db_connection,
+        },
+        upstream::Platform,
+    };
+    use fake::{Fake, Faker};
+
+    #[test]
+    fn test_new() {
+        let domain = Domain::new(DomainNameSystem::ENS, "Pay.Vitalik.eth");
+        assert_eq!(domain.name, "pay.vitalik.eth");
+        assert_eq!(domain.tld, "eth");
+    }
+
+    #[test]
+    fn test_parent_name() {
+        let domain = Domain::new(DomainNameSystem::ENS, "pay.vitalik.eth");
+        assert_eq!(domain.parent_name(), Some("vitalik.eth".into()));
+        let domain = Domain::new(DomainNameSystem::ENS, "vitalik.eth");
+        assert_eq!(domain.parent_name(), None);
+    }
+
+    #[test]
+    fn test_parent_can_control() {
+        let mut domain = Domain::new(DomainNameSystem::ENS, "pay.vitalik.eth");
+        assert!(domain.parent_can_control());
+        domain.fuses = Some(0);
+        assert!(domain.is_wrapped());
+        assert!(domain.parent_can_control());
+        // Emancipated
+        domain.fuses = Some(PARENT_CANNOT_CONTROL | 1);
+        assert!(!domain.parent_can_control());
+        assert!(!Domain::new(DomainNameSystem::ENS, "vitalik.eth").parent_can_control());
+    }
+
+    #[test]
+    fn test_pick_primary_name() {
+        let names = vec![
+            PrimaryName {
+                system: DomainNameSystem::DotBit,
+                name: "abc.bit".into(),
+            },
+            PrimaryName {
+                system: DomainNameSystem::ENS,
+                name: "abc.eth".into(),
+            },
+        ];
+        let picked = PrimaryName::pick(&names, &[DomainNameSystem::ENS, DomainNameSystem::DotBit]);
+        assert_eq!(picked.unwrap().name, "abc.eth");
+        let picked = PrimaryName::pick(&names, &[DomainNameSystem::Lens, DomainNameSystem::DotBit]);
+        assert_eq!(picked.unwrap().name, "abc.bit");
+        assert_eq!(PrimaryName::pick(&names, &[DomainNameSystem::SNS]), None);
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let mut domain = Domain::new(DomainNameSystem::ENS, "vitalik.eth");
+        assert!(!domain.is_expired());
+        assert_eq!(domain.grace_period_ended_at(), None);
+
+        let expired_at = naive_now() - Duration::days(1);
+        domain.expired_at = Some(expired_at);
+        assert!(domain.is_expired());
+        assert_eq!(
+            domain.grace_period_ended_at(),
+            Some(expired_at + Duration::days(90))
+        );
+        domain.system = DomainNameSystem::SNS;
+        assert_eq!(domain.grace_period_ended_at(), Some(expired_at));
+    }
+
+    #[tokio::test]
+    async fn test_create_or_update() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let name = format!("{}.bit", Uuid::new_v4().simple());
+        let mut domain = Domain::new(DomainNameSystem::DotBit, &name);
+        domain.token_id = Some("1".into());
+        let created = domain.create_or_update(&db).await?;
+
+        // Missing info doesn't erase the known one.
+        let updated = Domain::new(DomainNameSystem::DotBit, &name)
+            .create_or_update(&db)
+            .await?;
+        assert_eq!(created.key(), updated.key());
+        assert_eq!(updated.token_id, Some("1".into()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_resolve_edge() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let pool = new_connection_pool().await?;
+        let name = format!("{}.bit", Uuid::new_v4().simple());
+        let mut wallet: Identity = Faker.fake();
+        wallet.platform = Platform::Ethereum;
+        let mut domain_identity: Identity = Faker.fake();
+        domain_identity.platform = Platform::Dotbit;
+        domain_identity.identity = name.clone();
+
+        let hold = Hold {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Dotbit,
+            transaction: None,
+            id: name.clone(),
+            created_at: None,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            event_id: None,
+            event_name: None,
+            ended_at: None,
+            expired_at: None,
+            amount: None,
+            decimals: None,
+        };
+        create_identity_to_identity_hold_record(&db, &wallet, &domain_identity, &hold).await?;
+        let resolve = Resolve {
+            source: DataSource::Dotbit,
+            system: DomainNameSystem::DotBit,
+            name: name.clone(),
+            ..Default::default()
+        };
+        // Forward resolution, then reverse record.
+        create_domain_resolve_record(&db, &domain_identity, &wallet, &resolve).await?;
+        create_domain_resolve_record(&db, &wallet, &domain_identity, &resolve).await?;
+
+        let found = Domain::find_resolve_edge(&pool, &DomainNameSystem::DotBit, &name)
+            .await?
+            .expect("Domain should be found");
+        assert_eq!(found.name, name);
+        assert_eq!(found.source, DataSource::Dotbit);
+        assert_eq!(found.owner.unwrap().identity, wallet.identity);
+        assert_eq!(found.resolved.unwrap().identity, wallet.identity);
+        assert_eq!(found.reverse_of.len(), 1);
+        assert_eq!(found.domain.unwrap().tld, "bit");
+
+        assert!(
+            Domain::find_resolve_edge(&pool, &DomainNameSystem::ENS, &name)
+                .await?
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_resolve_edge_with_subdomains() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let pool = new_connection_pool().await?;
+        let parent_name = format!("{}.eth", Uuid::new_v4().simple());
+        let parent = Domain::new(DomainNameSystem::ENS, &parent_name);
+        let sub = Domain::new(DomainNameSystem::ENS, &format!("pay.{}", parent_name));
+        let subdomain = Subdomain {
+            system: DomainNameSystem::ENS,
+            name: sub.name.clone(),
+            ..Default::default()
+        };
+        create_domain_subdomain_record(&db, &parent, &sub, &subdomain).await?;
+
+        let found = Domain::find_resolve_edge(&pool, &DomainNameSystem::ENS, &parent.name)
+            .await?
+            .expect("Domain should be found");
+        assert!(found.parent.is_none());
+        assert_eq!(found.subdomains.len(), 1);
+        assert_eq!(found.subdomains[0].name, sub.name);
+
+        let found = Domain::find_resolve_edge(&pool, &DomainNameSystem::ENS, &sub.name)
+            .await?
+            .expect("Subdomain should be found");
+        assert_eq!(found.parent.unwrap().name, parent.name);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_primary_names() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let pool = new_connection_pool().await?;
+        let name = format!("{}.bit", Uuid::new_v4().simple());
+        let mut wallet: Identity = Faker.fake();
+        wallet.platform = Platform::Ethereum;
+        let mut other: Identity = Faker.fake();
+        other.platform = Platform::Ethereum;
+        let mut domain_identity: Identity = Faker.fake();
+        domain_identity.platform = Platform::Dotbit;
+        domain_identity.identity = name.clone();
+        let resolve = Resolve {
+            source: DataSource::Dotbit,
+            system: DomainNameSystem::DotBit,
+            name: name.clone(),
+            ..Default::default()
+        };
+        // Both set it as reverse record, but it resolves to `wallet` only.
+        create_domain_resolve_record(&db, &domain_identity, &wallet, &resolve).await?;
+        create_domain_resolve_record(&db, &wallet, &domain_identity, &resolve).await?;
+        create_domain_resolve_record(&db, &other, &domain_identity, &resolve).await?;
+
+        let wallet_id =
+            Identity::find_by_platform_identity(&db, &wallet.platform, &wallet.identity)
+                .await?
+                .unwrap()
+                .id()
+                .clone();
+        let other_id = Identity::find_by_platform_identity(&db, &other.platform, &other.identity)
+            .await?
+            .unwrap()
+            .id()
+            .clone();
+        let names = get_primary_names(&pool, vec![wallet_id.clone(), other_id.clone()]).await?;
+        assert_eq!(
+            names[&wallet_id],
+            vec![PrimaryName {
+                system: DomainNameSystem::DotBit,
+                name
+            }]
+        );
+        assert!(names[&other_id].is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_by_ens_name() -> Result<(), Error> {
+        let pool = new_connection_pool().await?;
+        let name = "zzfzz.eth";
+        let a = Domain::find_resolve_edge(&pool, &DomainNameSystem::ENS, name).await?;
+        print!("result: {:?}", a);
+        Ok(())
+    }
+}
@@ -2,12 +2,12 @@ use crate::{
     error::Error,
     graph::ConnectionPool,
     graph::{
-        edge::{Hold, HoldRecord, IdentityFromToRecord, Proof, ProofRecord},
-        vertex::contract::ContractCategory,
+        edge::{Hold, HoldRecord, IdentityFromToRecord, Proof, ProofRecord, Subdomain},
+        vertex::contract::{Chain, ContractCategory},
         vertex::vec_string_to_vec_datasource,
         vertex::Vertex,
     },
-    upstream::{DataSource, Platform},
+    upstream::{AccountId, DataSource, Platform},
     util::naive_now,
 };
 use aragog::{
@@ -57,6 +57,14 @@ pub struct Identity {
     pub added_at: NaiveDateTime,
     /// When it is updated (re-fetched) by us RelationService. Managed by us.
     pub updated_at: NaiveDateTime,
+    /// Chains this wallet is seen on by upstreams, e.g. `polygon` for a Lens profile owner.
+    /// Empty for non-wallet identities, or if no upstream tells.
+    #[serde(default)]
+    pub chains: Vec<Chain>,
+    /// `true` if this wallet is a contract (e.g. a Safe multisig) on any of the checked chains.
+    /// `None` if not checked yet.
+    #[serde(default)]
+    pub is_contract: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -77,6 +85,8 @@ impl Default for Identity {
             created_at: None,
             added_at: naive_now(),
             updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
         }
     }
 }
@@ -111,6 +121,20 @@ impl Identity {
         }
     }
 
+    /// CAIP-10 account IDs of this wallet, one per chain it is seen on
+    /// (mainnet of its platform if no chain is known). Empty for non-wallet identities.
+    pub fn account_ids(&self) -> Vec<AccountId> {
+        if self.chains.is_empty() {
+            return AccountId::new(&self.platform, None, &self.identity)
+                .into_iter()
+                .collect();
+        }
+        self.chains
+            .iter()
+            .filter_map(|chain| AccountId::new(&self.platform, Some(chain), &self.identity))
+            .collect()
+    }
+
     pub async fn find_by_platforms_identity(
         pool: &ConnectionPool,
         platforms: &Vec<Platform>,
@@ -223,6 +247,12 @@ impl Vertex<IdentityRecord> for Identity {
                 found.profile_url = self.profile_url.clone();
                 found.avatar_url = self.avatar_url.clone();
                 found.created_at = self.created_at.or(found.created_at);
+                for chain in self.chains.iter() {
+                    if !found.chains.contains(chain) {
+                        found.chains.push(*chain);
+                    }
+                }
+                found.is_contract = self.is_contract.or(found.is_contract);
                 found.updated_at = naive_now();
 
                 found.save(db).await?;
@@ -470,6 +500,7 @@ impl IdentityRecord {
                 IN 1..@depth ANY d Proofs, Holds
                 PRUNE IS_SAME_COLLECTION('Contracts' , vertex)
                 FILTER NOT CONTAINS(path.edges[*]._to, "Contracts")
+                FILTER path.edges[*].deleted_at ALL == null
                 RETURN path
         "###;
         let aql = AqlQuery::new(aql_str)
@@ -523,9 +554,11 @@ impl IdentityRecord {
     }
 
     // Return domain name owned by wallet address.
+    // Expired domains are skipped unless `include_expired`.
     pub async fn domain_owned_by(
         &self,
         pool: &ConnectionPool,
+        include_expired: bool,
     ) -> Result<Option<IdentityRecord>, Error> {
         // let db = pool.db().await?;
         let conn = pool
@@ -541,6 +574,7 @@ impl IdentityRecord {
             FOR vertex, edge, path
                 IN 1..1 ANY d @@holds
                 FILTER path.edges[*].source ALL == @platform
+                FILTER @include_expired OR edge.expired_at == null OR edge.expired_at > @now
                 RETURN DISTINCT vertex";
 
         let aql = AqlQuery::new(aql_str)
@@ -548,6 +582,8 @@ impl IdentityRecord {
             .bind_var("@holds", Hold::COLLECTION_NAME)
             .bind_var("id", self.id().as_str())
             .bind_var("platform", self.platform.to_string())
+            .bind_var("include_expired", include_expired)
+            .bind_var("now", naive_now().format("%Y-%m-%dT%H:%M:%S").to_string())
             .batch_size(1)
             .count(false);
 
@@ -560,6 +596,57 @@ impl IdentityRecord {
         }
     }
 
+    /// Returns sub-domains of this domain identity (e.g. `sub.abc.bit` of `abc.bit`).
+    pub async fn find_subdomains(
+        &self,
+        pool: &ConnectionPool,
+    ) -> Result<Vec<IdentityRecord>, Error> {
+        self.subdomain_neighbors(pool, "OUTBOUND").await
+    }
+
+    /// Returns parent domain of this sub-domain identity, if any.
+    pub async fn find_parent_domain(
+        &self,
+        pool: &ConnectionPool,
+    ) -> Result<Option<IdentityRecord>, Error> {
+        Ok(self
+            .subdomain_neighbors(pool, "INBOUND")
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn subdomain_neighbors(
+        &self,
+        pool: &ConnectionPool,
+        direction: &str,
+    ) -> Result<Vec<IdentityRecord>, Error> {
+        let conn = pool
+            .get()
+            .await
+            .map_err(|err| Error::PoolError(err.to_string()))?;
+        let db = conn.database();
+
+        // Direction cannot be a bind parameter in AQL.
+        let aql_str = format!(
+            r"
+        WITH @@identities
+        FOR vertex IN 1..1 {} @id @@subdomains
+            SORT vertex.identity
+            RETURN DISTINCT vertex",
+            direction
+        );
+        let aql = AqlQuery::new(aql_str.as_str())
+            .bind_var("@identities", Identity::COLLECTION_NAME)
+            .bind_var("@subdomains", Subdomain::COLLECTION_NAME)
+            .bind_var("id", self.id().as_str())
+            .batch_size(1)
+            .count(false);
+
+        let result: Vec<IdentityRecord> = db.aql_query(aql).await?;
+        Ok(result)
+    }
+
     // Return all neighbors of this identity with path<ProofRecord>
     #[tracing::instrument(skip(self, pool), level = "trace")]
     pub async fn neighbors_with_traversal(
@@ -582,6 +669,7 @@ impl IdentityRecord {
                 IN 1..@depth ANY d Proofs, Holds
                 PRUNE IS_SAME_COLLECTION('Contracts' , vertex)
                 FILTER NOT CONTAINS(path.edges[*]._to, "Contracts")
+                FILTER path.edges[*].deleted_at ALL == null
                 RETURN DISTINCT edge
         "###;
         let aql = AqlQuery::new(aql_str)
@@ -603,11 +691,15 @@ impl IdentityRecord {
         Ok(paths)
     }
 
-    /// Returns all Contracts owned by this identity. Empty list if `self.platform != Ethereum`.
+    /// Returns all Contracts currently owned by this identity. Empty list if `self.platform != Ethereum`.
+    /// Holds which are already ended (transferred out / burned) are excluded.
+    /// Fungible tokens are excluded unless asked in `category`.
+    /// If `min_balance` is provided, holds with a lower `Hold::balance()` are excluded.
     pub async fn nfts(
         &self,
         pool: &ConnectionPool,
         category: Option<Vec<ContractCategory>>,
+        min_balance: Option<f64>,
     ) -> Result<Vec<HoldRecord>, Error> {
         if self.0.record.platform != Platform::Ethereum {
             return Ok(vec![]);
@@ -618,16 +710,18 @@ impl IdentityRecord {
         if category.is_none() || category.as_ref().unwrap().len() == 0 {
             aql_str = r"WITH @@edge_collection_name
                 FOR d in @@edge_collection_name
-                FILTER d._from == @id
+                FILTER d._from == @id AND d.ended_at == null
+                FILTER DOCUMENT(d._to).category != @fungible
                 RETURN d";
             bind_vars.insert("@edge_collection_name", json!(Hold::COLLECTION_NAME));
             bind_vars.insert("id", json!(self.id().as_str()));
+            bind_vars.insert("fungible", to_value(ContractCategory::ERC20).unwrap());
         } else {
             aql_str = r"WITH @@identities FOR d IN @@identities
             FILTER d._id == @id LIMIT 1
             FOR vertex, edge
                 IN 1..1 ANY d @@holds
-                FILTER vertex.category IN @category
+                FILTER vertex.category IN @category AND edge.ended_at == null
                 RETURN DISTINCT edge";
 
             let category_array: Vec<Value> = category
@@ -656,10 +750,21 @@ impl IdentityRecord {
             .await?
             .into_iter()
             .filter(|x| x.id_to().contains("Contracts"))
+            .filter(|x| min_balance.map_or(true, |min| x.balance() >= min))
             .collect();
 
         Ok(result)
     }
+
+    /// Returns all fungible tokens (ERC20) currently held by this identity.
+    pub async fn fungible_tokens(
+        &self,
+        pool: &ConnectionPool,
+        min_balance: Option<f64>,
+    ) -> Result<Vec<HoldRecord>, Error> {
+        self.nfts(pool, Some(vec![ContractCategory::ERC20]), min_balance)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -701,6 +806,8 @@ mod tests {
                 created_at: Some(config.fake()),
                 added_at: naive_now(),
                 updated_at: naive_now(),
+                chains: vec![],
+                is_contract: None,
             }
         }
     }
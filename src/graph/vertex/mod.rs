@@ -1,12 +1,16 @@
 pub mod contract;
+mod domain;
 mod identity;
+mod token;
 // mod crypto_identity;
 
 use crate::upstream::DataSource;
 use aragog::{DatabaseConnection, Record};
 use async_trait::async_trait;
 pub use contract::{Contract, ContractRecord};
+pub use domain::{Domain, DomainRecord, PrimaryName, PrimaryNameLoadFn};
 pub use identity::{FromToLoadFn, Identity, IdentityLoadFn, IdentityRecord, IdentityWithSource};
+pub use token::{Token, TokenAttribute, TokenLoadFn, TokenRecord};
 use uuid::Uuid;
 
 use crate::error::Error;
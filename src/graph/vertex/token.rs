@@ -0,0 +1,355 @@
+use crate::{
+    error::Error,
+    graph::{vertex::contract::Chain, ConnectionPool, Vertex},
+    upstream::DataSource,
+    util::naive_now,
+};
+use aragog::{
+    query::{Comparison, Filter},
+    AqlQuery, DatabaseAccess, DatabaseConnection, DatabaseRecord, Record,
+};
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDateTime};
+use dataloader::BatchFn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Metadata of a token (i.e. an NFT) in a `Contract`, for clients to render it.
+#[derive(Clone, Serialize, Deserialize, Record, Debug)]
+#[collection_name = "Tokens"]
+pub struct Token {
+    /// UUID of this record.
+    pub uuid: Uuid,
+    /// On which chain?
+    pub chain: Chain,
+    /// Contract address
+    pub address: String,
+    /// Token ID in contract. Same as `Hold.id`, i.e. the name for ENS.
+    pub token_id: String,
+    /// Name of this token.
+    pub name: Option<String>,
+    /// Description of this token.
+    pub description: Option<String>,
+    /// Image URI. `ipfs://` and `ar://` ones are converted into HTTP gateway URLs.
+    pub image: Option<String>,
+    /// Traits of this token.
+    #[serde(default)]
+    pub attributes: Vec<TokenAttribute>,
+    /// Where the metadata is served, i.e. `tokenURI()` of ERC721 or `uri()` of ERC1155.
+    pub token_uri: Option<String>,
+    /// Data source (upstream) which provides this info.
+    pub source: DataSource,
+    /// When this data is fetched by RelationService.
+    pub updated_at: NaiveDateTime,
+}
+
+/// A trait of a token, e.g. `{"trait_type": "Background", "value": "Blue"}`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct TokenAttribute {
+    pub trait_type: String,
+    /// Numbers and booleans are kept in their JSON form.
+    pub value: String,
+}
+
+impl TokenAttribute {
+    /// From an item of `attributes` in metadata JSON, e.g. `{"trait_type": "Level", "value": 5}`.
+    /// `None` if it has no value.
+    pub fn from_json(attribute: &Value) -> Option<Self> {
+        let trait_type = attribute
+            .get("trait_type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let value = match attribute.get("value")? {
+            Value::Null => return None,
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+        Some(Self { trait_type, value })
+    }
+}
+
+impl Default for Token {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            chain: Default::default(),
+            address: Default::default(),
+            token_id: Default::default(),
+            name: None,
+            description: None,
+            image: None,
+            attributes: vec![],
+            token_uri: None,
+            source: Default::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+impl Token {
+    pub async fn find_by_chain_address_token_id(
+        db: &DatabaseConnection,
+        chain: &Chain,
+        address: &str,
+        token_id: &str,
+    ) -> Result<Option<TokenRecord>, Error> {
+        let query = Self::query().filter(
+            Filter::new(Comparison::field("chain").equals_str(chain))
+                .and(Comparison::field("address").equals_str(address))
+                .and(Comparison::field("token_id").equals_str(token_id)),
+        );
+        let result = Self::get(&query, db).await?;
+        if result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.first().unwrap().to_owned().into()))
+        }
+    }
+}
+
+#[async_trait]
+impl Vertex<TokenRecord> for Token {
+    fn uuid(&self) -> Option<Uuid> {
+        Some(self.uuid)
+    }
+
+    /// Create or update a token by (chain, address, token_id).
+    /// Metadata missing in `self` doesn't erase the known one.
+    async fn create_or_update(&self, db: &DatabaseConnection) -> Result<TokenRecord, Error> {
+        let found =
+            Self::find_by_chain_address_token_id(db, &self.chain, &self.address, &self.token_id)
+                .await?;
+        match found {
+            None => {
+                let mut to_be_created = self.clone();
+                to_be_created.updated_at = naive_now();
+                let created = DatabaseRecord::create(to_be_created, db).await?;
+                Ok(created.into())
+            }
+            Some(mut found) => {
+                found.name = self.name.clone().or(found.name.clone());
+                found.description = self.description.clone().or(found.description.clone());
+                found.image = self.image.clone().or(found.image.clone());
+                if !self.attributes.is_empty() {
+                    found.attributes = self.attributes.clone();
+                }
+                found.token_uri = self.token_uri.clone().or(found.token_uri.clone());
+                found.source = self.source;
+                found.updated_at = naive_now();
+                found.save(db).await?;
+                Ok(found)
+            }
+        }
+    }
+
+    async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: Uuid,
+    ) -> Result<Option<TokenRecord>, Error> {
+        let query = Token::query().filter(Comparison::field("uuid").equals_str(uuid).into());
+        let query_result = Token::get(&query, db).await?;
+        if query_result.len() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(query_result.first().unwrap().to_owned().into()))
+        }
+    }
+
+    /// Outdated in 1 day
+    fn is_outdated(&self) -> bool {
+        let outdated_in = Duration::days(1);
+        self.updated_at
+            .checked_add_signed(outdated_in)
+            .unwrap()
+            .lt(&naive_now())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Default, Debug)]
+pub struct TokenRecord(pub DatabaseRecord<Token>);
+
+impl std::ops::Deref for TokenRecord {
+    type Target = DatabaseRecord<Token>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for TokenRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<DatabaseRecord<Token>> for TokenRecord {
+    fn from(record: DatabaseRecord<Token>) -> Self {
+        Self(record)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+struct ContractToken {
+    contract_id: String,
+    token_id: String,
+    token: TokenRecord,
+}
+
+/// Loads `Token`s by `(contract_id, token_id)`, where `contract_id` is `_id` of the `Contract`
+/// (i.e. `_to` of a `Hold`) and `token_id` is `Hold.id`.
+pub struct TokenLoadFn {
+    pub pool: ConnectionPool,
+}
+
+#[async_trait]
+impl BatchFn<(String, String), Option<TokenRecord>> for TokenLoadFn {
+    async fn load(
+        &mut self,
+        keys: &[(String, String)],
+    ) -> HashMap<(String, String), Option<TokenRecord>> {
+        debug!("Loading token for: {:?}", keys);
+        match get_tokens(&self.pool, keys.to_vec()).await {
+            Ok(tokens) => tokens,
+            Err(_) => keys.iter().map(|k| (k.to_owned(), None)).collect(),
+        }
+    }
+}
+
+async fn get_tokens(
+    pool: &ConnectionPool,
+    keys: Vec<(String, String)>,
+) -> Result<HashMap<(String, String), Option<TokenRecord>>, Error> {
+    let conn = pool
+        .get()
+        .await
+        .map_err(|err| Error::PoolError(err.to_string()))?;
+    let db = conn.database();
+
+    let aql_str = r###"
+    FOR key IN @keys
+        LET c = DOCUMENT(key[0])
+        FILTER c != null
+        FOR t IN @@tokens
+            FILTER t.chain == c.chain AND t.address == c.address AND t.token_id == key[1]
+            LIMIT 1
+            RETURN {"contract_id": key[0], "token_id": key[1], "token": t}"###;
+    let aql = AqlQuery::new(aql_str)
+        .bind_var("@tokens", Token::COLLECTION_NAME)
+        .bind_var(
+            "keys",
+            keys.iter()
+                .map(|(contract_id, token_id)| vec![contract_id.clone(), token_id.clone()])
+                .collect::<Vec<Vec<String>>>(),
+        )
+        .batch_size(1)
+        .count(false);
+
+    let result: Vec<ContractToken> = db.aql_query(aql).await?;
+    let mut tokens: HashMap<(String, String), Option<TokenRecord>> = result
+        .into_iter()
+        .map(|found| ((found.contract_id, found.token_id), Some(found.token)))
+        .collect();
+    for key in keys.into_iter() {
+        tokens.entry(key).or_insert(None);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{arangopool::new_connection_pool, new_db_connection, vertex::Contract};
+
+    #[test]
+    fn test_attribute_from_json() {
+        let attribute =
+            TokenAttribute::from_json(&serde_json::json!({"trait_type": "Level", "value": 5}));
+        assert_eq!(
+            attribute,
+            Some(TokenAttribute {
+                trait_type: "Level".into(),
+                value: "5".into()
+            })
+        );
+        let attribute = TokenAttribute::from_json(&serde_json::json!({"value": "Blue"}));
+        assert_eq!(attribute.unwrap().trait_type, "");
+        assert_eq!(
+            TokenAttribute::from_json(&serde_json::json!({"trait_type": "Level"})),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_or_update() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let token = Token {
+            chain: Chain::Ethereum,
+            address: format!("0x{}", Uuid::new_v4().simple()),
+            token_id: "1".into(),
+            name: Some("Token #1".into()),
+            attributes: vec![TokenAttribute {
+                trait_type: "Background".into(),
+                value: "Blue".into(),
+            }],
+            source: DataSource::Rss3,
+            ..Default::default()
+        };
+        let created = token.create_or_update(&db).await?;
+
+        // Missing info doesn't erase the known one.
+        let updated = Token {
+            image: Some("https://example.com/1.png".into()),
+            source: DataSource::RPC,
+            ..token.clone()
+        };
+        let updated = Token {
+            name: None,
+            attributes: vec![],
+            ..updated
+        }
+        .create_or_update(&db)
+        .await?;
+        assert_eq!(created.key(), updated.key());
+        assert_eq!(updated.name, token.name);
+        assert_eq!(updated.attributes, token.attributes);
+        assert_eq!(updated.image, Some("https://example.com/1.png".into()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_tokens() -> Result<(), Error> {
+        let db = new_db_connection().await?;
+        let pool = new_connection_pool().await?;
+        let address = format!("0x{}", Uuid::new_v4().simple());
+        let contract = Contract {
+            chain: Chain::Ethereum,
+            address: address.clone(),
+            ..Default::default()
+        }
+        .create_or_update(&db)
+        .await?;
+        Token {
+            chain: Chain::Ethereum,
+            address,
+            token_id: "1".into(),
+            name: Some("Token #1".into()),
+            ..Default::default()
+        }
+        .create_or_update(&db)
+        .await?;
+
+        let found = (contract.id().clone(), "1".to_string());
+        let missing = (contract.id().clone(), "2".to_string());
+        let tokens = get_tokens(&pool, vec![found.clone(), missing.clone()]).await?;
+        assert_eq!(
+            tokens[&found].as_ref().unwrap().name,
+            Some("Token #1".into())
+        );
+        assert!(tokens[&missing].is_none());
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use warp::{http::StatusCode, path::FullPath, Filter};
 
 /// Bootstrap function to be defined before each test case.
 /// Automaticlly executed, don't call this manually.
@@ -20,3 +23,50 @@ fn before_each() {
     tracing::subscriber::set_global_default(log_subscriber)
         .expect("Setting default subscriber failed");
 }
+
+/// Serve `filter` on a random local port in background.
+/// Returns base URL (e.g. `http://127.0.0.1:12345`) of this stand-in upstream server.
+pub(crate) fn spawn_stub_server<F>(filter: F) -> String
+where
+    F: warp::Filter<Error = warp::Rejection> + Clone + Send + Sync + 'static,
+    F::Extract: warp::Reply,
+{
+    let (addr, server) = warp::serve(filter).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    format!("http://{}", addr)
+}
+
+/// Serve each `(path, body)` of `routes` as JSON, regardless of method and query. Others are 404.
+/// Returns base URL of this stand-in upstream server.
+pub(crate) fn spawn_json_server(routes: Vec<(&'static str, Value)>) -> String {
+    let routes: Arc<HashMap<&'static str, Value>> = Arc::new(routes.into_iter().collect());
+    let server = warp::path::full().map(move |path: FullPath| match routes.get(path.as_str()) {
+        Some(body) => warp::reply::with_status(warp::reply::json(body), StatusCode::OK),
+        None => warp::reply::with_status(warp::reply::json(&Value::Null), StatusCode::NOT_FOUND),
+    });
+    spawn_stub_server(server)
+}
+
+/// A JSON-RPC 2.0 server stand-in, e.g. an Ethereum node.
+/// `handler` gets `method` and `params` of each request, and returns its `result`,
+/// or `Err((code, message))` to reply with an error.
+/// Returns URL of this server.
+pub(crate) fn spawn_rpc_server<F>(handler: F) -> String
+where
+    F: Fn(&str, &Value) -> Result<Value, (i64, String)> + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    let server = warp::post().and(warp::body::json()).map(move |req: Value| {
+        let method = req["method"].as_str().unwrap_or_default();
+        let reply = match handler(method, &req["params"]) {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": req["id"], "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": req["id"],
+                "error": {"code": code, "message": message},
+            }),
+        };
+        warp::reply::json(&reply)
+    });
+    spawn_stub_server(server)
+}
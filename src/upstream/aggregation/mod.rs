@@ -2,24 +2,31 @@
 mod tests;
 
 use super::{DataFetcher, Target};
-use crate::config::C;
+use crate::config::{ConfigAggregationService, C};
 use crate::error::Error;
-use crate::graph::edge::Proof;
-use crate::graph::vertex::Identity;
-use crate::graph::{create_identity_to_identity_record, new_db_connection};
+use crate::graph::checkpoint::Checkpoint;
+use crate::graph::edge::{Edge, Proof};
+use crate::graph::new_db_connection;
+use crate::graph::vertex::{Identity, Vertex};
 use crate::upstream::{DataSource, Fetcher, Platform, TargetProcessedList};
 use crate::util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive};
+use aragog::DatabaseConnection;
 use async_trait::async_trait;
-use futures::future::join_all;
+use chrono::NaiveDateTime;
 use hyper::{Body, Method};
 use serde::Deserialize;
 use std::str::FromStr;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// Stop paging after this many pages, in case the service keeps giving a `next`.
+/// The next fetch resumes from where it stops.
+const MAX_PAGES: u32 = 20;
+
 #[derive(Deserialize, Debug)]
 pub struct Pagination {
     pub current: u32,
+    /// `0` if there is no more page.
     pub next: u32,
 }
 
@@ -41,21 +48,15 @@ pub struct Response {
     pub pagination: Pagination,
     pub records: Vec<Record>,
 }
+
+/// Identity search of an aggregation service which collects connections from other upstreams.
+/// Enabled by `upstream.aggregation_service.enabled`.
 pub struct Aggregation {}
 
 #[async_trait]
 impl Fetcher for Aggregation {
     async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
-        if !Self::can_fetch(target) {
-            return Ok(vec![]);
-        }
-
-        match target {
-            Target::Identity(platform, identity) => {
-                fetch_connections_by_platform_identity(platform, identity).await
-            }
-            Target::NFT(_, _, _, _) => todo!(),
-        }
+        fetch_with_config(&C.upstream.aggregation_service, target).await
     }
 
     fn can_fetch(target: &Target) -> bool {
@@ -63,19 +64,83 @@ impl Fetcher for Aggregation {
     }
 }
 
+/// `Aggregation::fetch` with `config` instead of the one in `C`.
+async fn fetch_with_config(
+    config: &ConfigAggregationService,
+    target: &Target,
+) -> Result<TargetProcessedList, Error> {
+    if !config.enabled || !Aggregation::can_fetch(target) {
+        return Ok(vec![]);
+    }
+
+    match target {
+        Target::Identity(platform, identity) => {
+            fetch_connections_by_platform_identity(&config.url, platform, identity).await
+        }
+        Target::NFT(_, _, _, _) => Ok(vec![]),
+    }
+}
+
 async fn fetch_connections_by_platform_identity(
+    base: &str,
     platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
-    let client = make_client();
-    let mut page = 1;
+    let db = new_db_connection().await?;
+    let checkpoint = format!("aggregation:{}:{}", platform, identity);
+    let start_page = Checkpoint::load_cursor(&db, &checkpoint)
+        .await?
+        .and_then(|page| page.parse().ok())
+        .unwrap_or(1);
+    let (records, next_page) = fetch_records(base, platform, identity, start_page).await?;
+    if let Some(next_page) = next_page {
+        warn!(
+            "AggregationService {} | Stopped at {} pages, resuming from page {} next time",
+            checkpoint, MAX_PAGES, next_page
+        );
+    }
+    Checkpoint::save_cursor(
+        &db,
+        &checkpoint,
+        next_page.map(|p| p.to_string()).as_deref(),
+    )
+    .await?;
+    if records.is_empty() {
+        info!("aggregation service response is empty");
+        return Ok(vec![]);
+    }
 
     let mut next_targets: TargetProcessedList = Vec::new();
+    for record in records.into_iter() {
+        match save_item(&db, record).await {
+            Ok(Some(target)) => {
+                if !next_targets.contains(&target) {
+                    next_targets.push(target);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => warn!("AggregationService | Fail to save record: {}", err),
+        }
+    }
 
-    loop {
+    Ok(next_targets)
+}
+
+/// Fetch pages of `{base}?platform=&identity=&page=&size=` from `page`, `MAX_PAGES` at most.
+/// Returns the records, and the page to resume from if there are more.
+async fn fetch_records(
+    base: &str,
+    platform: &Platform,
+    identity: &str,
+    mut page: u32,
+) -> Result<(Vec<Record>, Option<u32>), Error> {
+    let client = make_client();
+    let mut records: Vec<Record> = Vec::new();
+
+    for _ in 0..MAX_PAGES {
         let uri: http::Uri = match format!(
             "{}?platform={}&identity={}&page={}&size=100",
-            C.upstream.aggregation_service.url, platform, identity, page
+            base, platform, identity, page
         )
         .parse()
         {
@@ -100,38 +165,53 @@ async fn fetch_connections_by_platform_identity(
 
         let body: Response = parse_body(&mut resp).await?;
         if body.records.is_empty() {
-            info!("aggregation service response is empty");
-            break;
+            return Ok((records, None));
         }
+        records.extend(body.records);
 
-        let futures: Vec<_> = body.records.into_iter().map(save_item).collect();
-        let targets: TargetProcessedList = join_all(futures)
-            .await
-            .into_iter()
-            .flat_map(|result| result.unwrap_or_default())
-            .collect();
-        next_targets.extend(targets);
-
-        if body.pagination.current == body.pagination.next {
-            break;
+        // `next` is `0` (or not moving forward) on the last page.
+        if body.pagination.next == 0 || body.pagination.next <= body.pagination.current {
+            return Ok((records, None));
         }
         page = body.pagination.next;
     }
 
-    Ok(next_targets)
+    Ok((records, Some(page)))
 }
 
-async fn save_item(p: Record) -> Result<TargetProcessedList, Error> {
-    let db = new_db_connection().await?;
-    let mut targets = Vec::new();
+/// Maps `source` of aggregated records onto our `DataSource`.
+pub fn parse_source(source: &str) -> DataSource {
+    let source = source.to_lowercase();
+    match source.as_str() {
+        "sybil" | "sybil_list" | "sybillist" | "uniswap" => DataSource::SybilList,
+        "next_id" | "nextid" | "proof_service" => DataSource::NextID,
+        "eth_leaderboard" | "ethleaderboard" => DataSource::EthLeaderboard,
+        "thegraph" | "the_graph" | "ens" => DataSource::TheGraph,
+        "dotbit" | ".bit" | "bit" => DataSource::Dotbit,
+        "unstoppable" | "unstoppable_domains" | "unstoppabledomains" => {
+            DataSource::UnstoppableDomains
+        }
+        "spaceid" | "space_id" => DataSource::SpaceId,
+        _ => DataSource::from_str(&source).unwrap_or_default(),
+    }
+}
+
+/// `"1654669460431"` (milliseconds) => `NaiveDateTime`
+fn parse_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    let ms = timestamp.parse::<i64>().ok()?;
+    Some(timestamp_to_naive(ms / 1000, (ms % 1000) as u32))
+}
 
+/// Saves a record as a `Proof` between SNS handle and web3 address.
+/// Returns the web3 address as next target.
+async fn save_item(db: &DatabaseConnection, p: Record) -> Result<Option<Target>, Error> {
     let from_platform = Platform::from_str(p.sns_platform.as_str()).unwrap_or(Platform::Unknown);
     if from_platform == Platform::Unknown {
         error!(
             "AggregationService from_platform unknown , original data is: {:?}",
             p
         );
-        return Ok(vec![]);
+        return Ok(None);
     }
     let from: Identity = Identity {
         uuid: Some(Uuid::new_v4()),
@@ -143,6 +223,8 @@ async fn save_item(p: Record) -> Result<TargetProcessedList, Error> {
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
 
     let to_platform = Platform::from_str(p.web3_platform.as_str()).unwrap_or_default();
@@ -151,13 +233,13 @@ async fn save_item(p: Record) -> Result<TargetProcessedList, Error> {
             "AggregationService to_platform unknown , original data is: {:?}",
             p
         );
-        return Ok(vec![]);
+        return Ok(None);
     }
     let web3_addr = p.web3_addr.to_lowercase();
     let to: Identity = Identity {
         uuid: Some(Uuid::new_v4()),
         platform: to_platform,
-        identity: web3_addr.clone().to_lowercase(),
+        identity: web3_addr.clone(),
         created_at: None,
         // Don't use ETH's wallet as display_name, use ENS reversed lookup instead.
         display_name: None,
@@ -165,37 +247,39 @@ async fn save_item(p: Record) -> Result<TargetProcessedList, Error> {
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
 
-    let create_ms_time: u32 = (p.create_timestamp.parse::<i64>().unwrap() % 1000)
-        .try_into()
-        .unwrap();
-    let update_ms_time: u32 = (p.modify_timestamp.parse::<i64>().unwrap() % 1000)
-        .try_into()
-        .unwrap();
-
-    let source = DataSource::from_str(p.source.as_str()).unwrap_or(DataSource::Unknown);
-    if source == DataSource::Rss3 {
-        debug!("AggregationService filter source={}", DataSource::Rss3);
-        return Ok(vec![]);
+    let source = parse_source(&p.source);
+    if source == DataSource::Unknown {
+        debug!("AggregationService unknown source {}", p.source);
     }
     let pf: Proof = Proof {
         uuid: Uuid::new_v4(),
         source,
         record_id: Some(p.id.clone()),
-        created_at: Some(timestamp_to_naive(
-            p.create_timestamp.parse::<i64>().unwrap() / 1000,
-            create_ms_time,
-        )),
-        updated_at: timestamp_to_naive(
-            p.modify_timestamp.parse::<i64>().unwrap() / 1000,
-            update_ms_time,
-        ),
+        created_at: parse_timestamp(&p.create_timestamp),
+        updated_at: parse_timestamp(&p.modify_timestamp).unwrap_or_else(naive_now),
         fetcher: DataFetcher::AggregationService,
+        deleted_at: None,
     };
 
-    let _ = create_identity_to_identity_record(&db, &from, &to, &pf).await;
+    let from_record = from.create_or_update(db).await?;
+    let to_record = to.create_or_update(db).await?;
+    // Same connection from the same source may already be fetched from that upstream directly
+    // (with its own `record_id`). Keep that one instead of adding a duplicated edge.
+    match Proof::find_by_from_to(db, &from_record, &to_record, &source, &None).await? {
+        Some(found) if found.fetcher != DataFetcher::AggregationService => {
+            debug!(
+                "AggregationService | {} => {} ({}) exists, merged.",
+                from.identity, web3_addr, source
+            );
+        }
+        _ => {
+            pf.connect(db, &from_record, &to_record).await?;
+        }
+    }
 
-    targets.push(Target::Identity(to_platform, web3_addr.clone()));
-    Ok(targets)
+    Ok(Some(Target::Identity(to_platform, web3_addr)))
 }
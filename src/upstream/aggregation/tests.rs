@@ -1,19 +1,35 @@
 use crate::{
+    config::{ConfigAggregationService, C},
     error::Error,
     graph::new_db_connection,
     graph::vertex::{
         contract::{Chain, ContractCategory},
         Contract, Identity,
     },
-    upstream::{aggregation::Aggregation, Target},
-    upstream::{Fetcher, Platform},
+    tests::spawn_stub_server,
+    upstream::aggregation::{
+        fetch_connections_by_platform_identity, fetch_records, fetch_with_config, parse_source,
+    },
+    upstream::{DataSource, Platform, Target},
     util::timestamp_to_naive,
 };
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use warp::Filter;
 
 #[tokio::test]
 async fn test_smoke_aggregation() -> Result<(), Error> {
-    let target = Target::Identity(Platform::Twitter, "blake".to_string());
-    let _ = Aggregation::fetch(&target).await?;
+    // Call it directly, `Aggregation::fetch` is a no-op unless enabled in config.
+    let _ = fetch_connections_by_platform_identity(
+        &C.upstream.aggregation_service.url,
+        &Platform::Twitter,
+        "blake",
+    )
+    .await?;
 
     let db = new_db_connection().await?;
 
@@ -50,3 +66,76 @@ async fn test_parse_timestamp() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_source() {
+    assert_eq!(parse_source("sybil"), DataSource::SybilList);
+    assert_eq!(parse_source("next_id"), DataSource::NextID);
+    assert_eq!(parse_source("RSS3"), DataSource::Rss3);
+    assert_eq!(parse_source("ethLeaderboard"), DataSource::EthLeaderboard);
+    assert_eq!(parse_source("not-a-source"), DataSource::Unknown);
+}
+
+#[tokio::test]
+async fn test_fetch_records_paginated() -> Result<(), Error> {
+    // 3 pages, the last one gives `next: 0`.
+    let search = warp::query::<HashMap<String, String>>().map(|q: HashMap<String, String>| {
+        let page: u32 = q.get("page").unwrap().parse().unwrap();
+        let next = if page < 3 { page + 1 } else { 0 };
+        warp::reply::json(&json!({
+            "pagination": { "current": page, "next": next },
+            "records": [{
+                "id": format!("record-{}", page),
+                "sns_handle": "blake",
+                "sns_platform": "twitter",
+                "web3_addr": "0x0000000000000000000000000000000000000001",
+                "web3_platform": "ethereum",
+                "source": "rss3",
+                "ens": null,
+                "create_timestamp": "1654669460431",
+                "modify_timestamp": "1654669460431"
+            }]
+        }))
+    });
+    let base = spawn_stub_server(search);
+
+    let (records, next_page) = fetch_records(&base, &Platform::Twitter, "blake", 1).await?;
+    assert_eq!(
+        records.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+        vec!["record-1", "record-2", "record-3"]
+    );
+    assert_eq!(next_page, None);
+
+    let (records, _) = fetch_records(&base, &Platform::Twitter, "blake", 3).await?;
+    assert_eq!(records.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_disabled_by_default() -> Result<(), Error> {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let search = warp::query::<HashMap<String, String>>().map(move |_: HashMap<String, String>| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        warp::reply::json(&json!({
+            "pagination": { "current": 1, "next": 0 },
+            "records": []
+        }))
+    });
+    let base = spawn_stub_server(search);
+    let target = Target::Identity(Platform::Twitter, "blake".to_string());
+
+    let config: ConfigAggregationService = serde_json::from_value(json!({ "url": base }))?;
+    assert!(!config.enabled);
+    assert!(fetch_with_config(&config, &target).await?.is_empty());
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+    let config = ConfigAggregationService {
+        enabled: true,
+        ..config
+    };
+    fetch_with_config(&config, &target).await?;
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    Ok(())
+}
@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    config::C,
+    error::Error,
+    graph::{
+        edge::Proof,
+        new_db_connection,
+        vertex::{contract::Chain, Identity, IdentityRecord, Vertex},
+        Edge,
+    },
+    upstream::{
+        eth_rpc::{self, data_words, parse_hex_u64, word_to_address},
+        AccountId, DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList,
+    },
+    util::{is_evm_address, naive_now},
+};
+use aragog::DatabaseConnection;
+use async_trait::async_trait;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// `getOwners()` of Safe (formerly Gnosis Safe).
+/// https://github.com/safe-global/safe-contracts/blob/main/contracts/base/OwnerManager.sol
+pub const SELECTOR_GET_OWNERS: &str = "0xa0e67e2b";
+
+/// Detects if an Ethereum wallet is a contract (`eth_getCode`) on chains in
+/// `upstream.contract_wallet.chains`. For Safe multisigs, every owner is connected to the Safe.
+pub struct ContractWallet {}
+
+#[async_trait]
+impl Fetcher for ContractWallet {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(_, address) => fetch_contract_wallet(address).await,
+            Target::NFT(_, _, _, _) => Ok(vec![]),
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Ethereum])
+    }
+}
+
+async fn fetch_contract_wallet(address: &str) -> Result<TargetProcessedList, Error> {
+    if !is_evm_address(address) {
+        return Err(Error::ParamError(format!(
+            "ContractWallet: invalid Ethereum address {}",
+            address
+        )));
+    }
+    let chains: Vec<(Chain, String)> = C
+        .upstream
+        .contract_wallet
+        .chains
+        .iter()
+        .filter_map(|chain| match chain.info().and_then(|i| i.rpc_url.clone()) {
+            Some(rpc) => Some((*chain, rpc)),
+            None => {
+                warn!(
+                    "ContractWallet: no rpc_url configured for {}, skipped",
+                    chain
+                );
+                None
+            }
+        })
+        .collect();
+    if chains.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let db = new_db_connection().await?;
+    detect(&db, &chains, address).await
+}
+
+/// Records whether `address` is a contract on any of `chains` (`(chain, rpc_url)`),
+/// and connects owners of Safes among them. Returns owners found.
+pub async fn detect(
+    db: &DatabaseConnection,
+    chains: &[(Chain, String)],
+    address: &str,
+) -> Result<TargetProcessedList, Error> {
+    let address = address.to_lowercase();
+    let mut contract_chains: Vec<(Chain, &str)> = vec![];
+    for (chain, rpc) in chains.iter() {
+        let code = eth_rpc::get_code(rpc, &address).await?;
+        if !code.trim_start_matches("0x").is_empty() {
+            contract_chains.push((*chain, rpc));
+        }
+    }
+
+    let mut wallet = ethereum_identity(&address, contract_chains.iter().map(|(c, _)| *c).collect());
+    wallet.is_contract = Some(!contract_chains.is_empty());
+    let wallet_record = wallet.create_or_update(db).await?;
+
+    let mut next_targets: TargetProcessedList = vec![];
+    for (chain, rpc) in contract_chains.into_iter() {
+        let owners = match get_owners(rpc, &address).await {
+            Some(owners) => owners,
+            None => {
+                debug!("ContractWallet: {} on {} is not a Safe", address, chain);
+                continue;
+            }
+        };
+        save_owners(db, chain, &wallet_record, &owners).await?;
+        for owner in owners.into_iter() {
+            next_targets.push(Target::Identity(Platform::Ethereum, owner));
+        }
+    }
+    next_targets.dedup();
+
+    Ok(next_targets)
+}
+
+/// Owners of Safe `address`. `None` if it is not a Safe, i.e. `getOwners()` reverts or returns garbage.
+async fn get_owners(rpc: &str, address: &str) -> Option<Vec<String>> {
+    let result = eth_rpc::eth_call(rpc, address, SELECTOR_GET_OWNERS, None)
+        .await
+        .ok()?;
+    decode_addresses(&result)
+}
+
+/// ABI decodes a single `address[]` return value.
+pub fn decode_addresses(data: &str) -> Option<Vec<String>> {
+    let words = data_words(data);
+    let offset = parse_hex_u64(words.first()?)? as usize / 32;
+    let length = parse_hex_u64(words.get(offset)?)? as usize;
+    let addresses = words.get(offset + 1..offset + 1 + length)?;
+    let addresses: Vec<String> = addresses
+        .iter()
+        .map(|w| word_to_address(w))
+        .collect::<Option<_>>()?;
+    if addresses.is_empty() {
+        return None;
+    }
+    Some(addresses)
+}
+
+/// Connects every owner to the Safe, and tombstones connections from former owners.
+async fn save_owners(
+    db: &DatabaseConnection,
+    chain: Chain,
+    safe: &IdentityRecord,
+    owners: &[String],
+) -> Result<(), Error> {
+    let record_id = AccountId::new(&Platform::Ethereum, Some(&chain), &safe.identity)
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| format!("{}:{}", chain, safe.identity));
+    let proof = Proof {
+        uuid: Uuid::new_v4(),
+        source: DataSource::RPC,
+        record_id: Some(record_id.clone()),
+        created_at: None,
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        deleted_at: None,
+    };
+
+    let mut owner_records: Vec<IdentityRecord> = vec![];
+    for owner in owners.iter() {
+        let owner_record = ethereum_identity(owner, vec![chain])
+            .create_or_update(db)
+            .await?;
+        proof.connect(db, &owner_record.0, &safe.0).await?;
+        owner_records.push(owner_record);
+    }
+    let tombstoned =
+        Proof::tombstone_except(db, &DataSource::RPC, &record_id, &owner_records).await?;
+    if tombstoned > 0 {
+        debug!(
+            "ContractWallet: {} former owners of {} removed",
+            tombstoned, record_id
+        );
+    }
+    Ok(())
+}
+
+fn ethereum_identity(address: &str, chains: Vec<Chain>) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Ethereum,
+        identity: address.to_lowercase(),
+        created_at: None,
+        // Don't use ETH's wallet as display_name, use ENS reversed lookup instead.
+        display_name: None,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains,
+        is_contract: None,
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{
+    error::Error,
+    graph::{
+        edge::{Proof, ProofRecord},
+        new_db_connection,
+        vertex::{contract::Chain, Identity, IdentityRecord},
+    },
+    tests::spawn_rpc_server,
+    upstream::{
+        contract_wallet::{decode_addresses, detect, SELECTOR_GET_OWNERS},
+        DataSource, Platform, Target,
+    },
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+const ALICE: &str = "0x00000000000000000000000000000000000a11ce";
+const BOB: &str = "0x0000000000000000000000000000000000000b0b";
+const CAROL: &str = "0x00000000000000000000000000000000000ca201";
+
+fn encode_addresses(addresses: &[&str]) -> String {
+    let mut data = format!("0x{:0>64}{:0>64x}", "20", addresses.len());
+    for address in addresses {
+        data.push_str(&format!("{:0>64}", address.trim_start_matches("0x")));
+    }
+    data
+}
+
+#[test]
+fn test_decode_addresses() {
+    assert_eq!(
+        decode_addresses(&encode_addresses(&[ALICE, BOB])),
+        Some(vec![ALICE.to_string(), BOB.to_string()])
+    );
+    assert_eq!(decode_addresses("0x"), None);
+    assert_eq!(decode_addresses(&encode_addresses(&[])), None);
+    // Length is larger than the data.
+    assert_eq!(
+        decode_addresses(&format!("0x{:0>64}{:0>64}", "20", "2")),
+        None
+    );
+}
+
+/// A JSON-RPC node stand-in where `safe` is a Safe owned by `owners`, and any other address is an EOA.
+fn spawn_node(safe: String, owners: Arc<Mutex<Vec<&'static str>>>) -> String {
+    spawn_rpc_server(move |method, params| {
+        Ok(match method {
+            "eth_getCode" if params[0] == json!(safe) => json!("0x608060405273ffffffffffff"),
+            "eth_getCode" => json!("0x"),
+            "eth_call"
+                if params[0]["to"] == json!(safe)
+                    && params[0]["data"] == json!(SELECTOR_GET_OWNERS) =>
+            {
+                json!(encode_addresses(&owners.lock().unwrap()))
+            }
+            _ => Value::Null,
+        })
+    })
+}
+
+async fn owner_proof(safe: &IdentityRecord, owner: &str) -> Result<ProofRecord, Error> {
+    let db = new_db_connection().await?;
+    let owner = Identity::find_by_platform_identity(&db, &Platform::Ethereum, owner)
+        .await?
+        .expect("Owner should be saved");
+    let record_id = Some(format!("eip155:1:{}", safe.identity));
+    Ok(
+        Proof::find_by_from_to(&db, &owner, safe, &DataSource::RPC, &record_id)
+            .await?
+            .expect("Owner should be connected"),
+    )
+}
+
+#[tokio::test]
+async fn test_detect_safe() -> Result<(), Error> {
+    let safe = format!("0x{:0>40}", &Uuid::new_v4().simple().to_string()[..20]);
+    let owners = Arc::new(Mutex::new(vec![ALICE, BOB]));
+    let chains = vec![(Chain::Ethereum, spawn_node(safe.clone(), owners.clone()))];
+    let db = new_db_connection().await?;
+
+    let next_targets = detect(&db, &chains, &safe).await?;
+    assert_eq!(
+        next_targets,
+        vec![
+            Target::Identity(Platform::Ethereum, ALICE.to_string()),
+            Target::Identity(Platform::Ethereum, BOB.to_string()),
+        ]
+    );
+    let safe_record = Identity::find_by_platform_identity(&db, &Platform::Ethereum, &safe)
+        .await?
+        .expect("Safe should be saved");
+    assert_eq!(safe_record.is_contract, Some(true));
+    assert_eq!(safe_record.chains, vec![Chain::Ethereum]);
+
+    // EOAs are marked as is.
+    assert!(detect(&db, &chains, ALICE).await?.is_empty());
+    let alice = Identity::find_by_platform_identity(&db, &Platform::Ethereum, ALICE)
+        .await?
+        .unwrap();
+    assert_eq!(alice.is_contract, Some(false));
+
+    // Alice is replaced by Carol.
+    *owners.lock().unwrap() = vec![BOB, CAROL];
+    detect(&db, &chains, &safe).await?;
+    assert!(owner_proof(&safe_record, ALICE).await?.deleted_at.is_some());
+    assert!(owner_proof(&safe_record, BOB).await?.deleted_at.is_none());
+    assert!(owner_proof(&safe_record, CAROL).await?.deleted_at.is_none());
+    Ok(())
+}
@@ -0,0 +1,297 @@
+#[cfg(test)]
+mod tests;
+
+use crate::config::C;
+use crate::error::Error;
+use crate::graph::edge::{Hold, Proof};
+use crate::graph::vertex::{Identity, IdentityRecord};
+use crate::graph::{new_db_connection, Edge, Vertex};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
+use crate::util::{make_client, naive_now, parse_body, request_with_timeout};
+use aragog::DatabaseConnection;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
+use hyper::{Body, Method};
+use serde::Deserialize;
+use serde_json::Value;
+use std::str::FromStr;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// https://indexer.crossbell.io/docs
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Character {
+    pub character_id: i64,
+    pub handle: String,
+    pub owner: String,
+    pub primary: Option<bool>,
+    pub transaction_hash: Option<String>,
+    pub created_at: Option<String>,
+    pub metadata: Option<CharacterMetadata>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CharacterMetadata {
+    pub content: Option<CharacterContent>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CharacterContent {
+    pub name: Option<String>,
+    pub bio: Option<String>,
+    pub avatars: Option<Vec<String>>,
+    /// Items are either `"csb://account:{username}@{platform}"` strings,
+    /// or `{ "uri": "csb://account:..." }` objects in older characters.
+    pub connected_accounts: Option<Vec<Value>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CharactersResponse {
+    pub list: Vec<Character>,
+    pub count: i64,
+    pub cursor: Option<String>,
+}
+
+pub struct Crossbell {}
+
+#[async_trait]
+impl Fetcher for Crossbell {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target.platform()? {
+            Platform::Ethereum => fetch_by_addr(&target.identity()?).await,
+            Platform::Crossbell => fetch_by_handle(&target.identity()?).await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Ethereum, Platform::Crossbell])
+    }
+}
+
+async fn fetch_by_handle(handle: &str) -> Result<TargetProcessedList, Error> {
+    let uri = format!(
+        "{}/v1/handles/{}/character",
+        C.upstream.crossbell_api.url,
+        handle.trim_end_matches(".csb")
+    );
+    let character: Option<Character> = request(&uri).await?;
+    let character = match character {
+        Some(c) => c,
+        None => {
+            debug!("Crossbell handle {} | No result", handle);
+            return Ok(vec![]);
+        }
+    };
+
+    let db = new_db_connection().await?;
+    let mut next_targets = save_character(&db, &character).await?;
+    next_targets.push(Target::Identity(
+        Platform::Ethereum,
+        character.owner.to_lowercase(),
+    ));
+    Ok(next_targets)
+}
+
+async fn fetch_by_addr(address: &str) -> Result<TargetProcessedList, Error> {
+    let mut cursor: Option<String> = None;
+    let mut next_targets: TargetProcessedList = vec![];
+    let db = new_db_connection().await?;
+
+    loop {
+        let mut uri = format!(
+            "{}/v1/addresses/{}/characters?limit=20",
+            C.upstream.crossbell_api.url, address
+        );
+        if let Some(c) = cursor.as_ref() {
+            uri = format!("{}&cursor={}", uri, c);
+        }
+        let resp: CharactersResponse = request(&uri).await?;
+        for character in resp.list.iter() {
+            next_targets.extend(save_character(&db, character).await?);
+        }
+        match resp.cursor {
+            Some(c) if !c.is_empty() && !resp.list.is_empty() => cursor = Some(c),
+            _ => break,
+        }
+    }
+
+    Ok(next_targets)
+}
+
+async fn request<T>(uri: &str) -> Result<T, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let client = make_client();
+    let uri: http::Uri = uri
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("Crossbell Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("Crossbell fetch | error: {:?}", err.to_string()))
+    })?;
+
+    if !resp.status().is_success() {
+        warn!("Crossbell fetch error, status {}", resp.status());
+        return Err(Error::General(
+            format!("Crossbell Result Get Error: {}", resp.status()),
+            resp.status(),
+        ));
+    }
+
+    parse_body(&mut resp).await
+}
+
+/// Save character as `Identity(Crossbell, handle)`, held by its owner wallet
+/// and connected to every account listed in its metadata.
+/// Returns connected accounts for next round.
+async fn save_character(
+    db: &DatabaseConnection,
+    character: &Character,
+) -> Result<TargetProcessedList, Error> {
+    let created_at = character.created_at.as_ref().and_then(|c| parse_iso(c));
+    let content = character.metadata.as_ref().and_then(|m| m.content.clone());
+
+    let owner: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Ethereum,
+        identity: character.owner.to_lowercase(),
+        created_at: None,
+        display_name: None,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+
+    let csb: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Crossbell,
+        identity: character.handle.clone(),
+        created_at,
+        display_name: content
+            .as_ref()
+            .and_then(|c| c.name.clone())
+            .or_else(|| Some(character.handle.clone())),
+        added_at: naive_now(),
+        avatar_url: content
+            .as_ref()
+            .and_then(|c| c.avatars.as_ref())
+            .and_then(|a| a.first().cloned()),
+        profile_url: Some(format!("https://crossbell.io/@{}", character.handle)),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+
+    let hold: Hold = Hold {
+        uuid: Uuid::new_v4(),
+        source: DataSource::Crossbell,
+        transaction: character.transaction_hash.clone(),
+        id: character.character_id.to_string(),
+        created_at,
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
+    };
+
+    let owner_record = owner.create_or_update(db).await?;
+    let csb_record = csb.create_or_update(db).await?;
+    hold.connect(db, &owner_record, &csb_record).await?;
+
+    let mut next_targets: TargetProcessedList = vec![];
+    let accounts = content
+        .and_then(|c| c.connected_accounts)
+        .unwrap_or_default();
+    for account in accounts.iter() {
+        let uri = match account {
+            Value::String(s) => s.as_str(),
+            Value::Object(o) => match o.get("uri").and_then(|u| u.as_str()) {
+                Some(s) => s,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let (platform, username) = match parse_connected_account(uri) {
+            Some(r) => r,
+            None => {
+                debug!("Crossbell: skip unsupported connected account {}", uri);
+                continue;
+            }
+        };
+        connect_account(db, &csb_record, platform, &username, character).await?;
+        next_targets.push(Target::Identity(platform, username));
+    }
+
+    Ok(next_targets)
+}
+
+async fn connect_account(
+    db: &DatabaseConnection,
+    csb_record: &IdentityRecord,
+    platform: Platform,
+    username: &str,
+    character: &Character,
+) -> Result<(), Error> {
+    let to: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform,
+        identity: username.to_lowercase(),
+        created_at: None,
+        display_name: Some(username.to_string()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+    let to_record = to.create_or_update(db).await?;
+
+    let proof: Proof = Proof {
+        uuid: Uuid::new_v4(),
+        source: DataSource::Crossbell,
+        record_id: Some(character.character_id.to_string()),
+        created_at: None,
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        deleted_at: None,
+    };
+    proof.two_way_binding(db, csb_record, &to_record).await?;
+    Ok(())
+}
+
+/// Parse `csb://account:{username}@{platform}` into a supported `(Platform, username)`.
+fn parse_connected_account(uri: &str) -> Option<(Platform, String)> {
+    let account = uri.strip_prefix("csb://account:")?;
+    let (username, platform) = account.rsplit_once('@')?;
+    let platform = Platform::from_str(&platform.to_lowercase()).ok()?;
+    if username.is_empty() || platform == Platform::Unknown {
+        return None;
+    }
+    Some((platform, username.to_string()))
+}
+
+fn parse_iso(datetime: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(datetime)
+        .ok()
+        .map(|dt| dt.naive_utc())
+}
@@ -0,0 +1,196 @@
+#[cfg(test)]
+mod tests;
+
+use crate::config::C;
+use crate::error::Error;
+use crate::graph::edge::Proof;
+use crate::graph::vertex::Identity;
+use crate::graph::{create_identity_to_identity_record, new_db_connection};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
+use crate::util::{make_client, naive_now, parse_body, request_with_timeout};
+use async_trait::async_trait;
+use hyper::{Body, Method};
+use serde::Deserialize;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Resource record type of `TXT`.
+const TXT_RECORD_TYPE: u16 = 16;
+
+/// DNS-over-HTTPS JSON response.
+/// https://developers.cloudflare.com/1.1.1.1/encryption/dns-over-https/make-api-requests/dns-json/
+#[derive(Deserialize, Debug, Clone)]
+pub struct DohResponse {
+    /// `0` for `NOERROR`, `3` for `NXDOMAIN`, etc.
+    #[serde(rename = "Status")]
+    pub status: i32,
+    #[serde(rename = "Answer", default)]
+    pub answer: Vec<DohAnswer>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DohAnswer {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: u16,
+    pub data: String,
+}
+
+/// Turns TXT records of `Identity(DNS, domain)` into `Proof`s.
+///
+/// Supported TXT record formats (one claim per record):
+/// - `nextid=0x{persona}`: NextID persona, compressed secp256k1 public key in hex.
+/// - `a=0x{address}` (ENS DNSSEC style) or `eth=0x{address}`: Ethereum wallet.
+///
+/// Records are looked up through a DNS-over-HTTPS JSON resolver
+/// configured in `upstream.dns_resolver.url`.
+pub struct DNS {}
+
+#[async_trait]
+impl Fetcher for DNS {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(_, domain) => fetch_connections_by_domain(domain).await,
+            Target::NFT(_, _, _, _) => Ok(vec![]),
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::DNS])
+    }
+}
+
+async fn fetch_connections_by_domain(domain: &str) -> Result<TargetProcessedList, Error> {
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    let records = fetch_txt_records(&C.upstream.dns_resolver.url, &domain).await?;
+    let claims: Vec<(Platform, String, String)> = records
+        .into_iter()
+        .filter_map(|txt| parse_txt_record(&txt).map(|(platform, id)| (platform, id, txt)))
+        .collect();
+    if claims.is_empty() {
+        debug!("DNS {} | No identity found in TXT records", domain);
+        return Ok(vec![]);
+    }
+
+    let db = new_db_connection().await?;
+    let from: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::DNS,
+        identity: domain.clone(),
+        created_at: None,
+        display_name: Some(domain.clone()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: Some(format!("https://{}", domain)),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+
+    let mut next_targets: TargetProcessedList = vec![];
+    for (platform, identity, txt) in claims.into_iter() {
+        let to: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: identity.clone(),
+            created_at: None,
+            display_name: None,
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let proof: Proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::DNS,
+            record_id: Some(txt),
+            created_at: None,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            deleted_at: None,
+        };
+        create_identity_to_identity_record(&db, &from, &to, &proof).await?;
+        next_targets.push(Target::Identity(platform, identity));
+    }
+
+    Ok(next_targets)
+}
+
+/// Query all `TXT` records of `domain` from DNS-over-HTTPS `resolver`.
+async fn fetch_txt_records(resolver: &str, domain: &str) -> Result<Vec<String>, Error> {
+    let client = make_client();
+    let uri: http::Uri = format!("{}?name={}&type=TXT", resolver, domain)
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("accept", "application/dns-json")
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("DNS Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("DNS fetch | error: {:?}", err.to_string()))
+    })?;
+    if !resp.status().is_success() {
+        warn!("DNS fetch error, status {}", resp.status());
+        return Err(Error::General(
+            format!("DNS Result Get Error: {}", resp.status()),
+            resp.status(),
+        ));
+    }
+
+    let body: DohResponse = parse_body(&mut resp).await?;
+    if body.status != 0 {
+        debug!("DNS {} | resolver status {}", domain, body.status);
+        return Ok(vec![]);
+    }
+
+    Ok(body
+        .answer
+        .into_iter()
+        .filter(|a| a.record_type == TXT_RECORD_TYPE)
+        .map(|a| unquote_txt(&a.data))
+        .collect())
+}
+
+/// TXT data is given as one or more quoted character-strings,
+/// e.g. `"nextid=0x02" "abcd"`. Joins them into one.
+fn unquote_txt(data: &str) -> String {
+    if !data.starts_with('"') {
+        return data.to_string();
+    }
+    data.split('"')
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 1)
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// Parse one TXT record into `(Platform, identity)` following the formats
+/// described in [`DNS`].
+fn parse_txt_record(txt: &str) -> Option<(Platform, String)> {
+    let (key, value) = txt.trim().split_once('=')?;
+    let value = value.trim().to_lowercase();
+    match key.trim().to_lowercase().as_str() {
+        // Compressed (33 bytes) or uncompressed (65 bytes) public key.
+        "nextid" if is_hex_with_len(&value, 66) || is_hex_with_len(&value, 130) => {
+            Some((Platform::NextID, value))
+        }
+        "a" | "eth" if is_hex_with_len(&value, 40) => Some((Platform::Ethereum, value)),
+        _ => None,
+    }
+}
+
+/// `0x`-prefixed hex string with `len` hex digits.
+fn is_hex_with_len(value: &str, len: usize) -> bool {
+    value.strip_prefix("0x").map_or(false, |hex| {
+        hex.len() == len && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
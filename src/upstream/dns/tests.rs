@@ -0,0 +1,66 @@
+use crate::{
+    error::Error,
+    tests::spawn_stub_server,
+    upstream::{
+        dns::{fetch_txt_records, parse_txt_record},
+        Platform,
+    },
+};
+use serde_json::json;
+use warp::Filter;
+
+const PERSONA: &str = "0x02d7c5e01bedf1c993f40ec302d9bf162620daea93a7155cd9a8019ae3a2c2a476";
+const WALLET: &str = "0x934b510d4c9103e6a87aef13b816fb080286d649";
+
+#[test]
+fn test_parse_txt_record() {
+    assert_eq!(
+        parse_txt_record(&format!("nextid={}", PERSONA)),
+        Some((Platform::NextID, PERSONA.to_string()))
+    );
+    assert_eq!(
+        parse_txt_record(&format!("a={}", WALLET.to_uppercase().replace("0X", "0x"))),
+        Some((Platform::Ethereum, WALLET.to_string()))
+    );
+    assert_eq!(
+        parse_txt_record(&format!("eth={}", WALLET)),
+        Some((Platform::Ethereum, WALLET.to_string()))
+    );
+    assert_eq!(
+        parse_txt_record("v=spf1 include:_spf.google.com ~all"),
+        None
+    );
+    assert_eq!(parse_txt_record("a=0x1234"), None);
+}
+
+#[tokio::test]
+async fn test_fetch_txt_records() -> Result<(), Error> {
+    let resolver = warp::path("dns-query")
+        .and(warp::header::exact("accept", "application/dns-json"))
+        .map(|| {
+            warp::reply::json(&json!({
+                "Status": 0,
+                "Answer": [
+                    {"name": "example.com", "type": 16, "TTL": 300, "data": format!("\"nextid={}\"", PERSONA)},
+                    {"name": "example.com", "type": 16, "TTL": 300, "data": "\"a=0x934b510d4c9103e6\" \"a87aef13b816fb080286d649\""},
+                    {"name": "example.com", "type": 16, "TTL": 300, "data": "\"v=spf1 -all\""},
+                    {"name": "example.com", "type": 1, "TTL": 300, "data": "93.184.216.34"},
+                ]
+            }))
+        });
+    let base = spawn_stub_server(resolver);
+
+    let records = fetch_txt_records(&format!("{}/dns-query", base), "example.com").await?;
+    assert_eq!(
+        records,
+        vec![
+            format!("nextid={}", PERSONA),
+            format!("a={}", WALLET),
+            "v=spf1 -all".to_string(),
+        ]
+    );
+    let claims: Vec<_> = records.iter().filter_map(|r| parse_txt_record(r)).collect();
+    assert_eq!(claims.len(), 2);
+
+    Ok(())
+}
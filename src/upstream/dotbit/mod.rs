@@ -3,18 +3,26 @@ mod tests;
 use crate::config::C;
 use crate::error::Error;
 use crate::graph::create_domain_resolve_record;
+use crate::graph::create_domain_subdomain_record;
 use crate::graph::create_identity_to_identity_hold_record;
+use crate::graph::create_identity_to_identity_record;
 use crate::graph::edge::Edge;
-use crate::graph::edge::Resolve;
 use crate::graph::edge::{hold::Hold, resolve::DomainNameSystem};
-use crate::graph::vertex::Vertex;
+use crate::graph::edge::{Proof, Resolve, Subdomain};
+use crate::graph::vertex::{contract::Chain, Domain, Vertex};
 use crate::graph::{new_db_connection, vertex::Identity};
 use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
-use crate::util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive};
+use crate::util::{
+    ipfs_to_http, is_evm_address, make_client, naive_now, parse_body, request_with_timeout,
+    timestamp_to_naive,
+};
+use aragog::DatabaseConnection;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use hyper::{Body, Method, Request};
-use serde::{Deserialize, Serialize};
-use tracing::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, warn};
 use uuid::Uuid;
 
 pub struct DotBit {}
@@ -30,12 +38,18 @@ impl Fetcher for DotBit {
             Target::Identity(platform, identity) => {
                 fetch_connections_by_platform_identity(platform, identity).await
             }
-            Target::NFT(_, _, _, _) => todo!(),
+            // .bit accounts live on CKB, NFT targets are not supported yet.
+            Target::NFT(_, _, _, _) => Ok(vec![]),
         }
     }
 
     fn can_fetch(target: &Target) -> bool {
-        target.in_platform_supported(vec![Platform::Dotbit, Platform::Ethereum])
+        target.in_platform_supported(vec![
+            Platform::Dotbit,
+            Platform::Ethereum,
+            Platform::Tron,
+            Platform::Dogecoin,
+        ])
     }
 }
 
@@ -45,14 +59,6 @@ pub struct AccInfoRequestParams {
     pub account: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccInfoRequest {
-    pub jsonrpc: String,
-    pub id: i32,
-    pub method: String,
-    pub params: Vec<AccInfoRequestParams>,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OutPoint {
     pub tx_hash: String,
@@ -66,7 +72,16 @@ pub struct AccountInfo {
     pub account_id_hex: String,
     pub create_at_unix: i64,
     pub expired_at_unix: i64,
+    /// Signing algorithm of `owner_key`, see `algorithm_to_platform`.
+    #[serde(default)]
+    pub owner_algorithm_id: i32,
     pub owner_key: String,
+    /// Signing algorithm of `manager_key`, see `algorithm_to_platform`.
+    #[serde(default)]
+    pub manager_algorithm_id: i32,
+    /// Key allowed to edit records, set by the owner. Same as `owner_key` by default.
+    #[serde(default)]
+    pub manager_key: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -75,20 +90,6 @@ pub struct AccountInfoData {
     pub account_info: Option<AccountInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccountInfoResult {
-    pub errno: Option<i32>,
-    pub errmsg: String,
-    pub data: Option<AccountInfoData>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccountInfoResponse {
-    pub id: Option<i32>,
-    pub jsonrpc: String,
-    pub result: AccountInfoResult,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RequestKeyInfo {
     pub coin_type: String,
@@ -103,14 +104,6 @@ pub struct RequestTypeKeyInfoParams {
     pub key_info: RequestKeyInfo,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ReverseRecordRequest {
-    pub jsonrpc: String,
-    pub id: i32,
-    pub method: String,
-    pub params: Vec<RequestTypeKeyInfoParams>,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccountItem {
     pub account: String,
@@ -118,39 +111,217 @@ pub struct AccountItem {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ReverseResult {
-    pub errno: Option<i32>,
-    pub errmsg: String,
-    pub data: Option<AccountItem>,
+pub struct AccountListData {
+    pub account_list: Vec<AccountItem>,
+}
+
+/// A single record of `das_accountRecords`.
+/// `key` is `{type}.{label}`, e.g. `profile.twitter`, `address.60`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRecord {
+    pub key: String,
+    #[serde(default)]
+    pub label: String,
+    pub value: String,
+    #[serde(default)]
+    pub ttl: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ReverseResponse {
-    pub id: Option<i32>,
-    pub jsonrpc: String,
-    pub result: ReverseResult,
+pub struct AccountRecordsData {
+    pub account: String,
+    #[serde(default)]
+    pub records: Vec<AccountRecord>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct AccountListData {
-    pub account_list: Vec<AccountItem>,
+pub struct SubAccountItem {
+    pub account: String,
+    #[serde(default)]
+    pub owner_algorithm_id: i32,
+    pub owner_key: String,
+    #[serde(default)]
+    pub manager_algorithm_id: i32,
+    #[serde(default)]
+    pub manager_key: String,
+    #[serde(default)]
+    pub create_at_unix: i64,
+    #[serde(default)]
+    pub expired_at_unix: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubAccountListData {
+    pub total: i64,
+    #[serde(default)]
+    pub list: Vec<SubAccountItem>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct AccountListResult {
+pub struct DasResult<T> {
     pub errno: Option<i32>,
     pub errmsg: String,
-    pub data: Option<AccountListData>,
+    pub data: Option<T>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct AccountListResponse {
+pub struct DasResponse<T> {
     pub id: Option<i32>,
     pub jsonrpc: String,
-    pub result: AccountListResult,
+    pub result: DasResult<T>,
+}
+
+/// Profile records of a .bit account which we care about.
+#[derive(Debug, Default, PartialEq)]
+pub struct DotbitRecords {
+    pub avatar: Option<String>,
+    /// `(platform, username, record key)`
+    pub socials: Vec<(Platform, String, String)>,
+    /// `(platform, address)`
+    pub addresses: Vec<(Platform, String)>,
 }
 
 const UNKNOWN_OWNER: &str = "0x0000000000000000000000000000000000000000";
+const SUB_ACCOUNT_PAGE_SIZE: i64 = 100;
+const SUB_ACCOUNT_MAX_PAGES: i64 = 20;
+
+/// `owner_algorithm_id` / `manager_algorithm_id` of .bit:
+/// - `3`: ETH personal sign (also used by BSC and Polygon keys)
+/// - `4`: TRON
+/// - `5`: EIP-712
+/// - `6`: ed25519, unsupported
+/// - `7`: DOGE
+/// - `8`: WebAuthn (passkey), unsupported: its key is a credential of a device, not an address.
+pub fn algorithm_to_platform(algorithm_id: i32) -> Result<Platform, Error> {
+    match algorithm_id {
+        3 | 5 => Ok(Platform::Ethereum),
+        4 => Ok(Platform::Tron),
+        7 => Ok(Platform::Dogecoin),
+        6 => Err(Error::ParamError(
+            ".bit key algorithm 6 (ed25519) is not supported".to_string(),
+        )),
+        8 => Err(Error::ParamError(
+            ".bit key algorithm 8 (WebAuthn) is not supported".to_string(),
+        )),
+        _ => Err(Error::ParamError(format!(
+            "Unknown .bit key algorithm {}",
+            algorithm_id
+        ))),
+    }
+}
+
+/// Manager of an account, if it is not the owner itself.
+/// `None` for unsupported algorithms too.
+fn manager_of(
+    account: &str,
+    algorithm_id: i32,
+    key: &str,
+    owner_platform: &Platform,
+    owner_key: &str,
+) -> Option<(Platform, String)> {
+    if key.is_empty() || key == UNKNOWN_OWNER {
+        return None;
+    }
+    let platform = match algorithm_to_platform(algorithm_id) {
+        Ok(platform) => platform,
+        Err(err) => {
+            debug!(".bit {} | Manager skipped: {}", account, err);
+            return None;
+        }
+    };
+    let key = normalize_key(&platform, key);
+    if platform == *owner_platform && key == owner_key {
+        return None;
+    }
+    Some((platform, key))
+}
+
+/// Only EVM addresses are case-insensitive, Base58 ones must keep their case.
+fn normalize_key(platform: &Platform, key: &str) -> String {
+    match platform {
+        Platform::Ethereum => key.to_lowercase(),
+        _ => key.to_string(),
+    }
+}
+
+/// SLIP-44 coin type used by `key_info` of .bit API.
+fn coin_type(platform: &Platform) -> Option<&'static str> {
+    match platform {
+        Platform::Ethereum => Some("60"),
+        Platform::Tron => Some("195"),
+        Platform::Dogecoin => Some("3"),
+        _ => None,
+    }
+}
+
+/// Picks avatar, social accounts and addresses out of `das_accountRecords`.
+/// Address records of EVM chains (ETH / BSC / Polygon) all go to `Platform::Ethereum`.
+pub fn parse_records(records: &[AccountRecord]) -> DotbitRecords {
+    let mut result = DotbitRecords::default();
+    for record in records.iter() {
+        let value = record.value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let key = record.key.to_lowercase();
+        match key.as_str() {
+            "profile.avatar" => {
+                if result.avatar.is_none() {
+                    result.avatar = Some(ipfs_to_http(value));
+                }
+            }
+            "profile.twitter" | "profile.github" => {
+                let platform = if key == "profile.twitter" {
+                    Platform::Twitter
+                } else {
+                    Platform::Github
+                };
+                let username = value.trim_start_matches('@').to_lowercase();
+                if !result
+                    .socials
+                    .iter()
+                    .any(|(p, u, _)| *p == platform && *u == username)
+                {
+                    result.socials.push((platform, username, key.clone()));
+                }
+            }
+            "address.60" | "address.eth" | "address.9006" | "address.bsc" | "address.966"
+            | "address.matic" | "address.polygon" => {
+                if is_evm_address(value) {
+                    push_address(&mut result, Platform::Ethereum, value.to_lowercase());
+                }
+            }
+            "address.195" | "address.trx" | "address.tron" => {
+                push_address(&mut result, Platform::Tron, value.to_string());
+            }
+            "address.3" | "address.doge" => {
+                push_address(&mut result, Platform::Dogecoin, value.to_string());
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+fn push_address(records: &mut DotbitRecords, platform: Platform, address: String) {
+    if !records
+        .addresses
+        .iter()
+        .any(|(p, a)| *p == platform && *a == address)
+    {
+        records.addresses.push((platform, address));
+    }
+}
+
+/// `sub.abc.bit` => `abc.bit`. `None` if `account` is not a sub-account.
+pub fn parent_account(account: &str) -> Option<String> {
+    let (_, parent) = account.split_once('.')?;
+    if parent.contains('.') {
+        Some(parent.to_string())
+    } else {
+        None
+    }
+}
 
 async fn fetch_connections_by_platform_identity(
     platform: &Platform,
@@ -158,7 +329,9 @@ async fn fetch_connections_by_platform_identity(
 ) -> Result<TargetProcessedList, Error> {
     match *platform {
         Platform::Dotbit => fetch_connections_by_account_info(platform, identity).await,
-        Platform::Ethereum => fetch_hold_acc_and_reverse_record_by_addrs(platform, identity).await,
+        Platform::Ethereum | Platform::Tron | Platform::Dogecoin => {
+            fetch_hold_acc_and_reverse_record_by_addrs(platform, identity).await
+        }
         _ => Ok(vec![]),
     }
 }
@@ -167,39 +340,18 @@ async fn fetch_connections_by_account_info(
     _platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
-    let request_acc = AccInfoRequestParams {
-        account: identity.to_string(),
-    };
-    let params = AccInfoRequest {
-        jsonrpc: "2.0".to_string(),
-        id: 1,
-        method: "das_accountInfo".to_string(),
-        params: vec![request_acc],
-    };
-    let json_params = serde_json::to_vec(&params)?;
-
-    let client = make_client();
-    let req = Request::builder()
-        .method(Method::POST)
-        .uri(C.upstream.dotbit_service.url.clone())
-        .body(Body::from(json_params))
-        .map_err(|_err| Error::ParamError(format!("Dotbit Build Request Error {}", _err)))?;
-
-    let mut result = request_with_timeout(&client, req).await.map_err(|err| {
-        Error::ManualHttpClientError(format!(
-            "Dotbit fetch | das_accountInfo error: {:?}",
-            err.to_string()
-        ))
-    })?;
-
-    let resp: AccountInfoResponse = parse_body(&mut result).await?;
-    if resp.result.errno.unwrap() != 0 {
-        warn!("fail to fetch the result from .bit, resp {:?}", resp);
-        return Err(Error::NoResult);
-    }
-    let info = resp.result.data.unwrap();
-    let account_info = info.account_info.unwrap();
-    let out_point = info.out_point.unwrap();
+    let base = &C.upstream.dotbit_service.url;
+    let info: AccountInfoData = request(
+        base,
+        "das_accountInfo",
+        json!(AccInfoRequestParams {
+            account: identity.to_string(),
+        }),
+    )
+    .await?
+    .ok_or(Error::NoResult)?;
+    let account_info = info.account_info.ok_or(Error::NoResult)?;
+    let out_point = info.out_point.ok_or(Error::NoResult)?;
 
     // tricky way to remove the unexpected case...
     // will be removed after confirmied with .bit team how to define its a .bit NFT on Ethereum
@@ -208,21 +360,35 @@ async fn fetch_connections_by_account_info(
         warn!(".bit profile owner is zero address");
         return Err(Error::NoResult);
     }
+    let owner_platform = algorithm_to_platform(account_info.owner_algorithm_id).map_err(|err| {
+        warn!(".bit {} | Unsupported owner: {}", identity, err);
+        err
+    })?;
+    let records = match fetch_account_records(base, identity).await {
+        Ok(records) => parse_records(&records),
+        Err(err) => {
+            warn!(".bit {} | Fail to fetch records: {}", identity, err);
+            DotbitRecords::default()
+        }
+    };
 
     // add to db
     let db = new_db_connection().await?;
     let created_at_naive = timestamp_to_naive(account_info.create_at_unix, 0);
+    let owner_key = normalize_key(&owner_platform, &account_info.owner_key);
 
-    let eth_identity: Identity = Identity {
+    let owner_identity: Identity = Identity {
         uuid: Some(Uuid::new_v4()),
-        platform: Platform::Ethereum,
-        identity: account_info.owner_key.to_lowercase().clone(),
+        platform: owner_platform,
+        identity: owner_key.clone(),
         created_at: Some(created_at_naive),
         display_name: None,
         added_at: naive_now(),
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: evm_chains(&owner_platform),
+        is_contract: None,
     };
 
     let dotbit_identity: Identity = Identity {
@@ -232,9 +398,11 @@ async fn fetch_connections_by_account_info(
         created_at: Some(created_at_naive),
         display_name: Some(identity.to_string()),
         added_at: naive_now(),
-        avatar_url: None,
+        avatar_url: records.avatar.clone(),
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
 
     let hold: Hold = Hold {
@@ -245,6 +413,12 @@ async fn fetch_connections_by_account_info(
         created_at: Some(created_at_naive),
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: unix_to_naive(account_info.expired_at_unix),
+        amount: None,
+        decimals: None,
     };
 
     let resolve: Resolve = Resolve {
@@ -257,70 +431,295 @@ async fn fetch_connections_by_account_info(
     };
 
     // hold record
-    create_identity_to_identity_hold_record(&db, &eth_identity, &dotbit_identity, &hold).await?;
+    create_identity_to_identity_hold_record(&db, &owner_identity, &dotbit_identity, &hold).await?;
+
+    let mut next_targets: TargetProcessedList = vec![Target::Identity(owner_platform, owner_key)];
+
     // 'regular' resolution involves mapping from a name to an address.
-    create_domain_resolve_record(&db, &dotbit_identity, &eth_identity, &resolve).await?;
+    // Without any address record, .bit resolves to its owner.
+    if records.addresses.is_empty() {
+        create_domain_resolve_record(&db, &dotbit_identity, &owner_identity, &resolve).await?;
+    }
+    for (platform, address) in records.addresses.iter() {
+        let resolved = address_identity(platform, address);
+        create_domain_resolve_record(&db, &dotbit_identity, &resolved, &resolve).await?;
+        let target = Target::Identity(*platform, address.clone());
+        if !next_targets.contains(&target) {
+            next_targets.push(target);
+        }
+    }
+
+    if let Some((platform, key)) = manager_of(
+        identity,
+        account_info.manager_algorithm_id,
+        &account_info.manager_key,
+        &owner_platform,
+        &owner_key,
+    ) {
+        save_manager(&db, &dotbit_identity, &platform, &key).await?;
+        let target = Target::Identity(platform, key);
+        if !next_targets.contains(&target) {
+            next_targets.push(target);
+        }
+    }
 
-    return Ok(vec![Target::Identity(
-        Platform::Ethereum,
-        account_info.owner_key,
-    )]);
+    // Profile records are set by the owner, not verified by the social platform.
+    for (platform, username, key) in records.socials.into_iter() {
+        let social: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: username.clone(),
+            created_at: None,
+            display_name: Some(username.clone()),
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let proof: Proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Dotbit,
+            record_id: Some(key),
+            created_at: None,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            deleted_at: None,
+        };
+        create_identity_to_identity_record(&db, &dotbit_identity, &social, &proof).await?;
+        next_targets.push(Target::Identity(platform, username));
+    }
+
+    match parent_account(identity) {
+        // A sub-account is linked to its parent, and its own sub-accounts are not fetched.
+        Some(parent) => {
+            let parent_identity = dotbit_account_identity(&parent);
+            connect_subdomain(&db, &parent_identity, &dotbit_identity).await?;
+            next_targets.push(Target::Identity(Platform::Dotbit, parent));
+        }
+        None => {
+            let sub_owners = save_sub_accounts(&db, base, &dotbit_identity).await?;
+            next_targets.extend(sub_owners);
+        }
+    }
+
+    Ok(next_targets)
 }
 
-async fn fetch_hold_acc_and_reverse_record_by_addrs(
-    _platform: &Platform,
-    identity: &str,
+/// Saves every sub-account of `parent` with its owner,
+/// returns owners as next targets.
+async fn save_sub_accounts(
+    db: &DatabaseConnection,
+    base: &str,
+    parent: &Identity,
 ) -> Result<TargetProcessedList, Error> {
-    // account_list api result doesn't provide any proofs(tx, recordID...)
-    // remove the function first
-    // fetch_account_list_by_addrs(_platform, identity).await?;
+    let sub_accounts = match fetch_sub_accounts(base, &parent.identity, SUB_ACCOUNT_PAGE_SIZE).await
+    {
+        Ok(sub_accounts) => sub_accounts,
+        Err(err) => {
+            warn!(
+                ".bit {} | Fail to fetch sub-accounts: {}",
+                parent.identity, err
+            );
+            return Ok(vec![]);
+        }
+    };
 
-    // das_reverseRecord
-    let request_params = get_req_params_by_platform(_platform, identity);
-    let params = ReverseRecordRequest {
-        jsonrpc: "2.0".to_string(),
-        id: 1,
-        method: "das_reverseRecord".to_string(),
-        params: vec![request_params],
+    let mut next_targets: TargetProcessedList = vec![];
+    for sub in sub_accounts.into_iter() {
+        let owner_platform = match algorithm_to_platform(sub.owner_algorithm_id) {
+            Ok(platform) => platform,
+            Err(err) => {
+                debug!(".bit {} | Unsupported owner: {}", sub.account, err);
+                continue;
+            }
+        };
+        let owner_key = normalize_key(&owner_platform, &sub.owner_key);
+        let created_at = unix_to_naive(sub.create_at_unix);
+
+        let sub_identity = dotbit_account_identity(&sub.account);
+        let owner: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: owner_platform,
+            identity: owner_key.clone(),
+            created_at: None,
+            display_name: None,
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: evm_chains(&owner_platform),
+            is_contract: None,
+        };
+        let hold: Hold = Hold {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Dotbit,
+            transaction: None,
+            id: sub.account.clone(),
+            created_at,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            event_id: None,
+            event_name: None,
+            ended_at: None,
+            expired_at: unix_to_naive(sub.expired_at_unix),
+            amount: None,
+            decimals: None,
+        };
+        create_identity_to_identity_hold_record(db, &owner, &sub_identity, &hold).await?;
+        connect_subdomain(db, parent, &sub_identity).await?;
+
+        let mut targets = vec![Target::Identity(owner_platform, owner_key.clone())];
+        if let Some((platform, key)) = manager_of(
+            &sub.account,
+            sub.manager_algorithm_id,
+            &sub.manager_key,
+            &owner_platform,
+            &owner_key,
+        ) {
+            save_manager(db, &sub_identity, &platform, &key).await?;
+            targets.push(Target::Identity(platform, key));
+        }
+        for target in targets.into_iter() {
+            if !next_targets.contains(&target) {
+                next_targets.push(target);
+            }
+        }
+    }
+
+    Ok(next_targets)
+}
+
+/// Connects `account` with its manager.
+/// The manager is appointed by the owner to edit records, so like profile records,
+/// it is taken as the owner's own, though not verified.
+async fn save_manager(
+    db: &DatabaseConnection,
+    account: &Identity,
+    platform: &Platform,
+    key: &str,
+) -> Result<(), Error> {
+    let manager = address_identity(platform, key);
+    let proof: Proof = Proof {
+        uuid: Uuid::new_v4(),
+        source: DataSource::Dotbit,
+        record_id: Some("manager".to_string()),
+        created_at: None,
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        deleted_at: None,
     };
-    let json_params = serde_json::to_vec(&params)?;
+    create_identity_to_identity_record(db, account, &manager, &proof).await
+}
 
-    let client = make_client();
-    let req = Request::builder()
-        .method(Method::POST)
-        .uri(C.upstream.dotbit_service.url.clone())
-        .body(Body::from(json_params))
-        .map_err(|_err| Error::ParamError(format!("Dotbit Build Request Error {}", _err)))?;
+/// `0` means unknown in das APIs.
+fn unix_to_naive(timestamp: i64) -> Option<NaiveDateTime> {
+    if timestamp > 0 {
+        Some(timestamp_to_naive(timestamp, 0))
+    } else {
+        None
+    }
+}
 
-    let mut result = request_with_timeout(&client, req).await.map_err(|err| {
-        Error::ManualHttpClientError(format!(
-            "Dotbit fetch | das_reverseRecord error: {:?}",
-            err.to_string()
-        ))
-    })?;
+async fn connect_subdomain(
+    db: &DatabaseConnection,
+    parent: &Identity,
+    sub: &Identity,
+) -> Result<(), Error> {
+    let subdomain: Subdomain = Subdomain {
+        uuid: Uuid::new_v4(),
+        source: DataSource::Dotbit,
+        system: DomainNameSystem::DotBit,
+        name: sub.identity.clone(),
+        fetcher: DataFetcher::RelationService,
+        updated_at: naive_now(),
+    };
+    let parent_record = parent.create_or_update(db).await?;
+    let sub_record = sub.create_or_update(db).await?;
+    subdomain.connect(db, &parent_record, &sub_record).await?;
+    create_domain_subdomain_record(
+        db,
+        &Domain::new(DomainNameSystem::DotBit, &parent.identity),
+        &Domain::new(DomainNameSystem::DotBit, &sub.identity),
+        &subdomain,
+    )
+    .await
+}
 
-    let resp: ReverseResponse = parse_body(&mut result).await?;
-    if resp.result.errno.unwrap() != 0 {
-        warn!("fail to fetch the result from .bit, resp {:?}", resp);
-        return Err(Error::NoResult);
+fn dotbit_account_identity(account: &str) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Dotbit,
+        identity: account.to_string(),
+        created_at: None,
+        display_name: Some(account.to_string()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     }
-    if resp.result.data.is_none() || resp.result.data.as_ref().unwrap().account.len() == 0 {
-        return Err(Error::NoResult);
+}
+
+/// EVM keys of .bit are Ethereum mainnet keys.
+fn evm_chains(platform: &Platform) -> Vec<Chain> {
+    match platform {
+        Platform::Ethereum => vec![Chain::Ethereum],
+        _ => vec![],
     }
+}
 
-    let result_data = resp.result.data.unwrap();
-    let db = new_db_connection().await?;
-    let eth_identity: Identity = Identity {
+fn address_identity(platform: &Platform, address: &str) -> Identity {
+    Identity {
         uuid: Some(Uuid::new_v4()),
-        platform: Platform::Ethereum,
-        identity: identity.to_string().to_lowercase(),
+        platform: *platform,
+        identity: address.to_string(),
         created_at: None,
         display_name: None,
         added_at: naive_now(),
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: evm_chains(platform),
+        is_contract: None,
+    }
+}
+
+async fn fetch_hold_acc_and_reverse_record_by_addrs(
+    platform: &Platform,
+    identity: &str,
+) -> Result<TargetProcessedList, Error> {
+    let mut next_targets = match fetch_account_list_by_addrs(platform, identity).await {
+        Ok(targets) => targets,
+        Err(err) => {
+            warn!(".bit {} | Fail to fetch account list: {}", identity, err);
+            vec![]
+        }
+    };
+
+    // das_reverseRecord
+    let request_params = match get_req_params_by_platform(platform, identity) {
+        Some(params) => params,
+        None => return Ok(vec![]),
     };
+    let result_data: AccountItem = request(
+        &C.upstream.dotbit_service.url,
+        "das_reverseRecord",
+        json!(request_params),
+    )
+    .await?
+    .ok_or(Error::NoResult)?;
+    if result_data.account.is_empty() {
+        if next_targets.is_empty() {
+            return Err(Error::NoResult);
+        }
+        return Ok(next_targets);
+    }
+
+    let db = new_db_connection().await?;
+    let address_identity: Identity = address_identity(platform, &request_params.key_info.key);
 
     let dotbit_identity: Identity = Identity {
         uuid: Some(Uuid::new_v4()),
@@ -332,6 +731,8 @@ async fn fetch_hold_acc_and_reverse_record_by_addrs(
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
 
     let hold: Hold = Hold {
@@ -342,6 +743,12 @@ async fn fetch_hold_acc_and_reverse_record_by_addrs(
         created_at: None,
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
     };
 
     let resolve: Resolve = Resolve {
@@ -354,105 +761,143 @@ async fn fetch_hold_acc_and_reverse_record_by_addrs(
     };
 
     // hold record
-    create_identity_to_identity_hold_record(&db, &eth_identity, &dotbit_identity, &hold).await?;
+    create_identity_to_identity_hold_record(&db, &address_identity, &dotbit_identity, &hold)
+        .await?;
     // 'regular' resolution involves mapping from a name to an address.
-    create_domain_resolve_record(&db, &dotbit_identity, &eth_identity, &resolve).await?;
+    create_domain_resolve_record(&db, &dotbit_identity, &address_identity, &resolve).await?;
     // das_reverseRecord: 'reverse' resolution maps from an address back to a name.
-    create_domain_resolve_record(&db, &eth_identity, &dotbit_identity, &resolve).await?;
+    create_domain_resolve_record(&db, &address_identity, &dotbit_identity, &resolve).await?;
 
-    return Ok(vec![Target::Identity(
-        Platform::Dotbit,
-        result_data.account.clone(),
-    )]);
+    let target = Target::Identity(Platform::Dotbit, result_data.account.clone());
+    if !next_targets.contains(&target) {
+        next_targets.push(target);
+    }
+    Ok(next_targets)
 }
 
+/// Accounts owned by `identity`, as next targets.
+/// `das_accountList` doesn't give any proofs (tx, record ID...) of holding, so nothing is saved
+/// here: holds are saved with their proofs when the accounts are fetched by name.
 async fn fetch_account_list_by_addrs(
-    _platform: &Platform,
+    platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
     // das_accountList
-    let request_params = get_req_params_by_platform(_platform, identity);
-    let params = ReverseRecordRequest {
-        jsonrpc: "2.0".to_string(),
-        id: 1,
-        method: "das_accountList".to_string(),
-        params: vec![request_params],
+    let request_params = match get_req_params_by_platform(platform, identity) {
+        Some(params) => params,
+        None => return Ok(vec![]),
     };
-    let json_params = serde_json::to_vec(&params)?;
+    let data: AccountListData = request(
+        &C.upstream.dotbit_service.url,
+        "das_accountList",
+        json!(request_params),
+    )
+    .await?
+    .ok_or(Error::NoResult)?;
+
+    Ok(data
+        .account_list
+        .into_iter()
+        .filter(|item| !item.account.is_empty())
+        .map(|item| Target::Identity(Platform::Dotbit, item.account))
+        .collect())
+}
+
+/// `das_accountRecords`
+async fn fetch_account_records(base: &str, account: &str) -> Result<Vec<AccountRecord>, Error> {
+    let data: Option<AccountRecordsData> = request(
+        base,
+        "das_accountRecords",
+        json!(AccInfoRequestParams {
+            account: account.to_string(),
+        }),
+    )
+    .await?;
+    Ok(data.map(|d| d.records).unwrap_or_default())
+}
+
+/// `das_subAccountList`, paginated.
+async fn fetch_sub_accounts(
+    base: &str,
+    account: &str,
+    page_size: i64,
+) -> Result<Vec<SubAccountItem>, Error> {
+    let mut sub_accounts: Vec<SubAccountItem> = vec![];
+    for page in 1..=SUB_ACCOUNT_MAX_PAGES {
+        let data: SubAccountListData = match request(
+            base,
+            "das_subAccountList",
+            json!({"account": account, "page": page, "size": page_size}),
+        )
+        .await?
+        {
+            Some(data) => data,
+            None => break,
+        };
+        let count = data.list.len() as i64;
+        sub_accounts.extend(data.list);
+        if count < page_size || sub_accounts.len() as i64 >= data.total {
+            break;
+        }
+    }
+    Ok(sub_accounts)
+}
+
+/// Calls a JSON-RPC method of .bit indexer with a single `params` object.
+/// Returns `result.data`.
+async fn request<T>(base: &str, method: &str, params: serde_json::Value) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": [params],
+    });
+    let json_params = serde_json::to_vec(&body)?;
 
     let client = make_client();
     let req = Request::builder()
         .method(Method::POST)
-        .uri(C.upstream.dotbit_service.url.clone())
+        .uri(base)
+        .header("content-type", "application/json")
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("Dotbit Build Request Error {}", _err)))?;
 
     let mut result = request_with_timeout(&client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
-            "Dotbit fetch | das_accountList error: {:?}",
+            "Dotbit fetch | {} error: {:?}",
+            method,
             err.to_string()
         ))
     })?;
 
-    let resp: AccountListResponse = parse_body(&mut result).await?;
-    if resp.result.errno.unwrap() != 0 || resp.result.data.is_none() {
-        warn!("fail to fetch the result from .bit, resp {:?}", resp);
+    let resp: DasResponse<T> = parse_body(&mut result).await?;
+    if resp.result.errno.unwrap_or_default() != 0 {
+        warn!(
+            "fail to fetch the result from .bit, {} errno {:?}: {}",
+            method, resp.result.errno, resp.result.errmsg
+        );
         return Err(Error::NoResult);
     }
-
-    let db = new_db_connection().await?;
-    let from: Identity = Identity {
-        uuid: Some(Uuid::new_v4()),
-        platform: Platform::Ethereum,
-        identity: identity.to_string().to_lowercase().clone(),
-        created_at: None,
-        display_name: None,
-        added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
-        updated_at: naive_now(),
-    };
-    let from_record = from.create_or_update(&db).await?;
-
-    for i in resp.result.data.unwrap().account_list.into_iter() {
-        let to: Identity = Identity {
-            uuid: Some(Uuid::new_v4()),
-            platform: Platform::Dotbit,
-            identity: i.account.to_string(),
-            created_at: None,
-            display_name: Some(i.account.to_string()),
-            added_at: naive_now(),
-            avatar_url: None,
-            profile_url: None,
-            updated_at: naive_now(),
-        };
-
-        let hold: Hold = Hold {
-            uuid: Uuid::new_v4(),
-            source: DataSource::Dotbit,
-            transaction: None,
-            id: "".to_string(),
-            created_at: None,
-            updated_at: naive_now(),
-            fetcher: DataFetcher::RelationService,
-        };
-
-        let to_record = to.create_or_update(&db).await?;
-        hold.connect(&db, &from_record, &to_record).await?;
-    }
-
-    Ok(vec![])
+    Ok(resp.result.data)
 }
 
-fn get_req_params_by_platform(_platform: &Platform, identity: &str) -> RequestTypeKeyInfoParams {
-    // will support other platform later
+fn get_req_params_by_platform(
+    platform: &Platform,
+    identity: &str,
+) -> Option<RequestTypeKeyInfoParams> {
     let req_key_info: RequestKeyInfo = RequestKeyInfo {
-        coin_type: "60".to_string(),
-        chain_id: "1".to_string(),
-        key: identity.to_string().to_lowercase(),
+        coin_type: coin_type(platform)?.to_string(),
+        chain_id: match platform {
+            Platform::Ethereum => "1".to_string(),
+            _ => "".to_string(),
+        },
+        key: normalize_key(platform, identity),
     };
-    return RequestTypeKeyInfoParams {
+    Some(RequestTypeKeyInfoParams {
         req_type: "blockchain".to_string(),
         key_info: req_key_info,
-    };
+    })
 }
@@ -1,9 +1,15 @@
 use crate::graph::edge::Hold;
+use crate::tests::spawn_rpc_server;
+use crate::upstream::dotbit::{
+    algorithm_to_platform, fetch_account_records, fetch_sub_accounts, manager_of, parent_account,
+    parse_records, AccountRecord,
+};
 use crate::upstream::Target;
 use crate::{error::Error, upstream::dotbit::DotBit, upstream::Fetcher};
 use crate::{
     graph::new_db_connection, graph::vertex::Identity, upstream::Platform, util::naive_now,
 };
+use serde_json::json;
 
 #[tokio::test]
 async fn test_smoke_dotbit_by_dotbit_identity() -> Result<(), Error> {
@@ -25,11 +31,13 @@ async fn test_smoke_dotbit_by_dotbit_identity() -> Result<(), Error> {
 async fn test_smoke_dotbit_reverse_record() -> Result<(), Error> {
     //0x9176acd39a3a9ae99dcb3922757f8af4f94cdf3c holds justing.bit, resolve => "justing.bit"
     //0x4271B15dCa69f8C1c942c64028dBd3B84c5D03B0 holds test0920.bit, resolve => ""
+    // Accounts it holds are still listed.
     let target = Target::Identity(
         Platform::Ethereum,
         "0x4271B15dCa69f8C1c942c64028dBd3B84c5D03B0".into(),
     );
-    assert_eq!(DotBit::fetch(&target).await.is_err(), true);
+    let next_targets = DotBit::fetch(&target).await?;
+    assert!(next_targets.contains(&Target::Identity(Platform::Dotbit, "test0920.bit".into())));
 
     let target2 = Target::Identity(
         Platform::Ethereum,
@@ -58,3 +66,163 @@ async fn test_smoke_dotbit_reverse_record() -> Result<(), Error> {
 
     Ok(())
 }
+
+fn record(key: &str, value: &str) -> AccountRecord {
+    AccountRecord {
+        key: key.to_string(),
+        label: "".to_string(),
+        value: value.to_string(),
+        ttl: "300".to_string(),
+    }
+}
+
+#[test]
+fn test_algorithm_to_platform() {
+    assert_eq!(algorithm_to_platform(3).unwrap(), Platform::Ethereum);
+    assert_eq!(algorithm_to_platform(5).unwrap(), Platform::Ethereum);
+    assert_eq!(algorithm_to_platform(4).unwrap(), Platform::Tron);
+    assert_eq!(algorithm_to_platform(7).unwrap(), Platform::Dogecoin);
+    assert!(algorithm_to_platform(6).is_err());
+    assert!(algorithm_to_platform(8).is_err());
+    assert!(algorithm_to_platform(99).is_err());
+}
+
+#[test]
+fn test_manager_of() {
+    let owner = "0x4271b15dca69f8c1c942c64028dbd3b84c5d03b0";
+    // Same as owner
+    assert_eq!(
+        manager_of(
+            "test0920.bit",
+            5,
+            "0x4271B15dCa69f8C1c942c64028dBd3B84c5D03B0",
+            &Platform::Ethereum,
+            owner
+        ),
+        None
+    );
+    assert_eq!(
+        manager_of(
+            "test0920.bit",
+            3,
+            "0x9176ACD39A3A9AE99DCB3922757F8AF4F94CDF3C",
+            &Platform::Ethereum,
+            owner
+        ),
+        Some((
+            Platform::Ethereum,
+            "0x9176acd39a3a9ae99dcb3922757f8af4f94cdf3c".to_string()
+        ))
+    );
+    // WebAuthn
+    assert_eq!(
+        manager_of("test0920.bit", 8, "0x01", &Platform::Ethereum, owner),
+        None
+    );
+    assert_eq!(
+        manager_of("test0920.bit", 3, "", &Platform::Ethereum, owner),
+        None
+    );
+}
+
+#[test]
+fn test_parent_account() {
+    assert_eq!(parent_account("abc.bit"), None);
+    assert_eq!(parent_account("sub.abc.bit"), Some("abc.bit".to_string()));
+}
+
+#[test]
+fn test_parse_records() {
+    let records = parse_records(&[
+        record("profile.avatar", "ipfs://QmAvatar"),
+        record("profile.twitter", "@Jeffx"),
+        record("profile.github", "jeffx"),
+        record("profile.twitter", "jeffx"),
+        record("address.60", "0x9176ACD39A3A9AE99DCB3922757F8AF4F94CDF3C"),
+        record("address.9006", "0x9176acd39a3a9ae99dcb3922757f8af4f94cdf3c"),
+        record("address.195", "TQoLh9evwUmZKxpD1uhFttsZk3EBs8BksV"),
+        record("address.60", "not-an-address"),
+        record("profile.website", "https://example.com"),
+    ]);
+
+    assert_eq!(
+        records.avatar,
+        Some("https://ipfs.io/ipfs/QmAvatar".to_string())
+    );
+    assert_eq!(
+        records.socials,
+        vec![
+            (
+                Platform::Twitter,
+                "jeffx".to_string(),
+                "profile.twitter".to_string()
+            ),
+            (
+                Platform::Github,
+                "jeffx".to_string(),
+                "profile.github".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        records.addresses,
+        vec![
+            (
+                Platform::Ethereum,
+                "0x9176acd39a3a9ae99dcb3922757f8af4f94cdf3c".to_string()
+            ),
+            (
+                Platform::Tron,
+                "TQoLh9evwUmZKxpD1uhFttsZk3EBs8BksV".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_records_and_sub_accounts() -> Result<(), Error> {
+    let base = spawn_rpc_server(|method, params| {
+        let params = &params[0];
+        let data = match method {
+            "das_accountRecords" => json!({
+                "account": params["account"],
+                "records": [
+                    { "key": "profile.twitter", "label": "", "value": "jeffx", "ttl": "300" }
+                ]
+            }),
+            "das_subAccountList" => {
+                // 3 sub-accounts in total, 2 per page.
+                let page = params["page"].as_i64().unwrap();
+                let list: Vec<serde_json::Value> = (1..=3)
+                    .skip(((page - 1) * 2) as usize)
+                    .take(2)
+                    .map(|i| {
+                        json!({
+                            "account": format!("{}.test0920.bit", i),
+                            "owner_algorithm_id": 3,
+                            "owner_key": "0x4271B15dCa69f8C1c942c64028dBd3B84c5D03B0",
+                        })
+                    })
+                    .collect();
+                json!({ "total": 3, "list": list })
+            }
+            _ => serde_json::Value::Null,
+        };
+        Ok(json!({ "errno": 0, "errmsg": "", "data": data }))
+    });
+
+    let records = fetch_account_records(&base, "test0920.bit").await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].key, "profile.twitter");
+
+    let sub_accounts = fetch_sub_accounts(&base, "test0920.bit", 2).await?;
+    assert_eq!(
+        sub_accounts
+            .iter()
+            .map(|s| s.account.as_str())
+            .collect::<Vec<_>>(),
+        vec!["1.test0920.bit", "2.test0920.bit", "3.test0920.bit"]
+    );
+
+    Ok(())
+}
@@ -4,7 +4,13 @@ mod tests;
 use crate::{
     config::C,
     error::Error,
-    graph::{new_db_connection, vertex::Identity, Vertex},
+    graph::{
+        create_domain_reverse_of_record,
+        edge::{resolve::DomainNameSystem, ReverseOf},
+        new_db_connection,
+        vertex::{Domain, Identity},
+        Vertex,
+    },
     util::{make_client, parse_body, request_with_timeout},
 };
 use async_trait::async_trait;
@@ -12,7 +18,7 @@ use hyper::{Body, Method};
 use serde::Deserialize;
 use tracing::info;
 
-use super::{Fetcher, Platform, Target, TargetProcessedList};
+use super::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
 
 #[derive(Deserialize, Debug, Clone)]
 struct Response {
@@ -43,9 +49,21 @@ impl Fetcher for ENSReverseLookup {
         let mut identity = Identity::default();
         identity.platform = Platform::Ethereum;
         identity.identity = wallet.clone();
-        identity.display_name = Some(reverse_ens);
+        identity.display_name = Some(reverse_ens.clone());
         let db = new_db_connection().await?;
-        identity.create_or_update(&db).await?;
+        let identity_record = identity.create_or_update(&db).await?;
+        if reverse_ens.is_empty() {
+            ReverseOf::clear(&db, &identity_record, &DomainNameSystem::ENS).await?;
+        } else {
+            let reverse_of = ReverseOf {
+                source: DataSource::RPCServer,
+                system: DomainNameSystem::ENS,
+                fetcher: DataFetcher::RelationService,
+                ..Default::default()
+            };
+            let domain = Domain::new(DomainNameSystem::ENS, &reverse_ens);
+            create_domain_reverse_of_record(&db, &domain, &identity_record, &reverse_of).await?;
+        }
 
         Ok(vec![])
     }
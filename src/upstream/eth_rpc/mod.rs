@@ -0,0 +1,392 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error::Error;
+use crate::util::{make_client, parse_body, request_with_timeout};
+use hyper::{Body, Method, Request};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tracing::warn;
+
+/// `name()` of ERC-20 / ERC-721
+pub const SELECTOR_NAME: &str = "0x06fdde03";
+/// `totalSupply()` of ERC-20 / ERC-721 (Enumerable)
+pub const SELECTOR_TOTAL_SUPPLY: &str = "0x18160ddd";
+/// `balanceOf(address,uint256)` of ERC-1155
+pub const SELECTOR_BALANCE_OF_1155: &str = "0x00fdd58e";
+
+#[derive(Deserialize, Debug)]
+pub struct RpcResponse<T> {
+    pub result: Option<T>,
+    pub error: Option<RpcError>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_getlogs
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: String,
+    pub transaction_hash: String,
+    pub log_index: String,
+    /// `true` if this log is removed due to a chain reorganization.
+    #[serde(default)]
+    pub removed: bool,
+}
+
+/// Only fields we care about from `eth_getBlockByNumber`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Block {
+    pub hash: String,
+    pub timestamp: String,
+}
+
+/// Only fields we care about of a transaction in `eth_getBlockByNumber`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Transaction {
+    pub hash: String,
+    pub from: String,
+    /// `None` for contract creation.
+    pub to: Option<String>,
+}
+
+/// Only fields we care about from `eth_getTransactionReceipt`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    /// Address of the contract created by this transaction, if any.
+    pub contract_address: Option<String>,
+}
+
+/// Block in `eth_getBlockByNumber`, with transactions.
+#[derive(Deserialize, Debug, Clone)]
+struct BlockWithTransactions {
+    transactions: Vec<Transaction>,
+}
+
+impl Log {
+    pub fn block_number(&self) -> Option<u64> {
+        parse_hex_u64(&self.block_number)
+    }
+
+    pub fn log_index(&self) -> Option<u64> {
+        parse_hex_u64(&self.log_index)
+    }
+}
+
+impl Block {
+    pub fn timestamp(&self) -> Option<i64> {
+        parse_hex_u64(&self.timestamp).map(|t| t as i64)
+    }
+}
+
+/// Calls `method` on JSON-RPC node at `url`.
+/// `None` if node responds `"result": null` (e.g. block not found).
+pub async fn call<T>(url: &str, method: &str, params: Value) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+    let client = make_client();
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body)?))
+        .map_err(|_err| Error::ParamError(format!("JSON-RPC Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "JSON-RPC fetch | {} error: {:?}",
+            method,
+            err.to_string()
+        ))
+    })?;
+    if !resp.status().is_success() {
+        return Err(Error::General(
+            format!("JSON-RPC {} error, status {}", method, resp.status()),
+            resp.status(),
+        ));
+    }
+
+    let body: RpcResponse<T> = parse_body(&mut resp).await?;
+    if let Some(err) = body.error {
+        warn!("JSON-RPC {} error {}: {}", method, err.code, err.message);
+        return Err(Error::General(
+            format!("JSON-RPC {} error {}: {}", method, err.code, err.message),
+            resp.status(),
+        ));
+    }
+    Ok(body.result)
+}
+
+/// `eth_blockNumber`
+pub async fn block_number(url: &str) -> Result<u64, Error> {
+    let result: String = call(url, "eth_blockNumber", json!([]))
+        .await?
+        .ok_or(Error::NoResult)?;
+    parse_hex_u64(&result)
+        .ok_or_else(|| Error::ParamError(format!("Invalid block number {}", result)))
+}
+
+/// `eth_getBlockByNumber`, without transactions.
+pub async fn get_block(url: &str, number: u64) -> Result<Option<Block>, Error> {
+    call(url, "eth_getBlockByNumber", json!([to_hex(number), false])).await
+}
+
+/// Transactions of block `number`, from `eth_getBlockByNumber`.
+pub async fn get_block_transactions(url: &str, number: u64) -> Result<Vec<Transaction>, Error> {
+    let block: Option<BlockWithTransactions> =
+        call(url, "eth_getBlockByNumber", json!([to_hex(number), true])).await?;
+    Ok(block.map(|block| block.transactions).unwrap_or_default())
+}
+
+/// `eth_getTransactionReceipt`
+pub async fn get_transaction_receipt(url: &str, hash: &str) -> Result<Option<Receipt>, Error> {
+    call(url, "eth_getTransactionReceipt", json!([hash])).await
+}
+
+/// `eth_getLogs` of `address` in `[from_block, to_block]`, whose first topic is one of `topics`.
+pub async fn get_logs(
+    url: &str,
+    address: &str,
+    topics: &[&str],
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<Log>, Error> {
+    let filter = json!({
+        "address": address,
+        "fromBlock": to_hex(from_block),
+        "toBlock": to_hex(to_block),
+        "topics": [topics],
+    });
+    Ok(call(url, "eth_getLogs", json!([filter]))
+        .await?
+        .unwrap_or_default())
+}
+
+/// `eth_call` at `block` (`latest` if `None`). Returns hex encoded result.
+pub async fn eth_call(
+    url: &str,
+    to: &str,
+    data: &str,
+    block: Option<u64>,
+) -> Result<String, Error> {
+    let block = block.map(to_hex).unwrap_or_else(|| "latest".to_string());
+    Ok(
+        call(url, "eth_call", json!([{"to": to, "data": data}, block]))
+            .await?
+            .unwrap_or_default(),
+    )
+}
+
+/// `eth_getCode` of `address` at latest block. `"0x"` for EOAs.
+pub async fn get_code(url: &str, address: &str) -> Result<String, Error> {
+    get_code_at(url, address, None).await
+}
+
+/// `eth_getCode` of `address` at `block` (`latest` if `None`).
+pub async fn get_code_at(url: &str, address: &str, block: Option<u64>) -> Result<String, Error> {
+    let block = block.map(to_hex).unwrap_or_else(|| "latest".to_string());
+    Ok(call(url, "eth_getCode", json!([address, block]))
+        .await?
+        .unwrap_or_default())
+}
+
+/// Sender of the transaction which created the contract at `address`.
+/// Finds the block where its code shows up (which needs an archive node), then the creation
+/// transaction in it. `None` if it is not a contract, or it is created by another contract
+/// (e.g. a factory), whose creation is an internal transaction.
+pub async fn deployer_of(url: &str, address: &str) -> Result<Option<String>, Error> {
+    let has_code = |code: String| !code.trim_start_matches("0x").is_empty();
+    if !has_code(get_code(url, address).await?) {
+        return Ok(None);
+    }
+
+    // Code is there at `high`, and not yet at `low`.
+    let mut high = block_number(url).await?;
+    if has_code(get_code_at(url, address, Some(0)).await?) {
+        return Ok(None);
+    }
+    let mut low = 0;
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if has_code(get_code_at(url, address, Some(middle)).await?) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    let address = address.to_lowercase();
+    for tx in get_block_transactions(url, high).await? {
+        if tx.to.is_some() {
+            continue;
+        }
+        let created = get_transaction_receipt(url, &tx.hash)
+            .await?
+            .and_then(|receipt| receipt.contract_address);
+        if created.map_or(false, |created| created.to_lowercase() == address) {
+            return Ok(Some(tx.from.to_lowercase()));
+        }
+    }
+    Ok(None)
+}
+
+/// `name()` and `totalSupply()` of the contract at `address`, if it implements them.
+pub async fn contract_metadata(url: &str, address: &str) -> (Option<String>, Option<String>) {
+    let name = eth_call(url, address, SELECTOR_NAME, None)
+        .await
+        .ok()
+        .and_then(|data| decode_string(&data))
+        .filter(|name| !name.is_empty());
+    let total_supply = eth_call(url, address, SELECTOR_TOTAL_SUPPLY, None)
+        .await
+        .ok()
+        .filter(|data| !data.trim_start_matches("0x").is_empty())
+        .and_then(|data| hex_to_decimal(&data));
+    (name, total_supply)
+}
+
+/// ERC-1155 balance of token `id` (in decimal) held by `owner`, in decimal.
+/// At the latest block if `block` is `None`.
+pub async fn balance_of_1155(
+    url: &str,
+    address: &str,
+    owner: &str,
+    id: &str,
+    block: Option<u64>,
+) -> Result<Option<String>, Error> {
+    let id = match decimal_to_word(id) {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let data = format!(
+        "{}{}{}",
+        SELECTOR_BALANCE_OF_1155,
+        address_to_word(owner),
+        id
+    );
+    let balance = eth_call(url, address, &data, block).await?;
+    Ok(hex_to_decimal(&balance))
+}
+
+/// `1234` => `"0x4d2"`
+pub fn to_hex(number: u64) -> String {
+    format!("0x{:x}", number)
+}
+
+/// `"0x4d2"` => `1234`
+pub fn parse_hex_u64(hex: &str) -> Option<u64> {
+    let hex = hex.trim_start_matches("0x");
+    if hex.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(hex, 16).ok()
+}
+
+/// Hex encoded uint256 => decimal string, e.g. token ID.
+pub fn hex_to_decimal(hex: &str) -> Option<String> {
+    // Decimal digits, least significant first.
+    let mut digits: Vec<u32> = vec![0];
+    for c in hex.trim_start_matches("0x").chars() {
+        let mut carry = c.to_digit(16)?;
+        for digit in digits.iter_mut() {
+            let value = *digit * 16 + carry;
+            *digit = value % 10;
+            carry = value / 10;
+        }
+        while carry > 0 {
+            digits.push(carry % 10);
+            carry /= 10;
+        }
+    }
+    Some(
+        digits
+            .iter()
+            .rev()
+            .map(|d| char::from_digit(*d, 10).unwrap())
+            .collect(),
+    )
+}
+
+/// Decimal string => ABI encoded uint256 word (without `0x`), e.g. token ID.
+/// `None` if it is not a number, or overflows uint256.
+pub fn decimal_to_word(decimal: &str) -> Option<String> {
+    if decimal.is_empty() {
+        return None;
+    }
+    // Hex digits, least significant first.
+    let mut digits: Vec<u32> = vec![0];
+    for c in decimal.chars() {
+        let mut carry = c.to_digit(10)?;
+        for digit in digits.iter_mut() {
+            let value = *digit * 10 + carry;
+            *digit = value % 16;
+            carry = value / 16;
+        }
+        while carry > 0 {
+            digits.push(carry % 16);
+            carry /= 16;
+        }
+    }
+    if digits.len() > 64 {
+        return None;
+    }
+    let hex: String = digits
+        .iter()
+        .rev()
+        .map(|d| char::from_digit(*d, 16).unwrap())
+        .collect();
+    Some(format!("{:0>64}", hex))
+}
+
+/// ABI encoded `string` (i.e. what `tokenURI()` returns) => the string.
+/// `None` if `data` is not a valid UTF-8 string, or its offset / length is out of range.
+pub fn decode_string(data: &str) -> Option<String> {
+    let data = data.trim_start_matches("0x");
+    let words = data_words(data);
+    let offset = usize::try_from(parse_hex_u64(words.first()?)?).ok()? / 32;
+    let len = usize::try_from(parse_hex_u64(words.get(offset)?)?).ok()?;
+    let start = offset.checked_add(1)?.checked_mul(64)?;
+    let end = len.checked_mul(2)?.checked_add(start)?;
+    let hex = data.get(start..end)?;
+    let bytes = (0..len)
+        .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// ABI encoded `address` (i.e. an indexed topic, or a 32-byte word) => `0x` + 40 hex chars.
+pub fn word_to_address(word: &str) -> Option<String> {
+    let word = word.trim_start_matches("0x");
+    if word.len() < 40 {
+        return None;
+    }
+    Some(format!("0x{}", &word[word.len() - 40..]).to_lowercase())
+}
+
+/// Split ABI encoded `data` into 32-byte words (64 hex chars each).
+pub fn data_words(data: &str) -> Vec<&str> {
+    let data = data.trim_start_matches("0x");
+    (0..data.len() / 64)
+        .map(|i| &data[i * 64..(i + 1) * 64])
+        .collect()
+}
+
+/// ABI encodes an address as a 32-byte word (without `0x`).
+pub fn address_to_word(address: &str) -> String {
+    format!("{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
@@ -0,0 +1,158 @@
+use crate::{
+    error::Error,
+    tests::spawn_rpc_server,
+    upstream::eth_rpc::{
+        address_to_word, balance_of_1155, block_number, data_words, decimal_to_word, decode_string,
+        deployer_of, get_block, hex_to_decimal, parse_hex_u64, to_hex, word_to_address,
+    },
+};
+use serde_json::{json, Value};
+
+#[test]
+fn test_hex() {
+    assert_eq!(to_hex(1234), "0x4d2");
+    assert_eq!(parse_hex_u64("0x4d2"), Some(1234));
+    assert_eq!(parse_hex_u64("0x"), Some(0));
+    assert_eq!(parse_hex_u64("0xzz"), None);
+    assert_eq!(hex_to_decimal("0x0").unwrap(), "0");
+    assert_eq!(
+        hex_to_decimal("0x00000000000000000000000000000000000000000000000000000000000000ff")
+            .unwrap(),
+        "255"
+    );
+    // 2^128
+    assert_eq!(
+        hex_to_decimal("0x100000000000000000000000000000000").unwrap(),
+        "340282366920938463463374607431768211456"
+    );
+}
+
+#[test]
+fn test_abi_words() {
+    let word = "0x000000000000000000000000934B510D4C9103E6A87AEF13B816FB080286D649";
+    assert_eq!(
+        word_to_address(word).unwrap(),
+        "0x934b510d4c9103e6a87aef13b816fb080286d649"
+    );
+    assert_eq!(
+        address_to_word("0x934b510d4c9103e6a87aef13b816fb080286d649"),
+        word.trim_start_matches("0x").to_lowercase()
+    );
+    let data = format!("0x{}{}", "0".repeat(63) + "1", "0".repeat(63) + "2");
+    assert_eq!(data_words(&data).len(), 2);
+
+    assert_eq!(
+        decimal_to_word("255").unwrap(),
+        format!("{}ff", "0".repeat(62))
+    );
+    assert_eq!(
+        hex_to_decimal(&decimal_to_word("340282366920938463463374607431768211456").unwrap())
+            .unwrap(),
+        "340282366920938463463374607431768211456"
+    );
+    assert_eq!(decimal_to_word("0x1"), None);
+    assert_eq!(decimal_to_word("abc.eth"), None);
+
+    // "ipfs://abc"
+    let data = format!(
+        "0x{}{}{}",
+        "0".repeat(62) + "20",
+        "0".repeat(63) + "a",
+        "697066733a2f2f616263".to_string() + &"0".repeat(44)
+    );
+    assert_eq!(decode_string(&data).unwrap(), "ipfs://abc");
+    assert_eq!(decode_string("0x"), None);
+    // Length of u64::MAX overflows.
+    let data = format!("0x{:0>64}{:0>64x}", "20", u64::MAX);
+    assert_eq!(decode_string(&data), None);
+}
+
+#[tokio::test]
+async fn test_call() -> Result<(), Error> {
+    let url = spawn_rpc_server(|method, params| match method {
+        "eth_blockNumber" => Ok(json!("0x10")),
+        "eth_getBlockByNumber" if params[0] == "0x1" => Ok(json!({
+            "number": "0x1", "hash": "0xabc", "timestamp": "0x5f5e100"
+        })),
+        "eth_getBlockByNumber" => Ok(Value::Null),
+        _ => Err((-32601, "method not found".to_string())),
+    });
+
+    assert_eq!(block_number(&url).await?, 16);
+    let block = get_block(&url, 1).await?.expect("Block should be found");
+    assert_eq!(block.hash, "0xabc");
+    assert_eq!(block.timestamp(), Some(100_000_000));
+    assert!(get_block(&url, 2).await?.is_none());
+    assert!(crate::upstream::eth_rpc::eth_call(&url, "0x00", "0x", None)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_balance_of_1155() -> Result<(), Error> {
+    let owner = "0x00000000000000000000000000000000000000a1";
+    let url = spawn_rpc_server(|method, params| match method {
+        "eth_call" => {
+            let data = params[0]["data"].as_str().unwrap_or_default();
+            // balanceOf(owner, 7)
+            if data.starts_with("0x00fdd58e") && data.ends_with(&format!("{:064x}", 7)) {
+                Ok(json!(format!("0x{:064x}", 3)))
+            } else {
+                Ok(json!(format!("0x{:064x}", 0)))
+            }
+        }
+        _ => Err((-32601, "method not found".to_string())),
+    });
+
+    let balance = balance_of_1155(&url, "0x00", owner, "7", None).await?;
+    assert_eq!(balance.as_deref(), Some("3"));
+    let balance = balance_of_1155(&url, "0x00", owner, "8", None).await?;
+    assert_eq!(balance.as_deref(), Some("0"));
+    assert!(balance_of_1155(&url, "0x00", owner, "not-a-number", None)
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deployer_of() -> Result<(), Error> {
+    let contract = "0x00000000000000000000000000000000000000c0";
+    // Created at block 1234 by the second transaction in it.
+    let url = spawn_rpc_server(move |method, params| match method {
+        "eth_blockNumber" => Ok(json!("0x10000")),
+        "eth_getCode" if params[0] == contract => {
+            let block = match params[1].as_str().unwrap() {
+                "latest" => u64::MAX,
+                number => parse_hex_u64(number).unwrap(),
+            };
+            Ok(json!(if block >= 1234 { "0x6080" } else { "0x" }))
+        }
+        "eth_getCode" => Ok(json!("0x")),
+        "eth_getBlockByNumber" if params[0] == to_hex(1234) => Ok(json!({
+            "transactions": [
+                {"hash": "0x01", "from": "0x00000000000000000000000000000000000000a1", "to": null},
+                {"hash": "0x02", "from": "0x00000000000000000000000000000000000000A2", "to": null},
+                {"hash": "0x03", "from": "0x00000000000000000000000000000000000000a3", "to": contract},
+            ]
+        })),
+        "eth_getTransactionReceipt" if params[0] == "0x01" => Ok(json!({
+            "contractAddress": "0x00000000000000000000000000000000000000c1"
+        })),
+        "eth_getTransactionReceipt" if params[0] == "0x02" => Ok(json!({
+            "contractAddress": contract
+        })),
+        _ => Err((-32601, "method not found".to_string())),
+    });
+
+    assert_eq!(
+        deployer_of(&url, contract).await?.unwrap(),
+        "0x00000000000000000000000000000000000000a2"
+    );
+    // Not a contract
+    assert_eq!(
+        deployer_of(&url, "0x00000000000000000000000000000000000000a1").await?,
+        None
+    );
+    Ok(())
+}
@@ -1,67 +1,142 @@
+#[cfg(test)]
 mod tests;
 
 use crate::{
     config::C,
     error::Error,
     graph::{
-        edge::Edge,
-        edge::Hold,
+        create_identity_to_identity_two_way_binding,
+        edge::{Edge, Hold, Own, Proof, Resolve, ResolvesTo, ReverseOf, Subdomain},
         new_db_connection,
-        vertex::Identity,
-        vertex::{IdentityRecord, Vertex},
+        vertex::{Identity, Vertex},
+        ConnectionPool,
     },
     upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList},
-    util::naive_now,
+    util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive},
 };
-use aragog::DatabaseConnection;
+use aragog::{AqlQuery, DatabaseConnection, Record};
 use async_trait::async_trait;
-use futures::future::join_all;
-use gql_client::Client;
-use serde::{Deserialize, Serialize};
-use tracing::warn;
+use chrono::NaiveDateTime;
+use hyper::{Body, Method};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+/// Farcaster timestamps are seconds since 2021-01-01T00:00:00Z.
+const FARCASTER_EPOCH: i64 = 1609459200;
+/// Migrate at most this many username-keyed identities per run.
+const MIGRATION_BATCH: u64 = 100;
+
+/// https://docs.farcaster.xyz/reference/hubble/httpapi/usernameproof
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserNameProof {
+    pub fid: i64,
+}
+
+/// https://docs.farcaster.xyz/reference/hubble/httpapi/message
+#[derive(Deserialize, Debug, Clone)]
+pub struct MessagesResponse {
+    pub messages: Vec<Message>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Message {
+    pub data: MessageData,
+    pub hash: String,
+}
+
 #[derive(Deserialize, Debug, Clone)]
-#[allow(non_snake_case)]
-#[allow(dead_code)]
-struct FarcasterProfile {
-    /// Farcaster name (fname) The username for your Farcaster account.
-    username: String,
-    /// Display name Full name—up to 64 characters.
-    displayName: Option<String>,
-    /// A connected Ethereum address is associated with your Farcaster account via an off-chain proof
-    /// allowing you to display NFTs and on-chain events on your profile.
-    signerAddress: Option<String>,
-    /// Farcaster ID (fid) The ID number for your Farcaster account.
-    fid: i32,
+#[serde(rename_all = "camelCase")]
+pub struct MessageData {
+    /// Farcaster timestamp, see [`FARCASTER_EPOCH`].
+    pub timestamp: i64,
+    pub user_data_body: Option<UserDataBody>,
+    /// Given by hubs before protocol `2023.11.15`, Ethereum only.
+    pub verification_add_eth_address_body: Option<VerificationBody>,
+    pub verification_add_address_body: Option<VerificationBody>,
 }
 
-#[derive(Serialize)]
-struct UsernameQueryVars {
-    username: String,
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserDataBody {
+    /// `USER_DATA_TYPE_PFP`, `USER_DATA_TYPE_DISPLAY`, `USER_DATA_TYPE_USERNAME`, etc.
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub value: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct VerificationBody {
+    pub address: String,
+    /// `PROTOCOL_ETHEREUM` or `PROTOCOL_SOLANA`. Missing in older hubs.
+    pub protocol: Option<String>,
+}
+
+/// https://docs.farcaster.xyz/reference/hubble/httpapi/onchain
+#[derive(Deserialize, Debug, Clone)]
+pub struct OnChainEventsResponse {
+    pub events: Vec<OnChainEvent>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OnChainEvent {
+    pub block_number: i64,
+    /// Unix timestamp.
+    pub block_timestamp: Option<i64>,
+    pub transaction_hash: String,
+    pub fid: i64,
+    pub id_register_event_body: Option<IdRegisterEventBody>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IdRegisterEventBody {
+    /// Custody address after this event.
+    pub to: String,
+    /// `ID_REGISTER_EVENT_TYPE_REGISTER`, `ID_REGISTER_EVENT_TYPE_TRANSFER`
+    /// or `ID_REGISTER_EVENT_TYPE_CHANGE_RECOVERY`.
+    pub event_type: String,
 }
 
-#[derive(Deserialize, Debug)]
-#[allow(non_snake_case)]
-pub struct UsernameQueryResponse {
-    // farcasterProfile
-    #[serde(rename = "farcasterProfile")]
-    data: Vec<FarcasterProfile>,
+/// Everything we need of an fid, collected from hub.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FarcasterProfile {
+    pub fid: i64,
+    /// fname
+    pub username: Option<String>,
+    pub display_name: Option<String>,
+    pub pfp: Option<String>,
+    pub custody: Option<Custody>,
+    pub verifications: Vec<Verification>,
 }
 
-#[derive(Serialize)]
-struct SignerAddressQueryVars {
-    signer: String,
+/// Custody address owns the fid on `IdRegistry`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Custody {
+    pub address: String,
+    pub transaction: String,
+    pub created_at: Option<NaiveDateTime>,
 }
 
-#[derive(Deserialize, Debug)]
-#[allow(non_snake_case)]
-pub struct SignerAddressQueryResponse {
-    // farcasterSigner
-    #[serde(rename = "farcasterSigner")]
-    data: Vec<FarcasterProfile>,
+/// Address verified by the fid through a signed `VerificationAdd` message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verification {
+    pub platform: Platform,
+    pub address: String,
+    /// Hash of the verification message.
+    pub hash: String,
+    pub created_at: Option<NaiveDateTime>,
 }
 
+/// Fetches Farcaster accounts from a Farcaster Hub HTTP API (`upstream.farcaster_hub.url`).
+///
+/// `Identity(Farcaster, fid)` is the canonical identity, with fname as `display_name`.
+/// - Custody address becomes a `Hold` from `Identity(Ethereum, custody)` to the fid.
+/// - Verified addresses become `Proof`s between the fid and `Identity(Ethereum | Solana, address)`.
+///
+/// Hub does not index verifications by address, so an Ethereum target only
+/// finds the fid it is custody of.
 pub struct Farcaster {}
 
 #[async_trait]
@@ -74,9 +149,10 @@ impl Fetcher for Farcaster {
             Target::Identity(platform, identity) => {
                 fetch_connections_by_platform_identity(platform, identity).await
             }
-            Target::NFT(_, _, _, _) => todo!(),
+            Target::NFT(_, _, _, _) => Ok(vec![]),
         }
     }
+
     fn can_fetch(target: &Target) -> bool {
         target.in_platform_supported(vec![Platform::Farcaster, Platform::Ethereum])
     }
@@ -86,232 +162,404 @@ async fn fetch_connections_by_platform_identity(
     platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
-    match *platform {
-        Platform::Farcaster => fetch_by_username(platform, identity).await,
-        Platform::Ethereum => fetch_by_signer(platform, identity).await,
-        _ => Ok(vec![]),
-    }
-}
-
-async fn get_farcaster_profile_by_username(username: &str) -> Result<Vec<FarcasterProfile>, Error> {
-    const QUERY_BY_NAME: &str = r#"
-        query FarcasterSignerByName($username: String!) {
-            farcasterProfile(username: $username) {
-                username
-                displayName
-                signerAddress
-                fid
-            }
-        }
-    "#;
-    let client = Client::new(&C.upstream.datamgr_api.url);
-    let vars = UsernameQueryVars {
-        username: username.to_string(),
-    };
-    let response = client.query_with_vars::<UsernameQueryResponse, _>(QUERY_BY_NAME, vars);
-
-    let data = match tokio::time::timeout(std::time::Duration::from_secs(5), response).await {
-        Ok(response) => match response {
-            Ok(response) => {
-                let result = response.unwrap();
-                result.data
-            }
-            Err(err) => {
-                warn!(
-                    "GraphQLError: Farcaster fetch | Failed to fetch profile using `username`: {}, error: {:?}", 
-                    username,
-                    err);
-                vec![]
-            }
+    let hub = &C.upstream.farcaster_hub.url;
+    let fid = match *platform {
+        Platform::Farcaster => match identity.parse::<i64>() {
+            Ok(fid) => Some(fid),
+            Err(_) => fetch_fid_by_username(hub, identity).await?,
         },
-        Err(_) => {
-            warn!("Farcaster fetch | Timeout: no response in 5 seconds.");
-            vec![]
+        Platform::Ethereum => fetch_fid_by_custody(hub, identity).await?,
+        _ => None,
+    };
+    let fid = match fid {
+        Some(fid) => fid,
+        None => {
+            debug!("Farcaster {} {} | No fid found", platform, identity);
+            return Ok(vec![]);
         }
     };
-    Ok(data)
+
+    let profile = fetch_profile(hub, fid).await?;
+    let db = new_db_connection().await?;
+    save_profile(&db, &profile).await
 }
 
-async fn get_farcaster_profile_by_signer(address: &str) -> Result<Vec<FarcasterProfile>, Error> {
-    const QUERY_BY_SIGNER: &str = r#"
-        query FarcasterNameBySigner($signer: String!) {
-            farcasterSigner(signer: $signer) {
-                username
-                displayName
-                signerAddress
-                fid
-            }
-        }
-    "#;
-    let client = Client::new(&C.upstream.datamgr_api.url);
-    let vars = SignerAddressQueryVars {
-        signer: address.to_string(),
+/// Resolve a username (fname `dwr` or ENS name `dwr.eth`) to fid.
+/// Used to accept usernames where fid is expected.
+pub(crate) async fn fid_by_username(username: &str) -> Result<Option<String>, Error> {
+    Ok(
+        fetch_fid_by_username(&C.upstream.farcaster_hub.url, username)
+            .await?
+            .map(|fid| fid.to_string()),
+    )
+}
+
+/// `GET {hub}/v1/userNameProofByName?name={username}`
+async fn fetch_fid_by_username(hub: &str, username: &str) -> Result<Option<i64>, Error> {
+    let proof: Option<UserNameProof> = request(&format!(
+        "{}/v1/userNameProofByName?name={}",
+        hub,
+        username.to_lowercase()
+    ))
+    .await?;
+    Ok(proof.map(|p| p.fid))
+}
+
+/// `GET {hub}/v1/onChainIdRegistryEventByAddress?address={address}`
+async fn fetch_fid_by_custody(hub: &str, address: &str) -> Result<Option<i64>, Error> {
+    let event: Option<OnChainEvent> = request(&format!(
+        "{}/v1/onChainIdRegistryEventByAddress?address={}",
+        hub,
+        address.to_lowercase()
+    ))
+    .await?;
+    Ok(event.map(|e| e.fid))
+}
+
+/// Collect user data, custody address and verifications of `fid`.
+async fn fetch_profile(hub: &str, fid: i64) -> Result<FarcasterProfile, Error> {
+    let user_data: Option<MessagesResponse> =
+        request(&format!("{}/v1/userDataByFid?fid={}", hub, fid)).await?;
+    let verifications: Option<MessagesResponse> =
+        request(&format!("{}/v1/verificationsByFid?fid={}", hub, fid)).await?;
+    let id_events: Option<OnChainEventsResponse> = request(&format!(
+        "{}/v1/onChainEventsByFid?fid={}&event_type=EVENT_TYPE_ID_REGISTER",
+        hub, fid
+    ))
+    .await?;
+
+    let user_data = user_data.map(|r| r.messages).unwrap_or_default();
+    // Latest one wins, as hubs may return them in any order.
+    let find_user_data = |data_type: &str| {
+        user_data
+            .iter()
+            .filter_map(|m| Some((m.data.timestamp, m.data.user_data_body.as_ref()?)))
+            .filter(|(_, body)| body.data_type == data_type && !body.value.is_empty())
+            .max_by_key(|(timestamp, _)| *timestamp)
+            .map(|(_, body)| body.value.clone())
     };
-    let response = client.query_with_vars::<SignerAddressQueryResponse, _>(QUERY_BY_SIGNER, vars);
 
-    let data = match tokio::time::timeout(std::time::Duration::from_secs(5), response).await {
-        Ok(response) => match response {
-            Ok(response) => {
-                let result = response.unwrap();
-                result.data
-            }
-            Err(err) => {
-                warn!(
-                    "Farcaster fetch | Failed to fetch profile using `signer`: {}, error: {:?}",
-                    address, err
-                );
-                vec![]
+    Ok(FarcasterProfile {
+        fid,
+        username: find_user_data("USER_DATA_TYPE_USERNAME"),
+        display_name: find_user_data("USER_DATA_TYPE_DISPLAY"),
+        pfp: find_user_data("USER_DATA_TYPE_PFP"),
+        custody: id_events.and_then(|r| current_custody(r.events)),
+        verifications: verifications
+            .map(|r| r.messages)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(parse_verification)
+            .collect(),
+    })
+}
+
+/// Custody address is the receiver of the latest `Register` / `Transfer` event.
+fn current_custody(mut events: Vec<OnChainEvent>) -> Option<Custody> {
+    events.sort_by_key(|e| e.block_number);
+    events.into_iter().rev().find_map(|e| {
+        let body = e.id_register_event_body?;
+        match body.event_type.as_str() {
+            "ID_REGISTER_EVENT_TYPE_REGISTER" | "ID_REGISTER_EVENT_TYPE_TRANSFER" => {
+                Some(Custody {
+                    address: body.to.to_lowercase(),
+                    transaction: e.transaction_hash,
+                    created_at: e.block_timestamp.map(|t| timestamp_to_naive(t, 0)),
+                })
             }
-        },
-        Err(_) => {
-            warn!("Farcaster fetch | Timeout: no response in 5 seconds.");
-            vec![]
+            _ => None,
         }
-    };
-    Ok(data)
+    })
 }
 
-async fn save_profile_ethereum(
-    db: &DatabaseConnection,
-    profile: FarcasterProfile,
-) -> Result<TargetProcessedList, Error> {
-    let target_list = match profile.signerAddress {
-        None => vec![], // signer address is null
-        Some(signer_address) => match signer_address.as_str() {
-            "" => vec![], // signer address is empty string
-            &_ => {
-                let eth_identity: Identity = Identity {
-                    uuid: Some(Uuid::new_v4()),
-                    platform: Platform::Ethereum,
-                    identity: signer_address.to_lowercase().to_string(),
-                    created_at: None,
-                    display_name: None,
-                    added_at: naive_now(),
-                    avatar_url: None,
-                    profile_url: None,
-                    updated_at: naive_now(),
-                };
-                let farcaster_identity: Identity = Identity {
-                    uuid: Some(Uuid::new_v4()),
-                    platform: Platform::Farcaster,
-                    identity: profile.username.clone(),
-                    created_at: None,
-                    display_name: profile.displayName.clone(),
-                    added_at: naive_now(),
-                    avatar_url: None,
-                    profile_url: None,
-                    updated_at: naive_now(),
-                };
-                let hold: Hold = Hold {
-                    uuid: Uuid::new_v4(),
-                    source: DataSource::Farcaster,
-                    transaction: None,
-                    id: "".to_string(),
-                    created_at: None,
-                    updated_at: naive_now(),
-                    fetcher: DataFetcher::DataMgrService,
-                };
-                let eth_record = eth_identity.create_or_update(&db).await?;
-                let farcaster_record = farcaster_identity.create_or_update(&db).await?;
-                hold.connect(&db, &eth_record, &farcaster_record).await?;
-
-                vec![Target::Identity(
-                    Platform::Ethereum,
-                    signer_address.to_lowercase().to_string(),
-                )]
-            }
-        },
+fn parse_verification(message: &Message) -> Option<Verification> {
+    let data = &message.data;
+    let (body, is_legacy) = match (
+        data.verification_add_address_body.as_ref(),
+        data.verification_add_eth_address_body.as_ref(),
+    ) {
+        (Some(body), _) => (body, false),
+        (None, Some(body)) => (body, true),
+        (None, None) => return None,
     };
-    Ok(target_list)
+    let platform = match body.protocol.as_deref() {
+        Some("PROTOCOL_SOLANA") => Platform::Solana,
+        Some("PROTOCOL_ETHEREUM") => Platform::Ethereum,
+        None if is_legacy => Platform::Ethereum,
+        _ => return None,
+    };
+    let address = match platform {
+        // Solana addresses are case-sensitive.
+        Platform::Solana => body.address.clone(),
+        _ => body.address.to_lowercase(),
+    };
+
+    Some(Verification {
+        platform,
+        address,
+        hash: message.hash.clone(),
+        created_at: Some(farcaster_timestamp_to_naive(data.timestamp)),
+    })
 }
 
-async fn save_profile_signer(
+fn farcaster_timestamp_to_naive(timestamp: i64) -> NaiveDateTime {
+    timestamp_to_naive(FARCASTER_EPOCH + timestamp, 0)
+}
+
+async fn save_profile(
     db: &DatabaseConnection,
-    profile: FarcasterProfile,
+    profile: &FarcasterProfile,
 ) -> Result<TargetProcessedList, Error> {
-    let eth_identity: Identity = Identity {
-        uuid: Some(Uuid::new_v4()),
-        platform: Platform::Ethereum,
-        identity: profile
-            .signerAddress
-            .clone()
-            .unwrap()
-            .to_lowercase()
-            .to_string(),
-        created_at: None,
-        display_name: None,
-        added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
-        updated_at: naive_now(),
-    };
-    let farcaster_identity: Identity = Identity {
+    let fid = profile.fid.to_string();
+    let farcaster: Identity = Identity {
         uuid: Some(Uuid::new_v4()),
         platform: Platform::Farcaster,
-        identity: profile.username.clone(),
-        created_at: None,
-        display_name: profile.displayName.clone(),
+        identity: fid.clone(),
+        created_at: profile.custody.as_ref().and_then(|c| c.created_at),
+        display_name: profile
+            .username
+            .clone()
+            .or_else(|| profile.display_name.clone()),
         added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
+        avatar_url: profile.pfp.clone(),
+        profile_url: profile
+            .username
+            .as_ref()
+            .map(|username| format!("https://warpcast.com/{}", username)),
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
-    let hold: Hold = Hold {
-        uuid: Uuid::new_v4(),
-        source: DataSource::Farcaster,
-        transaction: None,
-        id: "".to_string(),
-        created_at: None,
-        updated_at: naive_now(),
-        fetcher: DataFetcher::DataMgrService,
-    };
-    let eth_record = eth_identity.create_or_update(&db).await?;
-    let farcaster_record = farcaster_identity.create_or_update(&db).await?;
-    hold.connect(&db, &eth_record, &farcaster_record).await?;
-    Ok(vec![Target::Identity(
-        Platform::Farcaster,
-        profile.username.clone(),
-    )])
+    let farcaster_record = farcaster.create_or_update(db).await?;
+
+    let mut next_targets: TargetProcessedList = vec![];
+    if let Some(custody) = profile.custody.as_ref() {
+        let owner: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: Platform::Ethereum,
+            identity: custody.address.clone(),
+            created_at: None,
+            display_name: None,
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let hold: Hold = Hold {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Farcaster,
+            transaction: Some(custody.transaction.clone()),
+            id: fid.clone(),
+            created_at: custody.created_at,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            event_id: None,
+            event_name: None,
+            ended_at: None,
+            expired_at: None,
+            amount: None,
+            decimals: None,
+        };
+        let owner_record = owner.create_or_update(db).await?;
+        hold.connect(db, &owner_record, &farcaster_record).await?;
+        next_targets.push(Target::Identity(
+            Platform::Ethereum,
+            custody.address.clone(),
+        ));
+    }
+
+    for verification in profile.verifications.iter() {
+        let verified: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: verification.platform,
+            identity: verification.address.clone(),
+            created_at: None,
+            display_name: None,
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let proof: Proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Farcaster,
+            record_id: Some(verification.hash.clone()),
+            created_at: verification.created_at,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            deleted_at: None,
+        };
+        create_identity_to_identity_two_way_binding(db, &farcaster, &verified, &proof).await?;
+        next_targets.push(Target::Identity(
+            verification.platform,
+            verification.address.clone(),
+        ));
+    }
+
+    Ok(next_targets)
 }
 
-async fn fetch_by_username(
-    _platform: &Platform,
-    username: &str,
-) -> Result<TargetProcessedList, Error> {
+/// Edge collections which may link an `Identity`.
+fn identity_edge_collections() -> [&'static str; 7] {
+    [
+        Proof::COLLECTION_NAME,
+        Hold::COLLECTION_NAME,
+        Resolve::COLLECTION_NAME,
+        Own::COLLECTION_NAME,
+        ResolvesTo::COLLECTION_NAME,
+        ReverseOf::COLLECTION_NAME,
+        Subdomain::COLLECTION_NAME,
+    ]
+}
+
+/// Migrates `Identity(Farcaster, username)`, saved before fids were the identity of
+/// Farcaster accounts, to `Identity(Farcaster, fid)`, so that one account is not saved twice.
+/// Only identities linked by this upstream are migrated, at most `MIGRATION_BATCH` per run.
+/// Returns how many of them are migrated.
+pub async fn migrate_username_identities(pool: &ConnectionPool) -> Result<usize, Error> {
+    let hub = &C.upstream.farcaster_hub.url;
+    let conn = pool
+        .get()
+        .await
+        .map_err(|err| Error::PoolError(err.to_string()))?;
+    let aql = r###"
+        FOR i IN @@identities
+            FILTER i.platform == @platform AND !REGEX_TEST(i.identity, "^[0-9]+$")
+            FILTER LENGTH(
+                FOR e IN @@holds
+                    FILTER e._to == i._id AND e.source == @source
+                    LIMIT 1
+                    RETURN 1
+            ) > 0
+            LIMIT @limit
+            RETURN {"id": i._id, "username": i.identity}"###;
+    let aql = AqlQuery::new(aql)
+        .bind_var("@identities", Identity::COLLECTION_NAME)
+        .bind_var("@holds", Hold::COLLECTION_NAME)
+        .bind_var("platform", Platform::Farcaster.to_string())
+        .bind_var("source", DataSource::Farcaster.to_string())
+        .bind_var("limit", MIGRATION_BATCH)
+        .batch_size(1)
+        .count(false);
+    let legacy: Vec<Value> = conn.database().aql_query(aql).await?;
+    drop(conn);
+
     let db = new_db_connection().await?;
-    let profiles = get_farcaster_profile_by_username(&username).await?;
-    if profiles.is_empty() {
-        return Err(Error::NoResult);
+    for found in legacy.iter() {
+        let (id, username) = match (found["id"].as_str(), found["username"].as_str()) {
+            (Some(id), Some(username)) => (id, username),
+            _ => continue,
+        };
+        let fid = match fetch_fid_by_username(hub, username).await? {
+            Some(fid) => {
+                let profile = fetch_profile(hub, fid).await?;
+                save_profile(&db, &profile).await?;
+                Identity::find_by_platform_identity(&db, &Platform::Farcaster, &fid.to_string())
+                    .await?
+            }
+            None => None,
+        };
+        merge_identity(pool, id, fid.as_ref().map(|record| record.id().as_str())).await?;
+        info!(
+            "Farcaster {} | Migrated to fid {:?}",
+            username,
+            fid.map(|record| record.identity.clone())
+        );
     }
-    let futures: Vec<_> = profiles
-        .into_iter()
-        .map(|profile| save_profile_ethereum(&db, profile))
-        .collect();
-    let targets: TargetProcessedList = join_all(futures)
+    Ok(legacy.len())
+}
+
+/// Moves edges of identity `old` (`_id`) onto `new`, then removes `old`.
+/// Edges given by this upstream are dropped instead, as fetching the fid saves them again.
+/// Without `new` (the username is no longer known by hub), `old` is only removed if
+/// nothing else links it.
+async fn merge_identity(pool: &ConnectionPool, old: &str, new: Option<&str>) -> Result<(), Error> {
+    let conn = pool
+        .get()
         .await
-        .into_iter()
-        .flat_map(|result| result.unwrap_or_default())
-        .collect();
-    Ok(targets)
+        .map_err(|err| Error::PoolError(err.to_string()))?;
+    let db = conn.database();
+    let mut linked = 0;
+    for collection in identity_edge_collections() {
+        let aql = r###"
+            FOR e IN @@edges
+                FILTER (e._from == @old OR e._to == @old) AND e.source == @source
+                REMOVE e IN @@edges"###;
+        let aql = AqlQuery::new(aql)
+            .bind_var("@edges", collection)
+            .bind_var("old", old)
+            .bind_var("source", DataSource::Farcaster.to_string());
+        db.aql_query::<Value>(aql).await?;
+
+        let aql = match new {
+            Some(new) => AqlQuery::new(
+                r###"
+                FOR e IN @@edges
+                    FILTER e._from == @old OR e._to == @old
+                    UPDATE e WITH {
+                        _from: e._from == @old ? @new : e._from,
+                        _to: e._to == @old ? @new : e._to
+                    } IN @@edges
+                    RETURN 1"###,
+            )
+            .bind_var("new", new),
+            None => AqlQuery::new(
+                r###"
+                FOR e IN @@edges
+                    FILTER e._from == @old OR e._to == @old
+                    RETURN 1"###,
+            ),
+        };
+        let aql = aql.bind_var("@edges", collection).bind_var("old", old);
+        linked += db.aql_query::<Value>(aql).await?.len();
+    }
+
+    if new.is_some() || linked == 0 {
+        let aql = AqlQuery::new("REMOVE PARSE_IDENTIFIER(@old).key IN @@identities")
+            .bind_var("@identities", Identity::COLLECTION_NAME)
+            .bind_var("old", old);
+        db.aql_query::<Value>(aql).await?;
+    }
+    Ok(())
 }
 
-async fn fetch_by_signer(
-    _platform: &Platform,
-    address: &str,
-) -> Result<TargetProcessedList, Error> {
-    let db = new_db_connection().await?;
-    let profiles = get_farcaster_profile_by_signer(&address).await?;
-    if profiles.is_empty() {
-        return Err(Error::NoResult);
+/// Returns `None` if hub responds `404` (i.e. fid / fname not found).
+async fn request<T>(uri: &str) -> Result<Option<T>, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let client = make_client();
+    let uri: http::Uri = uri
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("accept", "application/json")
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("Farcaster Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("Farcaster fetch | error: {:?}", err.to_string()))
+    })?;
+    // Hub responds 400 with `errCode: not_found` for unknown fname.
+    if resp.status() == hyper::StatusCode::NOT_FOUND
+        || resp.status() == hyper::StatusCode::BAD_REQUEST
+    {
+        debug!("Farcaster fetch | status {}", resp.status());
+        return Ok(None);
     }
-    let futures: Vec<_> = profiles
-        .into_iter()
-        .map(|profile| save_profile_signer(&db, profile))
-        .collect();
-    let targets: TargetProcessedList = join_all(futures)
-        .await
-        .into_iter()
-        .flat_map(|result| result.unwrap_or_default())
-        .collect();
-    Ok(targets)
+    if !resp.status().is_success() {
+        warn!("Farcaster fetch error, status {}", resp.status());
+        return Err(Error::General(
+            format!("Farcaster Hub Result Get Error: {}", resp.status()),
+            resp.status(),
+        ));
+    }
+
+    Ok(Some(parse_body(&mut resp).await?))
 }
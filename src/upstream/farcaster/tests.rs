@@ -1,23 +1,194 @@
-#[cfg(test)]
-mod tests {
-    use crate::error::Error;
-    use crate::upstream::farcaster::{
-        get_farcaster_profile_by_signer, get_farcaster_profile_by_username,
-    };
+use crate::{
+    error::Error,
+    graph::{
+        arangopool::new_connection_pool,
+        edge::{Edge, Hold, Proof},
+        new_db_connection,
+        vertex::{Identity, Vertex},
+    },
+    tests::spawn_stub_server,
+    upstream::{
+        farcaster::{
+            fetch_fid_by_custody, fetch_fid_by_username, fetch_profile, merge_identity, Custody,
+        },
+        DataSource, Platform,
+    },
+    util::timestamp_to_naive,
+};
+use fake::{Fake, Faker};
+use std::collections::HashMap;
+use uuid::Uuid;
+use warp::{http::StatusCode, Filter};
+
+const CUSTODY: &str = "0x6b0bda3f2ffed5efc83fa8c024acff1dd45793f1";
 
-    #[tokio::test]
-    async fn test_get_farcaster_profile_by_username() -> Result<(), Error> {
-        let username = "zella";
-        let data = get_farcaster_profile_by_username(&username).await?;
-        println!("data: {:?}", data);
-        Ok(())
+/// Hand-written responses in the shape of hub HTTP API for fid `3` (`dwr`).
+/// Hashes and signatures in them are placeholders, not real messages.
+/// TODO: replace them with recorded ones (`just record-farcaster-fixtures`), and update
+/// assertions below to what the hub returns.
+/// Solana verifications share `MESSAGE_TYPE_VERIFICATION_ADD_ETH_ADDRESS` with Ethereum ones,
+/// and are told apart by `protocol`, as hubs do.
+fn fixture(name: &str) -> Option<&'static str> {
+    match name {
+        "userNameProofByName" => Some(include_str!("fixtures/user_name_proof_by_name.json")),
+        "userDataByFid" => Some(include_str!("fixtures/user_data_by_fid.json")),
+        "verificationsByFid" => Some(include_str!("fixtures/verifications_by_fid.json")),
+        "onChainEventsByFid" => Some(include_str!("fixtures/on_chain_events_by_fid.json")),
+        "onChainIdRegistryEventByAddress" => Some(include_str!(
+            "fixtures/on_chain_id_registry_event_by_address.json"
+        )),
+        _ => None,
     }
+}
+
+/// Serves fixtures for fid `3` only, like a hub would do.
+fn stub_hub() -> String {
+    let hub = warp::path!("v1" / String)
+        .and(warp::query::<HashMap<String, String>>())
+        .map(|endpoint: String, query: HashMap<String, String>| {
+            let known = query.get("fid").map_or(false, |fid| fid == "3")
+                || query.get("name").map_or(false, |name| name == "dwr")
+                || query.get("address").map_or(false, |addr| addr == CUSTODY);
+            match fixture(&endpoint) {
+                Some(body) if known => warp::reply::with_status(body, StatusCode::OK),
+                _ => warp::reply::with_status(
+                    r#"{"errCode":"not_found","presentable":false}"#,
+                    StatusCode::BAD_REQUEST,
+                ),
+            }
+        });
+    spawn_stub_server(hub)
+}
+
+#[tokio::test]
+async fn test_fetch_fid() -> Result<(), Error> {
+    let hub = stub_hub();
+
+    assert_eq!(fetch_fid_by_username(&hub, "DWR").await?, Some(3));
+    assert_eq!(fetch_fid_by_username(&hub, "nobody").await?, None);
+    assert_eq!(fetch_fid_by_custody(&hub, CUSTODY).await?, Some(3));
+    assert_eq!(
+        fetch_fid_by_custody(&hub, "0x0000000000000000000000000000000000000000").await?,
+        None
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_profile() -> Result<(), Error> {
+    let hub = stub_hub();
+    let profile = fetch_profile(&hub, 3).await?;
+
+    assert_eq!(profile.username, Some("dwr".to_string()));
+    // The older display name listed after it is ignored.
+    assert_eq!(profile.display_name, Some("Dan Romero".to_string()));
+    assert_eq!(
+        profile.pfp,
+        Some("https://i.imgur.com/dwr-pfp.jpg".to_string())
+    );
+    // `ChangeRecovery` does not change custody.
+    assert_eq!(
+        profile.custody,
+        Some(Custody {
+            address: CUSTODY.to_string(),
+            transaction: "0x4ddbd0ec1e7cc45e7b8ba9db1a9b0d1c9b4e0f7c2b0f9b1c6e6b3b4ea2c1f0d9"
+                .to_string(),
+            created_at: Some(timestamp_to_naive(1695950965, 0)),
+        })
+    );
+
+    let verified: Vec<(Platform, &str)> = profile
+        .verifications
+        .iter()
+        .map(|v| (v.platform, v.address.as_str()))
+        .collect();
+    assert_eq!(
+        verified,
+        vec![
+            (
+                Platform::Ethereum,
+                "0xd7029bdea1c17493893aafe29aad69ef892b8ff2"
+            ),
+            (
+                Platform::Solana,
+                "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX"
+            ),
+            (
+                Platform::Ethereum,
+                "0x8fc5d6afe572fefc4ec153587b63ce543f6fa2ea"
+            ),
+        ]
+    );
+    // Farcaster epoch + 73244540
+    assert_eq!(
+        profile.verifications[0].created_at,
+        Some(timestamp_to_naive(1682703740, 0))
+    );
 
-    #[tokio::test]
-    async fn test_get_farcaster_profile_by_signer() -> Result<(), Error> {
-        let address = "0xb86ff7e3f4e6186dfd25cff40605441d0c0481c4";
-        let data = get_farcaster_profile_by_signer(&address).await?;
-        println!("data: {:?}", data);
-        Ok(())
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_unknown_fid() -> Result<(), Error> {
+    let hub = stub_hub();
+    let profile = fetch_profile(&hub, 999999999).await?;
+
+    assert_eq!(profile.username, None);
+    assert_eq!(profile.custody, None);
+    assert!(profile.verifications.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_identity() -> Result<(), Error> {
+    let db = new_db_connection().await?;
+    let pool = new_connection_pool().await?;
+    let username = format!("legacy-{}", Uuid::new_v4().simple());
+    let legacy = Identity {
+        platform: Platform::Farcaster,
+        identity: username.clone(),
+        ..Faker.fake()
     }
+    .create_or_update(&db)
+    .await?;
+    let fid = Identity {
+        platform: Platform::Farcaster,
+        identity: (Uuid::new_v4().as_u128() % 1_000_000_000_000).to_string(),
+        ..Faker.fake()
+    }
+    .create_or_update(&db)
+    .await?;
+    let custody = Identity::create_dummy(&db).await?;
+    let twitter = Identity::create_dummy(&db).await?;
+    let hold = Hold {
+        source: DataSource::Farcaster,
+        ..Faker.fake()
+    };
+    hold.connect(&db, &custody, &legacy).await?;
+    let proof = Proof {
+        source: DataSource::NextID,
+        ..Faker.fake()
+    };
+    proof.connect(&db, &legacy, &twitter).await?;
+
+    merge_identity(&pool, legacy.id(), Some(fid.id())).await?;
+
+    assert!(
+        Identity::find_by_platform_identity(&db, &Platform::Farcaster, &username)
+            .await?
+            .is_none()
+    );
+    // Proofs from other sources are moved over.
+    assert!(
+        Proof::find_by_from_to(&db, &fid, &twitter, &DataSource::NextID, &proof.record_id)
+            .await?
+            .is_some()
+    );
+    // Holds given by Farcaster are dropped, fetching the fid saves them again.
+    assert!(Hold::find_by_from_to_id(&db, &custody, &fid, &hold.id)
+        .await?
+        .is_none());
+    Ok(())
 }
@@ -11,7 +11,9 @@ use async_trait::async_trait;
 use serde::Deserialize;
 
 use hyper::{Body, Method};
+use std::collections::BTreeMap;
 use std::str::FromStr;
+use tracing::debug;
 use uuid::Uuid;
 
 use super::{DataFetcher, Target};
@@ -19,7 +21,7 @@ use super::{DataFetcher, Target};
 #[derive(Deserialize, Debug)]
 pub struct KeybaseResponse {
     pub status: Status,
-    pub them: Vec<PersonInfo>,
+    pub them: Vec<Option<PersonInfo>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -27,6 +29,30 @@ pub struct PersonInfo {
     pub id: String,
     pub basics: Basics,
     pub proofs_summary: ProofsSummary,
+    #[serde(default)]
+    pub public_keys: Option<PublicKeys>,
+    /// `{"bitcoin": [...], "zcash.t": [...]}`
+    #[serde(default)]
+    pub cryptocurrency_addresses: BTreeMap<String, Vec<CryptocurrencyAddress>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PublicKeys {
+    pub primary: Option<PublicKey>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PublicKey {
+    pub kid: String,
+    /// Empty for non-PGP (NaCl) keys.
+    #[serde(default)]
+    pub key_fingerprint: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CryptocurrencyAddress {
+    pub address: String,
+    pub sig_id: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -87,26 +113,206 @@ impl Fetcher for Keybase {
             Target::Identity(platform, identity) => {
                 fetch_connections_by_platform_identity(platform, identity).await
             }
-            Target::NFT(_, _, _, _) => todo!(),
+            Target::NFT(_, _, _, _) => Ok(vec![]),
         }
     }
 
     fn can_fetch(target: &Target) -> bool {
-        target.in_platform_supported(vec![Platform::Twitter, Platform::Github, Platform::Reddit])
+        target.in_platform_supported(vec![
+            Platform::Keybase,
+            Platform::Twitter,
+            Platform::Github,
+            Platform::Reddit,
+            Platform::HackerNews,
+            Platform::Facebook,
+            Platform::DNS,
+            Platform::PGP,
+        ])
     }
 }
 
+/// A connection proven by a Keybase user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub platform: Platform,
+    pub identity: String,
+    pub display_name: String,
+    /// `proof_id` of a social proof, `sig_id` of a crypto address, `kid` of a PGP key.
+    pub record_id: String,
+}
+
 async fn fetch_connections_by_platform_identity(
     platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
-    let client = make_client();
-    let uri: http::Uri = match format!(
-        "{}?{}={}&fields=proofs_summary",
-        C.upstream.keybase_service.url, platform, identity
+    let query = match lookup_query(platform, identity) {
+        Some(query) => query,
+        None => return Ok(vec![]),
+    };
+    let person_info = fetch_person(&C.upstream.keybase_service.url, &query).await?;
+    let from: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Keybase,
+        identity: person_info.id.clone(),
+        created_at: None,
+        display_name: Some(person_info.basics.username.clone()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: Some(format!(
+            "https://keybase.io/{}",
+            person_info.basics.username
+        )),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+
+    let db = new_db_connection().await?;
+    let mut next_targets: TargetProcessedList = Vec::new();
+    for connection in connections_of(&person_info).into_iter() {
+        let to: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: connection.platform,
+            identity: connection.identity.clone(),
+            created_at: None,
+            display_name: Some(connection.display_name.clone()),
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+
+        let pf: Proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Keybase,
+            record_id: Some(connection.record_id.clone()),
+            created_at: None,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            deleted_at: None,
+        };
+
+        create_identity_to_identity_two_way_binding(&db, &from, &to, &pf).await?;
+
+        next_targets.push(Target::Identity(connection.platform, connection.identity));
+    }
+
+    Ok(next_targets)
+}
+
+/// Query string of `user/lookup.json` for `identity`.
+/// `None` if Keybase cannot be searched by this platform.
+/// https://keybase.io/docs/api/1.0/call/user/lookup
+fn lookup_query(platform: &Platform, identity: &str) -> Option<String> {
+    let key = match platform {
+        Platform::Keybase if is_keybase_uid(identity) => "uids",
+        Platform::Keybase => "usernames",
+        Platform::Twitter => "twitter",
+        Platform::Github => "github",
+        Platform::Reddit => "reddit",
+        Platform::HackerNews => "hackernews",
+        Platform::Facebook => "facebook",
+        Platform::DNS => "domain",
+        Platform::PGP => "key_fingerprint",
+        _ => return None,
+    };
+    Some(
+        url::form_urlencoded::Serializer::new(String::new())
+            .append_pair(key, identity)
+            .append_pair(
+                "fields",
+                "basics,proofs_summary,public_keys,cryptocurrency_addresses",
+            )
+            .finish(),
     )
-    .parse()
-    {
+}
+
+/// Keybase identities are saved by user ID, which is 32 hex chars.
+fn is_keybase_uid(identity: &str) -> bool {
+    identity.len() == 32 && identity.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Maps a social proof onto `(Platform, identity)`.
+/// Websites and DNS proofs become `Identity(DNS, host)`,
+/// Mastodon-like instances (`proof_type` being the instance domain) become `user@instance`.
+pub fn proof_to_identity(proof: &ProofItem) -> Option<(Platform, String)> {
+    let nametag = proof.nametag.to_lowercase();
+    match proof.proof_type.as_str() {
+        "generic_web_site" | "dns" | "web" => Some((Platform::DNS, nametag)),
+        proof_type if proof_type.contains('.') => {
+            let profile = format!("https://{}/@{}", proof_type, proof.nametag).to_lowercase();
+            if proof.service_url.to_lowercase() == profile {
+                Some((Platform::Mastodon, format!("{}@{}", nametag, proof_type)))
+            } else {
+                None
+            }
+        }
+        proof_type => match Platform::from_str(proof_type) {
+            Ok(Platform::Unknown) | Err(_) => None,
+            Ok(platform) => Some((platform, nametag)),
+        },
+    }
+}
+
+/// All social proofs, PGP key and crypto addresses of a Keybase user.
+pub fn connections_of(person_info: &PersonInfo) -> Vec<Connection> {
+    let mut result: Vec<Connection> = Vec::new();
+    for p in person_info.proofs_summary.all.iter() {
+        match proof_to_identity(p) {
+            Some((platform, identity)) => result.push(Connection {
+                platform,
+                identity,
+                display_name: p.nametag.clone(),
+                record_id: p.proof_id.clone(),
+            }),
+            None => debug!("Keybase | Unsupported proof type {}", p.proof_type),
+        }
+    }
+
+    let primary_key = person_info
+        .public_keys
+        .as_ref()
+        .and_then(|keys| keys.primary.as_ref());
+    if let Some(key) = primary_key {
+        if let Some(fingerprint) = key.key_fingerprint.as_ref().filter(|f| !f.is_empty()) {
+            result.push(Connection {
+                platform: Platform::PGP,
+                identity: fingerprint.to_lowercase(),
+                display_name: fingerprint.to_uppercase(),
+                record_id: key.kid.clone(),
+            });
+        }
+    }
+
+    for (currency, addresses) in person_info.cryptocurrency_addresses.iter() {
+        // `zcash.t`, `zcash.z`, ...
+        let platform = match currency.split('.').next().unwrap_or_default() {
+            "bitcoin" => Platform::Bitcoin,
+            "zcash" => Platform::Zcash,
+            _ => {
+                debug!("Keybase | Unsupported cryptocurrency {}", currency);
+                continue;
+            }
+        };
+        for address in addresses.iter() {
+            result.push(Connection {
+                platform,
+                identity: address.address.clone(),
+                display_name: address.address.clone(),
+                record_id: address.sig_id.clone(),
+            });
+        }
+    }
+
+    result
+}
+
+/// `GET {base}?{query}`
+async fn fetch_person(base: &str, query: &str) -> Result<PersonInfo, Error> {
+    let client = make_client();
+    let uri: http::Uri = match format!("{}?{}", base, query).parse() {
         Ok(n) => n,
         Err(err) => return Err(Error::ParamError(format!("Uri format Error: {}", err))),
     };
@@ -137,61 +343,9 @@ async fn fetch_connections_by_platform_identity(
         ));
     }
 
-    let person_info = match body.them.pop() {
-        Some(i) => i,
-        None => {
-            return Err(Error::NoResult);
-        }
-    };
-    let user_id = person_info.id;
-    let user_name = person_info.basics.username;
-    let db = new_db_connection().await?;
-    let mut next_targets: TargetProcessedList = Vec::new();
-
-    for p in person_info.proofs_summary.all.into_iter() {
-        let from: Identity = Identity {
-            uuid: Some(Uuid::new_v4()),
-            platform: Platform::Keybase,
-            identity: user_id.clone(),
-            created_at: None,
-            display_name: Some(user_name.clone()),
-            added_at: naive_now(),
-            avatar_url: None,
-            profile_url: None,
-            updated_at: naive_now(),
-        };
-
-        if Platform::from_str(p.proof_type.as_str()).is_err() {
-            continue;
-        }
-        let to: Identity = Identity {
-            uuid: Some(Uuid::new_v4()),
-            platform: Platform::from_str(p.proof_type.as_str()).unwrap(),
-            identity: p.nametag.clone().to_lowercase(),
-            created_at: None,
-            display_name: Some(p.nametag.clone()),
-            added_at: naive_now(),
-            avatar_url: None,
-            profile_url: None,
-            updated_at: naive_now(),
-        };
-
-        let pf: Proof = Proof {
-            uuid: Uuid::new_v4(),
-            source: DataSource::Keybase,
-            record_id: Some(p.proof_id.clone()),
-            created_at: None,
-            updated_at: naive_now(),
-            fetcher: DataFetcher::RelationService,
-        };
-
-        create_identity_to_identity_two_way_binding(&db, &from, &to, &pf).await?;
-
-        next_targets.push(Target::Identity(
-            Platform::from_str(&p.proof_type).unwrap(),
-            p.nametag,
-        ));
+    // `them` may contain `null` for usernames not found.
+    match body.them.pop().flatten() {
+        Some(person_info) => Ok(person_info),
+        None => Err(Error::NoResult),
     }
-
-    Ok(next_targets)
 }
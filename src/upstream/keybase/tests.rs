@@ -2,10 +2,153 @@ use crate::{
     error::Error,
     graph::new_db_connection,
     graph::vertex::Identity,
-    upstream::{keybase::Keybase, Target},
+    tests::spawn_json_server,
+    upstream::{
+        keybase::{connections_of, fetch_person, lookup_query, proof_to_identity, Keybase},
+        Target,
+    },
     upstream::{Fetcher, Platform},
     util::naive_now,
 };
+use serde_json::{json, Value};
+
+fn person_json() -> Value {
+    json!({
+        "id": "0123456789abcdef0123456789abcd19",
+        "basics": {
+            "username": "alice",
+            "ctime": 1400000000,
+            "mtime": 1400000000,
+            "id_version": 1,
+            "track_version": 1,
+            "last_id_change": 1400000000,
+            "username_cased": "Alice",
+            "status": 0,
+            "salt": "",
+            "eldest_seqno": 1
+        },
+        "proofs_summary": {
+            "all": [
+                proof_json("twitter", "Alice", "https://twitter.com/Alice"),
+                proof_json("hackernews", "alice", "https://news.ycombinator.com/user?id=alice"),
+                proof_json("generic_web_site", "alice.example.com", "https://alice.example.com"),
+                proof_json("mastodon.social", "Alice", "https://mastodon.social/@Alice"),
+                proof_json("unknown.service", "alice", "https://unknown.service/u/alice")
+            ]
+        },
+        "public_keys": {
+            "primary": {
+                "kid": "0101abcd",
+                "key_fingerprint": "ABCDEF0123456789ABCDEF0123456789ABCDEF01"
+            }
+        },
+        "cryptocurrency_addresses": {
+            "bitcoin": [{"address": "1AliceBtcAddress", "sig_id": "sig_btc"}],
+            "zcash.t": [{"address": "t1AliceZecAddress", "sig_id": "sig_zec"}],
+            "dogecoin": [{"address": "DAlice", "sig_id": "sig_doge"}]
+        }
+    })
+}
+
+fn proof_json(proof_type: &str, nametag: &str, service_url: &str) -> Value {
+    json!({
+        "proof_type": proof_type,
+        "nametag": nametag,
+        "state": 1,
+        "service_url": service_url,
+        "proof_url": service_url,
+        "sig_id": format!("sig_{}", proof_type),
+        "proof_id": format!("proof_{}", proof_type),
+        "human_url": service_url,
+        "presentation_group": proof_type,
+        "presentation_tag": proof_type
+    })
+}
+
+#[test]
+fn test_lookup_query() {
+    assert_eq!(
+        lookup_query(&Platform::Keybase, "alice").unwrap(),
+        "usernames=alice&fields=basics%2Cproofs_summary%2Cpublic_keys%2Ccryptocurrency_addresses"
+    );
+    // Identities can't break out of their parameter.
+    assert!(lookup_query(&Platform::Twitter, "a&b=c d")
+        .unwrap()
+        .starts_with("twitter=a%26b%3Dc+d&"));
+    assert!(
+        lookup_query(&Platform::Keybase, "0123456789abcdef0123456789abcd19")
+            .unwrap()
+            .starts_with("uids=")
+    );
+    assert!(lookup_query(&Platform::DNS, "example.com")
+        .unwrap()
+        .starts_with("domain=example.com"));
+    assert!(lookup_query(&Platform::PGP, "abcdef")
+        .unwrap()
+        .starts_with("key_fingerprint=abcdef"));
+    assert_eq!(lookup_query(&Platform::Ethereum, "0x00"), None);
+}
+
+#[tokio::test]
+async fn test_fetch_person_connections() -> Result<(), Error> {
+    let base = spawn_json_server(vec![(
+        "/lookup.json",
+        json!({
+            "status": {"code": 0, "name": "OK"},
+            "them": [person_json()]
+        }),
+    )]);
+
+    let person = fetch_person(&format!("{}/lookup.json", base), "usernames=alice").await?;
+    assert_eq!(person.basics.username, "alice");
+
+    let proofs = &person.proofs_summary.all;
+    assert_eq!(
+        proof_to_identity(&proofs[2]),
+        Some((Platform::DNS, "alice.example.com".to_string()))
+    );
+    assert_eq!(
+        proof_to_identity(&proofs[3]),
+        Some((Platform::Mastodon, "alice@mastodon.social".to_string()))
+    );
+    assert_eq!(proof_to_identity(&proofs[4]), None);
+
+    let connections: Vec<(Platform, String)> = connections_of(&person)
+        .into_iter()
+        .map(|c| (c.platform, c.identity))
+        .collect();
+    assert_eq!(
+        connections,
+        vec![
+            (Platform::Twitter, "alice".to_string()),
+            (Platform::HackerNews, "alice".to_string()),
+            (Platform::DNS, "alice.example.com".to_string()),
+            (Platform::Mastodon, "alice@mastodon.social".to_string()),
+            (
+                Platform::PGP,
+                "abcdef0123456789abcdef0123456789abcdef01".to_string()
+            ),
+            (Platform::Bitcoin, "1AliceBtcAddress".to_string()),
+            (Platform::Zcash, "t1AliceZecAddress".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_person_not_found() -> Result<(), Error> {
+    let base = spawn_json_server(vec![(
+        "/lookup.json",
+        json!({
+            "status": {"code": 0, "name": "OK"},
+            "them": [null]
+        }),
+    )]);
+
+    let result = fetch_person(&format!("{}/lookup.json", base), "usernames=nobody").await;
+    assert!(matches!(result, Err(Error::NoResult)));
+    Ok(())
+}
 
 #[tokio::test]
 async fn test_smoke_keybase() -> Result<(), Error> {
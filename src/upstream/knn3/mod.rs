@@ -133,6 +133,8 @@ async fn fetch_ens_by_eth_wallet(identity: &str) -> Result<TargetProcessedList,
             avatar_url: None,
             profile_url: None,
             updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
         };
         let to: Contract = Contract {
             uuid: Uuid::new_v4(),
@@ -140,6 +142,10 @@ async fn fetch_ens_by_eth_wallet(identity: &str) -> Result<TargetProcessedList,
             address: ContractCategory::ENS.default_contract_address().unwrap(),
             chain: Chain::Ethereum,
             symbol: None,
+            name: None,
+            total_supply: None,
+            deployer: None,
+            verified: false,
             updated_at: naive_now(),
         };
         let ownership: Hold = Hold {
@@ -150,6 +156,12 @@ async fn fetch_ens_by_eth_wallet(identity: &str) -> Result<TargetProcessedList,
             created_at: None,
             updated_at: naive_now(),
             fetcher: DataFetcher::RelationService,
+            event_id: None,
+            event_name: None,
+            ended_at: None,
+            expired_at: None,
+            amount: None,
+            decimals: None,
         };
         create_identity_to_contract_record(&db, &from, &to, &ownership).await?;
     }
@@ -222,6 +234,8 @@ async fn fetch_eth_wallet_by_ens(id: &str) -> Result<TargetProcessedList, Error>
         created_at: None,
         added_at: naive_now(),
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
     let to = Contract {
         uuid: Uuid::new_v4(),
@@ -230,6 +244,10 @@ async fn fetch_eth_wallet_by_ens(id: &str) -> Result<TargetProcessedList, Error>
         address: ContractCategory::ENS.default_contract_address().unwrap(),
         chain: Chain::Ethereum,
         symbol: None,
+        name: None,
+        total_supply: None,
+        deployer: None,
+        verified: false,
     };
     let hold = Hold {
         uuid: Uuid::new_v4(),
@@ -239,6 +257,12 @@ async fn fetch_eth_wallet_by_ens(id: &str) -> Result<TargetProcessedList, Error>
         created_at: None,
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
     };
     let db = new_db_connection().await?;
     create_identity_to_contract_record(&db, &from, &to, &hold).await?;
@@ -5,13 +5,18 @@ use crate::{
     config::C,
     error::Error,
     graph::{
-        edge::{hold::Hold, resolve::DomainNameSystem, Resolve},
+        checkpoint::Checkpoint,
+        connect_domain_resolve, create_domain_own_record,
+        edge::{hold::Hold, resolve::DomainNameSystem, Own, Resolve},
         new_db_connection,
-        vertex::Identity,
+        vertex::{
+            contract::{Chain, ContractCategory},
+            Contract, Domain, Identity,
+        },
         Edge, Vertex,
     },
     upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList},
-    util::naive_now,
+    util::{ipfs_to_http, naive_now},
 };
 use aragog::DatabaseConnection;
 use async_trait::async_trait;
@@ -30,7 +35,7 @@ mod queries {
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(graphql_type = "Query", argument_struct = "ProfileQueryArguments")]
     pub struct ProfileQuery {
-        #[arguments(request = SingleProfileQueryRequest { handle: args.request.handle.clone() })]
+        #[arguments(request = SingleProfileQueryRequest { handle: args.request.handle.clone(), profile_id: args.request.profile_id.clone() })]
         pub profile: Option<Profile>,
     }
 
@@ -44,6 +49,28 @@ mod queries {
         pub name: Option<String>,
         pub metadata: Option<String>,
         pub owned_by: String,
+        pub picture: Option<ProfileMedia>,
+    }
+
+    #[derive(cynic::InlineFragments, Debug)]
+    pub enum ProfileMedia {
+        NftImage(NftImage),
+        MediaSet(MediaSet),
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    pub struct NftImage {
+        pub uri: String,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    pub struct MediaSet {
+        pub original: Media,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    pub struct Media {
+        pub url: String,
     }
 
     #[derive(cynic::FragmentArguments, Debug)]
@@ -54,6 +81,7 @@ mod queries {
     #[derive(cynic::InputObject, Debug)]
     pub struct SingleProfileQueryRequest {
         pub handle: Option<String>,
+        pub profile_id: Option<String>,
     }
 
     #[derive(cynic::FragmentArguments, Debug)]
@@ -64,18 +92,25 @@ mod queries {
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(graphql_type = "Query", argument_struct = "ProfilesQueryArguments")]
     pub struct ProfilesQuery {
-        #[arguments(request = ProfileQueryRequest {owned_by:args.request.owned_by.clone() })]
+        #[arguments(request = ProfileQueryRequest { owned_by: args.request.owned_by.clone(), cursor: args.request.cursor.clone() })]
         pub profiles: PaginatedProfileResult,
     }
 
     #[derive(cynic::QueryFragment, Debug)]
     pub struct PaginatedProfileResult {
         pub items: Vec<Profile>,
+        pub page_info: PaginatedResultInfo,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    pub struct PaginatedResultInfo {
+        pub next: Option<String>,
     }
 
     #[derive(cynic::InputObject, Debug)]
     pub struct ProfileQueryRequest {
         pub owned_by: Option<Vec<String>>,
+        pub cursor: Option<String>,
     }
 
     #[derive(cynic::Scalar, Debug, Clone)]
@@ -97,12 +132,24 @@ mod queries {
     #[derive(cynic::Scalar, Debug, Clone)]
     pub struct LimitScalar(pub String);
     cynic::impl_scalar!(String, schema::LimitScalar);
+
+    #[derive(cynic::Scalar, Debug, Clone)]
+    pub struct Cursor(pub String);
+    cynic::impl_scalar!(String, schema::Cursor);
 }
 
 mod schema {
     cynic::use_schema!("src/upstream/lens/schema.graphql");
 }
 
+/// Lens Hub, where every Lens profile is minted as an ERC721 token.
+/// https://docs.lens.xyz/docs/deployed-contract-addresses
+pub const LENS_HUB_ADDRESS: &str = "0xdb46d1dc155634fbc732f92e853b10b288ad5a1d";
+
+/// Stop paginating after this many pages, in case upstream loops.
+/// The next fetch of the same address resumes from where it stops.
+const MAX_PAGES: usize = 50;
+
 pub struct Lens {}
 
 #[async_trait]
@@ -112,15 +159,20 @@ impl Fetcher for Lens {
             return Ok(vec![]);
         }
 
-        match target.platform()? {
-            Platform::Ethereum => fetch_by_addr(target).await,
-            Platform::Lens => fetch_by_lens_profile(target).await,
+        match target {
+            Target::Identity(Platform::Ethereum, _) => fetch_by_addr(target).await,
+            Target::Identity(Platform::Lens, _) => fetch_by_lens_profile(target).await,
+            Target::NFT(_, _, _, token_id) => fetch_by_token_id(token_id).await,
             _ => Ok(vec![]),
         }
     }
 
     fn can_fetch(target: &Target) -> bool {
         target.in_platform_supported(vec![Platform::Ethereum, Platform::Lens])
+            || (target.in_nft_supported(vec![ContractCategory::ERC721], vec![Chain::Polygon])
+                && target
+                    .nft_address()
+                    .map_or(false, |address| address.to_lowercase() == LENS_HUB_ADDRESS))
     }
 }
 
@@ -128,45 +180,115 @@ impl Fetcher for Lens {
 async fn fetch_by_addr(target: &Target) -> Result<TargetProcessedList, Error> {
     use queries::*;
 
-    let operation = ProfilesQuery::build(ProfilesQueryArguments {
-        request: ProfileQueryRequest {
-            owned_by: Some(vec![target.identity()?]),
-        },
-    });
-
-    let response = surf::post(C.upstream.lens_api.url.clone())
-        .run_graphql(operation)
-        .await;
-
-    if response.is_err() {
+    let db = new_db_connection().await?;
+    let checkpoint = format!("lens:{}", target.identity()?.to_lowercase());
+    let mut cursor = Checkpoint::load_cursor(&db, &checkpoint).await?;
+    let mut profiles: Vec<Profile> = vec![];
+    let mut is_last = false;
+    let mut capped = true;
+    for _ in 0..MAX_PAGES {
+        let operation = ProfilesQuery::build(ProfilesQueryArguments {
+            request: ProfileQueryRequest {
+                owned_by: Some(vec![target.identity()?]),
+                cursor: cursor.clone(),
+            },
+        });
+
+        let response = surf::post(C.upstream.lens_api.url.clone())
+            .run_graphql(operation)
+            .await;
+
+        if response.is_err() {
+            warn!(
+                "Lens target {} | Failed to fetch: {}",
+                target,
+                response.unwrap_err(),
+            );
+            capped = false;
+            break;
+        }
+        let page = match response.unwrap().data {
+            Some(data) => data.profiles,
+            None => {
+                is_last = true;
+                break;
+            }
+        };
+        is_last =
+            page.items.is_empty() || page.page_info.next.is_none() || page.page_info.next == cursor;
+        profiles.extend(page.items);
+        if is_last {
+            break;
+        }
+        cursor = page.page_info.next;
+    }
+    if is_last {
+        cursor = None;
+    } else if capped {
         warn!(
-            "Lens target {} | Failed to fetch: {}",
+            "Lens target {} | Stopped at {} pages, resuming from cursor {} next time",
             target,
-            response.unwrap_err(),
+            MAX_PAGES,
+            cursor.as_deref().unwrap_or_default(),
         );
-        return Ok(vec![]);
     }
-    let data = response.unwrap().data.unwrap().profiles.items;
-    if data.len() == 0 {
+    // On a failure, the next fetch retries the page it failed at.
+    Checkpoint::save_cursor(&db, &checkpoint, cursor.as_deref()).await?;
+
+    if profiles.len() == 0 {
         info!("Lens profile {} | No result", target);
         return Ok(vec![]);
     }
-    let db = new_db_connection().await?;
-    for profile in data.into_iter() {
-        save_profile(&db, &profile).await?;
+    for profile in profiles.iter() {
+        save_profile(&db, profile).await?;
     }
     // there is no other upstream can get lens protocol
     Ok(vec![])
 }
 
 async fn fetch_by_lens_profile(target: &Target) -> Result<TargetProcessedList, Error> {
-    use queries::*;
-
-    let operation = ProfileQuery::build(ProfileQueryArguments {
-        request: SingleProfileQueryRequest {
+    fetch_single_profile(
+        target,
+        queries::SingleProfileQueryRequest {
             handle: Some(target.identity()?),
+            profile_id: None,
+        },
+    )
+    .await
+}
+
+/// Lens Hub NFT token ID is the profile ID in decimal.
+async fn fetch_by_token_id(token_id: &str) -> Result<TargetProcessedList, Error> {
+    let profile_id = match token_id_to_profile_id(token_id) {
+        Some(profile_id) => profile_id,
+        None => {
+            warn!("Lens | Invalid Lens Hub token ID {}", token_id);
+            return Ok(vec![]);
+        }
+    };
+    let target = Target::NFT(
+        Chain::Polygon,
+        ContractCategory::ERC721,
+        LENS_HUB_ADDRESS.to_string(),
+        token_id.to_string(),
+    );
+    fetch_single_profile(
+        &target,
+        queries::SingleProfileQueryRequest {
+            handle: None,
+            profile_id: Some(profile_id),
         },
-    });
+    )
+    .await
+}
+
+async fn fetch_single_profile(
+    target: &Target,
+    request: queries::SingleProfileQueryRequest,
+) -> Result<TargetProcessedList, Error> {
+    use queries::*;
+
+    let operation = ProfileQuery::build(ProfileQueryArguments { request });
 
     let response = surf::post(C.upstream.lens_api.url.clone())
         .run_graphql(operation)
@@ -181,7 +303,7 @@ async fn fetch_by_lens_profile(target: &Target) -> Result<TargetProcessedList, E
         return Ok(vec![]);
     }
 
-    let data: Option<Profile> = response.unwrap().data.unwrap().profile;
+    let data: Option<Profile> = response.unwrap().data.and_then(|d| d.profile);
     if data.is_none() {
         info!("Lens profile {} | No result", target);
         return Ok(vec![]);
@@ -197,6 +319,10 @@ async fn fetch_by_lens_profile(target: &Target) -> Result<TargetProcessedList, E
     )])
 }
 
+/// Save a Lens profile as:
+/// - `Hold`: wallet -> Lens identity, and wallet -> Lens Hub contract (profile NFT).
+/// - `Resolve`: Lens identity -> wallet. Also wallet -> Lens identity if it is the default profile.
+/// - `Own` / `ResolvesTo` / `ReverseOf` of the handle as a `Domain` accordingly.
 async fn save_profile(db: &DatabaseConnection, profile: &Profile) -> Result<(), Error> {
     let from: Identity = Identity {
         uuid: Some(Uuid::new_v4()),
@@ -208,6 +334,9 @@ async fn save_profile(db: &DatabaseConnection, profile: &Profile) -> Result<(),
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        // Lens profiles are minted on Polygon.
+        chains: vec![Chain::Polygon],
+        is_contract: None,
     };
 
     let to: Identity = Identity {
@@ -217,9 +346,11 @@ async fn save_profile(db: &DatabaseConnection, profile: &Profile) -> Result<(),
         created_at: None,
         display_name: profile.name.clone(),
         added_at: naive_now(),
-        avatar_url: profile.metadata.clone(),
+        avatar_url: picture_url(profile),
         profile_url: Some("https://lenster.xyz/u/".to_owned() + &profile.handle.clone()),
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
 
     let hold: Hold = Hold {
@@ -230,21 +361,111 @@ async fn save_profile(db: &DatabaseConnection, profile: &Profile) -> Result<(),
         created_at: None,
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
     };
     let from_record = from.create_or_update(db).await?;
     let to_record = to.create_or_update(db).await?;
     hold.connect(db, &from_record, &to_record).await?;
 
-    if profile.is_default {
-        let resolve: Resolve = Resolve {
+    let domain = Domain {
+        token_id: profile_id_to_token_id(&profile.id),
+        ..Domain::new(DomainNameSystem::Lens, &profile.handle)
+    };
+    let own = Own {
+        source: DataSource::Lens,
+        fetcher: DataFetcher::RelationService,
+        ..Default::default()
+    };
+    create_domain_own_record(db, &from_record, &domain, &own).await?;
+
+    if let Some(token_id) = profile_id_to_token_id(&profile.id) {
+        let contract: Contract = Contract {
+            uuid: Uuid::new_v4(),
+            category: ContractCategory::ERC721,
+            address: LENS_HUB_ADDRESS.to_string(),
+            chain: Chain::Polygon,
+            symbol: Some("LPP".to_string()),
+            name: Some("Lens Protocol Profiles".to_string()),
+            total_supply: None,
+            deployer: None,
+            verified: true,
+            updated_at: naive_now(),
+        };
+        let nft_hold: Hold = Hold {
             uuid: Uuid::new_v4(),
             source: DataSource::Lens,
-            system: DomainNameSystem::Lens,
-            name: profile.handle.clone(),
-            fetcher: DataFetcher::RelationService,
+            transaction: None,
+            id: token_id,
+            created_at: None,
             updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            event_id: None,
+            event_name: None,
+            ended_at: None,
+            expired_at: None,
+            amount: None,
+            decimals: None,
         };
-        resolve.connect(db, &to_record, &from_record).await?;
+        let contract_record = contract.create_or_update(db).await?;
+        nft_hold.connect(db, &from_record, &contract_record).await?;
+    }
+
+    // 'regular' resolution: handle -> owner.
+    let resolve = lens_resolve(&profile.handle);
+    resolve.connect(db, &to_record, &from_record).await?;
+    connect_domain_resolve(db, &to_record, &from_record, &resolve).await?;
+    if profile.is_default {
+        // 'reverse' resolution: owner -> default handle.
+        let reverse = lens_resolve(&profile.handle);
+        reverse.connect(db, &from_record, &to_record).await?;
+        connect_domain_resolve(db, &from_record, &to_record, &reverse).await?;
     }
     Ok(())
 }
+
+fn lens_resolve(handle: &str) -> Resolve {
+    Resolve {
+        uuid: Uuid::new_v4(),
+        source: DataSource::Lens,
+        system: DomainNameSystem::Lens,
+        name: handle.to_string(),
+        fetcher: DataFetcher::RelationService,
+        updated_at: naive_now(),
+    }
+}
+
+/// Picture of a profile, either an NFT image or an uploaded media.
+fn picture_url(profile: &Profile) -> Option<String> {
+    use queries::ProfileMedia;
+
+    let uri = match profile.picture.as_ref()? {
+        ProfileMedia::NftImage(image) => &image.uri,
+        ProfileMedia::MediaSet(media) => &media.original.url,
+    };
+    Some(ipfs_to_http(uri))
+}
+
+/// `0x01a4` => `420`
+fn profile_id_to_token_id(profile_id: &str) -> Option<String> {
+    u128::from_str_radix(profile_id.trim_start_matches("0x"), 16)
+        .ok()
+        .map(|id| id.to_string())
+}
+
+/// `420` => `0x01a4`
+fn token_id_to_profile_id(token_id: &str) -> Option<String> {
+    token_id.parse::<u128>().ok().map(|id| {
+        let hex = format!("{:x}", id);
+        // Lens pads profile ID to even length.
+        if hex.len() % 2 == 1 {
+            format!("0x0{}", hex)
+        } else {
+            format!("0x{}", hex)
+        }
+    })
+}
@@ -7,9 +7,39 @@ use crate::{
         vertex::Identity,
         vertex::{contract::ContractCategory, Contract},
     },
-    upstream::{lens::Lens, DataFetcher, DataSource, Fetcher, Platform, Target},
+    upstream::{
+        lens::{profile_id_to_token_id, token_id_to_profile_id, Lens, LENS_HUB_ADDRESS},
+        DataFetcher, DataSource, Fetcher, Platform, Target,
+    },
 };
 
+#[test]
+fn test_profile_id_token_id() {
+    assert_eq!(profile_id_to_token_id("0x05"), Some("5".to_string()));
+    assert_eq!(profile_id_to_token_id("0x01a4"), Some("420".to_string()));
+    assert_eq!(token_id_to_profile_id("5"), Some("0x05".to_string()));
+    assert_eq!(token_id_to_profile_id("420"), Some("0x01a4".to_string()));
+    assert_eq!(token_id_to_profile_id("stani.lens"), None);
+}
+
+#[test]
+fn test_can_fetch() {
+    let lens_hub = Target::NFT(
+        Chain::Polygon,
+        ContractCategory::ERC721,
+        LENS_HUB_ADDRESS.to_uppercase().replace("0X", "0x"),
+        "5".into(),
+    );
+    assert!(Lens::can_fetch(&lens_hub));
+    let other = Target::NFT(
+        Chain::Polygon,
+        ContractCategory::ERC721,
+        "0x0000000000000000000000000000000000000000".into(),
+        "5".into(),
+    );
+    assert!(!Lens::can_fetch(&other));
+}
+
 #[tokio::test]
 async fn test_fetch_by_lens_profile() -> Result<(), Error> {
     let db = new_db_connection().await?;
@@ -39,6 +69,32 @@ async fn test_fetch_by_addrs() -> Result<(), Error> {
     Identity::find_by_platform_identity(&db, &target.platform()?, &target.identity()?)
         .await?
         .expect("Record not found");
+    Contract::find_by_chain_address(&db, &Chain::Polygon, LENS_HUB_ADDRESS)
+        .await?
+        .expect("Lens Hub contract not found");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_by_token_id() -> Result<(), Error> {
+    let db = new_db_connection().await?;
+
+    // stani.lens
+    let target = Target::NFT(
+        Chain::Polygon,
+        ContractCategory::ERC721,
+        LENS_HUB_ADDRESS.to_string(),
+        "5".into(),
+    );
+    let next = Lens::fetch(&target).await?;
+    assert_eq!(next.len(), 1);
+
+    let hold = Hold::find_by_id_chain_address(&db, "5", &Chain::Polygon, LENS_HUB_ADDRESS)
+        .await?
+        .expect("Hold record not found");
+    assert_eq!(hold.source, DataSource::Lens);
+    assert_eq!(hold.fetcher, DataFetcher::RelationService);
 
     Ok(())
 }
@@ -0,0 +1,494 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    config::{ConfigIndexedContract, C},
+    error::Error,
+    graph::{
+        checkpoint::Checkpoint,
+        create_identity_to_contract_record,
+        edge::Hold,
+        new_db_connection,
+        vertex::{
+            contract::{Chain, ContractCategory},
+            Contract, Identity, Vertex,
+        },
+    },
+    upstream::{
+        eth_rpc::{
+            self, address_to_word, data_words, hex_to_decimal, word_to_address, Log,
+            SELECTOR_BALANCE_OF_1155,
+        },
+        DataFetcher, DataSource, Platform,
+    },
+    util::{naive_now, timestamp_to_naive},
+};
+use aragog::DatabaseConnection;
+use chrono::NaiveDateTime;
+use http::StatusCode;
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// `Transfer(address indexed from, address indexed to, uint256 indexed tokenId)`
+pub const TOPIC_TRANSFER: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+/// `TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)`
+pub const TOPIC_TRANSFER_SINGLE: &str =
+    "0xc3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62";
+/// `TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)`
+pub const TOPIC_TRANSFER_BATCH: &str =
+    "0x4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb";
+/// `balanceOf(address)` of ERC-20
+const SELECTOR_BALANCE_OF_20: &str = "0x70a08231";
+/// `decimals()` of ERC-20
+const SELECTOR_DECIMALS: &str = "0x313ce567";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+const DEFAULT_REORG_DEPTH: u64 = 12;
+const DEFAULT_BATCH_SIZE: u64 = 2000;
+
+/// A token moved by a `Transfer` / `TransferSingle` / `TransferBatch` log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenTransfer {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub transaction: String,
+    pub from: String,
+    pub to: String,
+    /// Token ID in decimal. Empty for ERC-20, which has no token ID.
+    pub id: String,
+    /// Token ID as ABI encoded word, to call the contract with.
+    pub id_word: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IndexReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub transfers: usize,
+    /// Blocks re-indexed due to a reorg.
+    pub rolled_back: u64,
+}
+
+/// Where the indexer of a contract stopped, saved in `Checkpoint.snapshot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCursor {
+    pub number: u64,
+    pub hash: String,
+}
+
+/// Indexes all contracts in `upstream.log_indexer.contracts`.
+pub async fn index_all() -> Result<Vec<IndexReport>, Error> {
+    let db = new_db_connection().await?;
+    let config = &C.upstream.log_indexer;
+    let reorg_depth = if config.reorg_depth == 0 {
+        DEFAULT_REORG_DEPTH
+    } else {
+        config.reorg_depth
+    };
+    let batch_size = if config.batch_size == 0 {
+        DEFAULT_BATCH_SIZE
+    } else {
+        config.batch_size
+    };
+
+    let mut reports = vec![];
+    for contract in config.contracts.iter() {
+        match index_contract(&db, contract, reorg_depth, batch_size).await {
+            Ok(report) => {
+                info!(
+                    chain = contract.chain.to_string(),
+                    address = contract.address,
+                    from_block = report.from_block,
+                    to_block = report.to_block,
+                    transfers = report.transfers,
+                    rolled_back = report.rolled_back,
+                    "LogIndexer: contract indexed."
+                );
+                reports.push(report);
+            }
+            Err(err) => warn!(
+                "LogIndexer {} {} | Fail to index: {}",
+                contract.chain, contract.address, err
+            ),
+        }
+    }
+    Ok(reports)
+}
+
+/// Indexes transfer logs of `contract` since last checkpoint till the latest block.
+/// If the checkpointed block is no longer on the chain, last `reorg_depth` blocks
+/// are rolled back and indexed again.
+pub async fn index_contract(
+    db: &DatabaseConnection,
+    contract: &ConfigIndexedContract,
+    reorg_depth: u64,
+    batch_size: u64,
+) -> Result<IndexReport, Error> {
+    let rpc_url = rpc_url_of(contract)?;
+    let rpc = rpc_url.as_str();
+    let (name, total_supply) = eth_rpc::contract_metadata(rpc, &contract.address).await;
+    // Finding the deployer takes a few dozen calls, so only once.
+    let deployer =
+        match Contract::find_by_chain_address(db, &contract.chain, &contract.address).await? {
+            Some(found) if found.deployer.is_some() => found.deployer.clone(),
+            _ => eth_rpc::deployer_of(rpc, &contract.address)
+                .await
+                .unwrap_or_else(|err| {
+                    warn!(
+                        "LogIndexer {} | Fail to find deployer: {}",
+                        contract.address, err
+                    );
+                    None
+                }),
+        };
+    let contract_record = Contract {
+        name,
+        total_supply,
+        deployer,
+        ..to_contract(contract)
+    }
+    .create_or_update(db)
+    .await?;
+    let decimals = if contract.category.is_fungible() {
+        decimals_of(rpc, &contract.address).await?
+    } else {
+        None
+    };
+    let name = checkpoint_name(contract);
+    let checkpoint = Checkpoint::find_by_name(db, &name).await?;
+    let mut report = IndexReport::default();
+
+    let mut from_block = match checkpoint.as_ref().and_then(|c| parse_cursor(&c.snapshot)) {
+        None => contract.start_block,
+        Some(cursor) => {
+            let canonical = eth_rpc::get_block(rpc, cursor.number).await?;
+            if canonical.map(|b| b.hash.to_lowercase()) == Some(cursor.hash.to_lowercase()) {
+                cursor.number + 1
+            } else {
+                let rollback_from = (cursor.number + 1)
+                    .saturating_sub(reorg_depth)
+                    .max(contract.start_block);
+                warn!(
+                    "LogIndexer {} | Reorg detected at block {}, rolling back to {}",
+                    name, cursor.number, rollback_from
+                );
+                if let Some(since) = block_time(rpc, rollback_from).await? {
+                    Hold::rollback(db, &contract_record.0, &DataSource::RPC, since).await?;
+                }
+                report.rolled_back = cursor.number + 1 - rollback_from;
+                rollback_from
+            }
+        }
+    };
+    report.from_block = from_block;
+    report.to_block = from_block.saturating_sub(1);
+
+    let head = eth_rpc::block_number(rpc).await?;
+    let topics = [TOPIC_TRANSFER, TOPIC_TRANSFER_SINGLE, TOPIC_TRANSFER_BATCH];
+    let mut block_times: HashMap<u64, NaiveDateTime> = HashMap::new();
+    while from_block <= head {
+        let to_block = (from_block + batch_size - 1).min(head);
+        let logs = eth_rpc::get_logs(rpc, &contract.address, &topics, from_block, to_block).await?;
+        let mut transfers: Vec<TokenTransfer> = logs
+            .iter()
+            .filter(|log| !log.removed)
+            .flat_map(decode_log)
+            .collect();
+        transfers.sort_by_key(|t| (t.block_number, t.log_index));
+
+        for transfer in transfers.iter() {
+            let happened_at = match block_times.get(&transfer.block_number) {
+                Some(t) => *t,
+                None => {
+                    let t = block_time(rpc, transfer.block_number)
+                        .await?
+                        .ok_or(Error::NoResult)?;
+                    block_times.insert(transfer.block_number, t);
+                    t
+                }
+            };
+            save_transfer(
+                db,
+                rpc,
+                contract,
+                &contract_record.0,
+                transfer,
+                happened_at,
+                decimals,
+            )
+            .await?;
+        }
+        report.transfers += transfers.len();
+
+        let block = eth_rpc::get_block(rpc, to_block)
+            .await?
+            .ok_or(Error::NoResult)?;
+        let mut checkpoint = Checkpoint::new(&name);
+        checkpoint.snapshot = json!({"number": to_block, "hash": block.hash});
+        checkpoint.upsert(db).await?;
+
+        report.to_block = to_block;
+        from_block = to_block + 1;
+    }
+
+    Ok(report)
+}
+
+/// `rpc_url` of `contract`, or of its chain in the chain registry.
+fn rpc_url_of(contract: &ConfigIndexedContract) -> Result<String, Error> {
+    if !contract.rpc_url.is_empty() {
+        return Ok(contract.rpc_url.clone());
+    }
+    contract
+        .chain
+        .info()
+        .and_then(|info| info.rpc_url.clone())
+        .ok_or_else(|| {
+            Error::General(
+                format!("LogIndexer: No rpc_url configured for {}", contract.chain),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })
+}
+
+/// `log_indexer:{chain}:{address}`
+pub fn checkpoint_name(contract: &ConfigIndexedContract) -> String {
+    format!(
+        "log_indexer:{}:{}",
+        contract.chain,
+        contract.address.to_lowercase()
+    )
+}
+
+fn parse_cursor(snapshot: &serde_json::Value) -> Option<BlockCursor> {
+    Some(BlockCursor {
+        number: snapshot.get("number")?.as_u64()?,
+        hash: snapshot.get("hash")?.as_str()?.to_string(),
+    })
+}
+
+async fn block_time(rpc: &str, number: u64) -> Result<Option<NaiveDateTime>, Error> {
+    Ok(eth_rpc::get_block(rpc, number)
+        .await?
+        .and_then(|b| b.timestamp())
+        .map(|t| timestamp_to_naive(t, 0)))
+}
+
+fn to_contract(contract: &ConfigIndexedContract) -> Contract {
+    Contract {
+        uuid: Uuid::new_v4(),
+        category: contract.category,
+        address: contract.address.to_lowercase(),
+        chain: contract.chain,
+        symbol: contract.symbol.clone(),
+        name: None,
+        total_supply: None,
+        deployer: None,
+        verified: true,
+        updated_at: naive_now(),
+    }
+}
+
+/// Decodes an ERC-721 / ERC-20 `Transfer` or ERC-1155 `TransferSingle` / `TransferBatch` log.
+/// ERC-20 `Transfer` (with only 3 topics) gives a transfer without token ID.
+pub fn decode_log(log: &Log) -> Vec<TokenTransfer> {
+    let (block_number, log_index) = match (log.block_number(), log.log_index()) {
+        (Some(block_number), Some(log_index)) => (block_number, log_index),
+        _ => return vec![],
+    };
+    let topic = |i: usize| log.topics.get(i).map(|t| t.to_lowercase());
+    let transfer = |from: &str, to: &str, id_word: &str| -> Option<TokenTransfer> {
+        Some(TokenTransfer {
+            block_number,
+            log_index,
+            transaction: log.transaction_hash.to_lowercase(),
+            from: word_to_address(from)?,
+            to: word_to_address(to)?,
+            id: hex_to_decimal(id_word)?,
+            id_word: id_word.trim_start_matches("0x").to_lowercase(),
+        })
+    };
+    let words = data_words(&log.data);
+
+    match topic(0).as_deref() {
+        Some(TOPIC_TRANSFER) if log.topics.len() == 4 => {
+            transfer(&log.topics[1], &log.topics[2], &log.topics[3])
+                .into_iter()
+                .collect()
+        }
+        Some(TOPIC_TRANSFER) if log.topics.len() == 3 => {
+            transfer(&log.topics[1], &log.topics[2], "")
+                .map(|t| TokenTransfer {
+                    id: String::new(),
+                    ..t
+                })
+                .into_iter()
+                .collect()
+        }
+        Some(TOPIC_TRANSFER_SINGLE) if log.topics.len() == 4 && words.len() >= 2 => {
+            transfer(&log.topics[2], &log.topics[3], words[0])
+                .into_iter()
+                .collect()
+        }
+        Some(TOPIC_TRANSFER_BATCH) if log.topics.len() == 4 => {
+            // data: offset of ids, offset of values, ids.length, ids..., values.length, values...
+            let ids_at = match words
+                .first()
+                .and_then(|w| eth_rpc::parse_hex_u64(w))
+                .map(|offset| offset as usize / 32)
+            {
+                Some(at) if at < words.len() => at,
+                _ => return vec![],
+            };
+            let count = eth_rpc::parse_hex_u64(words[ids_at]).unwrap_or_default() as usize;
+            words
+                .iter()
+                .skip(ids_at + 1)
+                .take(count)
+                .filter_map(|id| transfer(&log.topics[2], &log.topics[3], id))
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// Opens the receiver's `Hold` and ends the sender's.
+/// For ERC-721 every other `Hold` of the token is ended, since it has only one owner.
+/// For ERC-1155 and ERC-20 balances of both sides after that block are saved as `Hold.amount`,
+/// and sender's `Hold` is ended only if it has no balance left.
+/// ERC-20 has no token ID, its `Hold.id` is the contract address.
+async fn save_transfer(
+    db: &DatabaseConnection,
+    rpc: &str,
+    contract: &ConfigIndexedContract,
+    contract_record: &aragog::DatabaseRecord<Contract>,
+    transfer: &TokenTransfer,
+    happened_at: NaiveDateTime,
+    decimals: Option<u8>,
+) -> Result<(), Error> {
+    let is_fungible = contract.category.is_fungible();
+    if transfer.id.is_empty() != is_fungible {
+        // i.e. an ERC-20 `Transfer` emitted by an ERC-721 contract.
+        return Ok(());
+    }
+    let has_balance = is_fungible || contract.category == ContractCategory::ERC1155;
+    let id = if is_fungible {
+        contract.address.to_lowercase()
+    } else {
+        transfer.id.clone()
+    };
+    let hold = |amount: Option<String>| Hold {
+        uuid: Uuid::new_v4(),
+        source: DataSource::RPC,
+        transaction: Some(transfer.transaction.clone()),
+        id: id.clone(),
+        created_at: Some(happened_at),
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount,
+        decimals,
+    };
+
+    let mut receiver_record = None;
+    if transfer.to != ZERO_ADDRESS {
+        let amount = if has_balance {
+            balance_of(rpc, contract, &transfer.to, transfer).await?
+        } else {
+            None
+        };
+        let (identity_record, _, _) = create_identity_to_contract_record(
+            db,
+            &ethereum_identity(&transfer.to, contract.chain),
+            &to_contract(contract),
+            &hold(amount),
+        )
+        .await?;
+        receiver_record = Some(identity_record);
+    }
+
+    if transfer.from == ZERO_ADDRESS {
+        return Ok(());
+    }
+    if !has_balance {
+        Hold::close(
+            db,
+            contract_record,
+            &id,
+            receiver_record.as_ref().map(|r| &r.0),
+            happened_at,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let balance = balance_of(rpc, contract, &transfer.from, transfer).await?;
+    let sender = ethereum_identity(&transfer.from, contract.chain);
+    match balance.as_deref() {
+        Some("0") => {
+            let sender = sender.create_or_update(db).await?;
+            Hold::close_of(db, &sender.0, contract_record, &id, happened_at).await?;
+        }
+        Some(_) => {
+            create_identity_to_contract_record(db, &sender, &to_contract(contract), &hold(balance))
+                .await?;
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// Balance of `owner` in `contract` right after the block of `transfer`, in decimal.
+/// For ERC-1155 it's the balance of the transferred token.
+async fn balance_of(
+    rpc: &str,
+    contract: &ConfigIndexedContract,
+    owner: &str,
+    transfer: &TokenTransfer,
+) -> Result<Option<String>, Error> {
+    let data = if contract.category.is_fungible() {
+        format!("{}{}", SELECTOR_BALANCE_OF_20, address_to_word(owner))
+    } else {
+        format!(
+            "{}{}{:0>64}",
+            SELECTOR_BALANCE_OF_1155,
+            address_to_word(owner),
+            transfer.id_word
+        )
+    };
+    let balance =
+        eth_rpc::eth_call(rpc, &contract.address, &data, Some(transfer.block_number)).await?;
+    Ok(hex_to_decimal(&balance))
+}
+
+/// `decimals()` of an ERC-20 contract. `None` if it doesn't tell.
+async fn decimals_of(rpc: &str, address: &str) -> Result<Option<u8>, Error> {
+    let decimals = eth_rpc::eth_call(rpc, address, SELECTOR_DECIMALS, None).await?;
+    if decimals.trim_start_matches("0x").is_empty() {
+        return Ok(None);
+    }
+    Ok(eth_rpc::parse_hex_u64(&decimals).and_then(|d| u8::try_from(d).ok()))
+}
+
+fn ethereum_identity(address: &str, chain: Chain) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Ethereum,
+        identity: address.to_lowercase(),
+        created_at: None,
+        // Don't use ETH's wallet as display_name, use ENS reversed lookup instead.
+        display_name: None,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![chain],
+        is_contract: None,
+    }
+}
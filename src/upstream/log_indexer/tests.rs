@@ -0,0 +1,310 @@
+use crate::{
+    config::ConfigIndexedContract,
+    error::Error,
+    graph::edge::{Hold, HoldRecord},
+    graph::new_db_connection,
+    graph::vertex::{
+        contract::{Chain, ContractCategory},
+        Contract, Identity,
+    },
+    tests::spawn_rpc_server,
+    upstream::{
+        eth_rpc::{parse_hex_u64, to_hex, Log},
+        log_indexer::{
+            decode_log, index_contract, TOPIC_TRANSFER, TOPIC_TRANSFER_BATCH, TOPIC_TRANSFER_SINGLE,
+        },
+        Platform,
+    },
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+const ALICE: &str = "0x00000000000000000000000000000000000a11ce";
+const BOB: &str = "0x0000000000000000000000000000000000000b0b";
+const CAROL: &str = "0x00000000000000000000000000000000000ca201";
+const ZERO: &str = "0x0000000000000000000000000000000000000000";
+
+fn word(hex: &str) -> String {
+    format!("0x{:0>64}", hex.trim_start_matches("0x"))
+}
+
+fn log(block: u64, topics: Vec<String>, data: &str) -> Log {
+    serde_json::from_value(json!({
+        "topics": topics,
+        "data": data,
+        "blockNumber": to_hex(block),
+        "transactionHash": format!("0xtx{}", block),
+        "logIndex": "0x0"
+    }))
+    .unwrap()
+}
+
+fn transfer_log(block: u64, from: &str, to: &str, id: u64) -> Log {
+    log(
+        block,
+        vec![
+            TOPIC_TRANSFER.to_string(),
+            word(from),
+            word(to),
+            word(&format!("{:x}", id)),
+        ],
+        "0x",
+    )
+}
+
+#[test]
+fn test_decode_log() {
+    let transfers = decode_log(&transfer_log(3, ZERO, ALICE, 255));
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].from, ZERO);
+    assert_eq!(transfers[0].to, ALICE);
+    assert_eq!(transfers[0].id, "255");
+    assert_eq!(transfers[0].block_number, 3);
+
+    // ERC-20 `Transfer` has the amount in `data`, not a token ID.
+    let erc20 = log(
+        3,
+        vec![TOPIC_TRANSFER.to_string(), word(ALICE), word(BOB)],
+        &word("64"),
+    );
+    let transfers = decode_log(&erc20);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].to, BOB);
+    assert_eq!(transfers[0].id, "");
+
+    let single = log(
+        4,
+        vec![
+            TOPIC_TRANSFER_SINGLE.to_string(),
+            word(ALICE),
+            word(ALICE),
+            word(BOB),
+        ],
+        &format!("{}{}", word("2a"), &word("1")[2..]),
+    );
+    let transfers = decode_log(&single);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(
+        (transfers[0].to.as_str(), transfers[0].id.as_str()),
+        (BOB, "42")
+    );
+
+    // ids = [1, 2], values = [10, 20]
+    let data: String = ["40", "a0", "2", "1", "2", "2", "a", "14"]
+        .iter()
+        .map(|w| word(w)[2..].to_string())
+        .collect();
+    let batch = log(
+        5,
+        vec![
+            TOPIC_TRANSFER_BATCH.to_string(),
+            word(ALICE),
+            word(ALICE),
+            word(BOB),
+        ],
+        &format!("0x{}", data),
+    );
+    let ids: Vec<String> = decode_log(&batch).into_iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec!["1".to_string(), "2".to_string()]);
+}
+
+/// `eth_getLogs` result of `logs` within the block range in `params`.
+fn logs_result(logs: Vec<Log>, params: &Value) -> Value {
+    let from = parse_hex_u64(params[0]["fromBlock"].as_str().unwrap()).unwrap();
+    let to = parse_hex_u64(params[0]["toBlock"].as_str().unwrap()).unwrap();
+    let logs: Vec<Value> = logs
+        .into_iter()
+        .filter(|l| (from..=to).contains(&l.block_number().unwrap()))
+        .map(|l| {
+            json!({
+                "topics": l.topics,
+                "data": l.data,
+                "blockNumber": l.block_number,
+                "transactionHash": l.transaction_hash,
+                "logIndex": l.log_index,
+            })
+        })
+        .collect();
+    json!(logs)
+}
+
+/// A JSON-RPC node stand-in with 10 blocks.
+/// `fork` changes block hashes, and moves the token to Carol instead of Bob in block 9.
+fn spawn_node(fork: Arc<Mutex<bool>>) -> String {
+    spawn_rpc_server(move |method, params| {
+        let forked = *fork.lock().unwrap();
+        Ok(match method {
+            "eth_blockNumber" => json!(to_hex(10)),
+            "eth_getBlockByNumber" => {
+                let number = parse_hex_u64(params[0].as_str().unwrap()).unwrap();
+                let hash = if forked && number >= 9 {
+                    format!("0xforked{}", number)
+                } else {
+                    format!("0xblock{}", number)
+                };
+                json!({
+                    "number": to_hex(number),
+                    "hash": hash,
+                    "timestamp": to_hex(1_600_000_000 + number * 12),
+                })
+            }
+            "eth_getLogs" => {
+                let receiver = if forked { CAROL } else { BOB };
+                let logs = vec![
+                    transfer_log(3, ZERO, ALICE, 7),
+                    transfer_log(9, ALICE, receiver, 7),
+                ];
+                logs_result(logs, params)
+            }
+            _ => Value::Null,
+        })
+    })
+}
+
+async fn owner_of(
+    contract: &ConfigIndexedContract,
+    holder: &str,
+) -> Result<Option<HoldRecord>, Error> {
+    let db = new_db_connection().await?;
+    let contract = Contract::find_by_chain_address(&db, &contract.chain, &contract.address)
+        .await?
+        .expect("Contract should be saved");
+    let identity =
+        match Identity::find_by_platform_identity(&db, &Platform::Ethereum, holder).await? {
+            Some(identity) => identity,
+            None => return Ok(None),
+        };
+    Hold::find_by_from_to_id(&db, &identity, &contract, "7").await
+}
+
+#[tokio::test]
+async fn test_index_contract_with_reorg() -> Result<(), Error> {
+    let fork = Arc::new(Mutex::new(false));
+    let contract = ConfigIndexedContract {
+        chain: Chain::Ethereum,
+        address: format!("0x{:0>40}", &Uuid::new_v4().simple().to_string()[..20]),
+        category: ContractCategory::ERC721,
+        symbol: Some("TEST".into()),
+        rpc_url: spawn_node(fork.clone()),
+        start_block: 1,
+    };
+    let db = new_db_connection().await?;
+
+    let report = index_contract(&db, &contract, 3, 4).await?;
+    assert_eq!((report.from_block, report.to_block), (1, 10));
+    assert_eq!(report.transfers, 2);
+    assert_eq!(report.rolled_back, 0);
+    let alice = owner_of(&contract, ALICE).await?.expect("Alice held it");
+    assert!(alice.ended_at.is_some());
+    assert_eq!(alice.transaction, Some("0xtx3".to_string()));
+    let bob = owner_of(&contract, BOB).await?.expect("Bob holds it");
+    assert!(bob.ended_at.is_none());
+
+    // Block 9 and 10 are replaced, where Alice sends the token to Carol instead.
+    *fork.lock().unwrap() = true;
+    let report = index_contract(&db, &contract, 3, 4).await?;
+    assert_eq!(report.rolled_back, 3);
+    assert_eq!(report.from_block, 8);
+    assert_eq!(report.transfers, 1);
+    assert!(owner_of(&contract, BOB).await?.is_none());
+    let carol = owner_of(&contract, CAROL).await?.expect("Carol holds it");
+    assert!(carol.ended_at.is_none());
+    assert!(owner_of(&contract, ALICE)
+        .await?
+        .unwrap()
+        .ended_at
+        .is_some());
+
+    // Nothing new.
+    let report = index_contract(&db, &contract, 3, 4).await?;
+    assert_eq!(report.transfers, 0);
+    Ok(())
+}
+
+fn erc20_transfer_log(block: u64, from: &str, to: &str, value: u64) -> Log {
+    log(
+        block,
+        vec![TOPIC_TRANSFER.to_string(), word(from), word(to)],
+        &word(&format!("{:x}", value)),
+    )
+}
+
+/// A JSON-RPC node stand-in of an ERC-20 token with 6 decimals.
+/// 100 tokens are minted to Alice in block 3, then she sends 40 to Bob in block 9.
+fn spawn_token_node() -> String {
+    spawn_rpc_server(|method, params| {
+        Ok(match method {
+            "eth_blockNumber" => json!(to_hex(10)),
+            "eth_getBlockByNumber" => {
+                let number = parse_hex_u64(params[0].as_str().unwrap()).unwrap();
+                json!({
+                    "number": to_hex(number),
+                    "hash": format!("0xblock{}", number),
+                    "timestamp": to_hex(1_600_000_000 + number * 12),
+                })
+            }
+            "eth_getLogs" => {
+                let logs = vec![
+                    erc20_transfer_log(3, ZERO, ALICE, 100_000_000),
+                    erc20_transfer_log(9, ALICE, BOB, 40_000_000),
+                ];
+                logs_result(logs, params)
+            }
+            "eth_call" => {
+                let data = params[0]["data"].as_str().unwrap();
+                let block = parse_hex_u64(params[1].as_str().unwrap_or_default()).unwrap_or(10);
+                let balance = if data == "0x313ce567" {
+                    6
+                } else if data == "0x18160ddd" {
+                    100_000_000
+                } else if data.ends_with(&ALICE[2..]) {
+                    if block < 9 {
+                        100_000_000
+                    } else {
+                        60_000_000
+                    }
+                } else if data.ends_with(&BOB[2..]) && block >= 9 {
+                    40_000_000
+                } else {
+                    0
+                };
+                json!(word(&format!("{:x}", balance)))
+            }
+            _ => Value::Null,
+        })
+    })
+}
+
+#[tokio::test]
+async fn test_index_erc20_balances() -> Result<(), Error> {
+    let contract = ConfigIndexedContract {
+        chain: Chain::Ethereum,
+        address: format!("0x{:0>40}", &Uuid::new_v4().simple().to_string()[..20]),
+        category: ContractCategory::ERC20,
+        symbol: Some("USDT".into()),
+        rpc_url: spawn_token_node(),
+        start_block: 1,
+    };
+    let db = new_db_connection().await?;
+    let report = index_contract(&db, &contract, 3, 4).await?;
+    assert_eq!(report.transfers, 2);
+
+    let token = Contract::find_by_chain_address(&db, &contract.chain, &contract.address)
+        .await?
+        .expect("Contract should be saved");
+    assert_eq!(token.total_supply.as_deref(), Some("100000000"));
+    assert!(token.verified);
+    for (holder, amount) in [(ALICE, "60000000"), (BOB, "40000000")] {
+        let identity = Identity::find_by_platform_identity(&db, &Platform::Ethereum, holder)
+            .await?
+            .expect("Holder should be saved");
+        let hold = Hold::find_by_from_to_id(&db, &identity, &token, &contract.address)
+            .await?
+            .expect("Holder should hold the token");
+        assert!(hold.ended_at.is_none());
+        assert_eq!(hold.amount.as_deref(), Some(amount));
+        assert_eq!(hold.decimals, Some(6));
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error::Error;
+use crate::graph::edge::Proof;
+use crate::graph::vertex::Identity;
+use crate::graph::{create_identity_to_identity_two_way_binding, new_db_connection, Vertex};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
+use crate::util::{make_client, naive_now, parse_body, request_with_timeout};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
+use hyper::{Body, Method};
+use serde::Deserialize;
+use tracing::debug;
+use uuid::Uuid;
+
+/// https://docs.joinmastodon.org/spec/webfinger/
+#[derive(Deserialize, Debug, Clone)]
+pub struct WebFingerResponse {
+    /// `acct:user@domain`
+    pub subject: String,
+    #[serde(default)]
+    pub links: Vec<WebFingerLink>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WebFingerLink {
+    pub rel: String,
+    #[serde(rename = "type")]
+    pub link_type: Option<String>,
+    pub href: Option<String>,
+}
+
+/// https://docs.joinmastodon.org/entities/Account/
+#[derive(Deserialize, Debug, Clone)]
+pub struct Account {
+    pub id: String,
+    pub username: String,
+    pub display_name: String,
+    pub url: String,
+    pub avatar: Option<String>,
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<AccountField>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountField {
+    pub name: String,
+    /// HTML, e.g. `<a href="https://github.com/user" rel="me">...</a>`
+    pub value: String,
+    /// Set only if the linked page links back with `rel="me"`.
+    pub verified_at: Option<String>,
+}
+
+/// Resolves `user@instance` through WebFinger, then turns verified (`rel="me"`)
+/// profile links into `Proof`s.
+pub struct Mastodon {}
+
+#[async_trait]
+impl Fetcher for Mastodon {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(_, acct) => fetch_by_acct(acct).await,
+            Target::NFT(_, _, _, _) => Ok(vec![]),
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Mastodon])
+    }
+}
+
+async fn fetch_by_acct(acct: &str) -> Result<TargetProcessedList, Error> {
+    let acct = acct.trim_start_matches('@').to_lowercase();
+    let (_, domain) = acct
+        .split_once('@')
+        .ok_or_else(|| Error::ParamError(format!("Mastodon: invalid account {}", acct)))?;
+
+    let webfinger = fetch_webfinger(&format!("https://{}", domain), &acct).await?;
+    let canonical = webfinger.subject.trim_start_matches("acct:").to_lowercase();
+    // Instance serving the API may differ from the domain in `acct`.
+    let api_host = webfinger
+        .links
+        .iter()
+        .find(|l| l.rel == "self")
+        .and_then(|l| l.href.as_ref())
+        .and_then(|href| href.parse::<http::Uri>().ok())
+        .and_then(|uri| uri.host().map(|h| h.to_string()))
+        .unwrap_or_else(|| domain.to_string());
+    let username = canonical.split('@').next().unwrap_or_default().to_string();
+    let account = fetch_account(&format!("https://{}", api_host), &username).await?;
+
+    let db = new_db_connection().await?;
+    let from: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Mastodon,
+        identity: canonical.clone(),
+        created_at: account.created_at.as_ref().and_then(|c| parse_iso(c)),
+        display_name: Some(if account.display_name.is_empty() {
+            account.username.clone()
+        } else {
+            account.display_name.clone()
+        }),
+        added_at: naive_now(),
+        avatar_url: account.avatar.clone(),
+        profile_url: Some(account.url.clone()),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+    from.create_or_update(&db).await?;
+
+    let mut next_targets: TargetProcessedList = vec![];
+    for (platform, identity, href, verified_at) in verified_links(&account).into_iter() {
+        let to: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: identity.clone(),
+            created_at: None,
+            display_name: Some(identity.clone()),
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: Some(href.clone()),
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let proof: Proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::Mastodon,
+            record_id: Some(href),
+            created_at: verified_at,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            deleted_at: None,
+        };
+        create_identity_to_identity_two_way_binding(&db, &from, &to, &proof).await?;
+        next_targets.push(Target::Identity(platform, identity));
+    }
+
+    Ok(next_targets)
+}
+
+/// `GET {base}/.well-known/webfinger?resource=acct:{acct}`
+async fn fetch_webfinger(base: &str, acct: &str) -> Result<WebFingerResponse, Error> {
+    request(&format!(
+        "{}/.well-known/webfinger?resource=acct:{}",
+        base, acct
+    ))
+    .await
+}
+
+/// `GET {base}/api/v1/accounts/lookup?acct={username}`
+async fn fetch_account(base: &str, username: &str) -> Result<Account, Error> {
+    request(&format!(
+        "{}/api/v1/accounts/lookup?acct={}",
+        base, username
+    ))
+    .await
+}
+
+async fn request<T>(uri: &str) -> Result<T, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let client = make_client();
+    let uri: http::Uri = uri
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("accept", "application/json")
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("Mastodon Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("Mastodon fetch | error: {:?}", err.to_string()))
+    })?;
+    if !resp.status().is_success() {
+        debug!("Mastodon fetch | status {}", resp.status());
+        return Err(Error::NoResult);
+    }
+
+    parse_body(&mut resp).await
+}
+
+/// Verified profile fields as `(platform, identity, link, verified_at)`.
+fn verified_links(account: &Account) -> Vec<(Platform, String, String, Option<NaiveDateTime>)> {
+    account
+        .fields
+        .iter()
+        .filter(|f| f.verified_at.is_some())
+        .filter_map(|f| {
+            let href = extract_href(&f.value)?;
+            let (platform, identity) = link_to_identity(&href)?;
+            let verified_at = f.verified_at.as_ref().and_then(|v| parse_iso(v));
+            Some((platform, identity, href, verified_at))
+        })
+        .collect()
+}
+
+/// Get the first `href="..."` in an HTML snippet.
+fn extract_href(html: &str) -> Option<String> {
+    let start = html.find("href=\"")? + "href=\"".len();
+    let end = html[start..].find('"')?;
+    Some(html[start..start + end].to_string())
+}
+
+/// Profile pages of known platforms become that platform's identity,
+/// any other website becomes `Identity(DNS, host)` if it's the root of the site.
+/// Other pages (e.g. `https://medium.com/@alice`) are dropped, as the site is not owned by the account.
+fn link_to_identity(href: &str) -> Option<(Platform, String)> {
+    let uri: http::Uri = href.parse().ok()?;
+    let host = uri.host()?.to_lowercase();
+    let host = host.trim_start_matches("www.");
+    let first_segment = uri
+        .path()
+        .split('/')
+        .find(|s| !s.is_empty())
+        .map(|s| s.to_lowercase());
+
+    match (host, first_segment) {
+        ("github.com", Some(user)) => Some((Platform::Github, user)),
+        ("twitter.com", Some(user)) | ("x.com", Some(user)) => Some((Platform::Twitter, user)),
+        (_, None) => Some((Platform::DNS, host.to_string())),
+        _ => None,
+    }
+}
+
+fn parse_iso(datetime: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(datetime)
+        .ok()
+        .map(|dt| dt.naive_utc())
+}
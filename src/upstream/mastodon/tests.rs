@@ -0,0 +1,76 @@
+use crate::{
+    error::Error,
+    tests::spawn_json_server,
+    upstream::{
+        mastodon::{fetch_account, fetch_webfinger, link_to_identity, verified_links},
+        Platform,
+    },
+};
+use serde_json::json;
+
+#[test]
+fn test_link_to_identity() {
+    assert_eq!(
+        link_to_identity("https://github.com/Nykma/"),
+        Some((Platform::Github, "nykma".to_string()))
+    );
+    assert_eq!(
+        link_to_identity("https://twitter.com/nykma"),
+        Some((Platform::Twitter, "nykma".to_string()))
+    );
+    assert_eq!(
+        link_to_identity("https://www.example.com/"),
+        Some((Platform::DNS, "example.com".to_string()))
+    );
+    assert_eq!(link_to_identity("https://www.example.com/about"), None);
+    assert_eq!(link_to_identity("https://medium.com/@alice"), None);
+    assert_eq!(link_to_identity("not a link"), None);
+}
+
+#[tokio::test]
+async fn test_fetch_webfinger_and_account() -> Result<(), Error> {
+    let base = spawn_json_server(vec![
+        (
+            "/.well-known/webfinger",
+            json!({
+                "subject": "acct:alice@example.social",
+                "links": [
+                    {"rel": "http://webfinger.net/rel/profile-page", "type": "text/html", "href": "https://example.social/@alice"},
+                    {"rel": "self", "type": "application/activity+json", "href": "https://example.social/users/alice"}
+                ]
+            }),
+        ),
+        (
+            "/api/v1/accounts/lookup",
+            json!({
+                "id": "109",
+                "username": "alice",
+                "acct": "alice",
+                "display_name": "Alice",
+                "url": "https://example.social/@alice",
+                "avatar": "https://example.social/avatars/alice.png",
+                "created_at": "2022-11-01T00:00:00.000Z",
+                "fields": [
+                    {"name": "GitHub", "value": "<a href=\"https://github.com/alice\" rel=\"nofollow noopener noreferrer me\" target=\"_blank\">github.com/alice</a>", "verified_at": "2022-11-02T00:00:00.000+00:00"},
+                    {"name": "Blog", "value": "<a href=\"https://alice.example.com\" rel=\"me\">alice.example.com</a>", "verified_at": "2022-11-03T00:00:00.000+00:00"},
+                    {"name": "Twitter", "value": "<a href=\"https://twitter.com/alice\" rel=\"me\">@alice</a>", "verified_at": null}
+                ]
+            }),
+        ),
+    ]);
+
+    let finger = fetch_webfinger(&base, "alice@example.social").await?;
+    assert_eq!(finger.subject, "acct:alice@example.social");
+
+    let account = fetch_account(&base, "alice").await?;
+    assert_eq!(account.display_name, "Alice");
+    let links = verified_links(&account);
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].0, Platform::Github);
+    assert_eq!(links[0].1, "alice");
+    assert_eq!(links[1].0, Platform::DNS);
+    assert_eq!(links[1].1, "alice.example.com");
+    assert!(links[1].3.is_some());
+
+    Ok(())
+}
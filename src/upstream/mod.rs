@@ -1,15 +1,25 @@
 // Upstreams
 mod aggregation;
+mod contract_wallet;
+mod crossbell;
+mod dns;
 mod dotbit;
 mod ens_reverse;
+mod eth_rpc;
 mod farcaster;
 mod keybase;
 mod knn3;
 mod lens;
+mod log_indexer;
+mod mastodon;
+mod nostr;
+mod poap;
 mod proof_client;
 mod rss3;
+mod sns;
 mod space_id;
 mod sybil_list;
+mod token_uri;
 mod unstoppable;
 
 #[cfg(test)]
@@ -24,11 +34,13 @@ use std::{
 
 use crate::{
     error::Error,
+    graph::arangopool::new_connection_pool,
     upstream::{
-        aggregation::Aggregation, dotbit::DotBit, ens_reverse::ENSReverseLookup,
-        farcaster::Farcaster, keybase::Keybase, knn3::Knn3, lens::Lens, proof_client::ProofClient,
-        rss3::Rss3, space_id::SpaceId, sybil_list::SybilList, the_graph::TheGraph,
-        unstoppable::UnstoppableDomains,
+        aggregation::Aggregation, contract_wallet::ContractWallet, crossbell::Crossbell, dns::DNS,
+        dotbit::DotBit, ens_reverse::ENSReverseLookup, farcaster::Farcaster, keybase::Keybase,
+        knn3::Knn3, lens::Lens, mastodon::Mastodon, nostr::Nostr, poap::POAP,
+        proof_client::ProofClient, rss3::Rss3, sns::SNS, space_id::SpaceId, sybil_list::SybilList,
+        the_graph::TheGraph, token_uri::TokenUri, unstoppable::UnstoppableDomains,
     },
     util::hashset_append,
 };
@@ -36,7 +48,13 @@ use async_trait::async_trait;
 use futures::{future::join_all, StreamExt};
 use tracing::{event, info, warn, Level};
 
-pub(crate) use types::{DataFetcher, DataSource, Platform, Target, TargetProcessedList};
+pub(crate) use farcaster::fid_by_username as farcaster_fid_by_username;
+pub(crate) use nostr::nip05_to_hex as nostr_nip05_to_hex;
+pub(crate) use space_id::tld_of as space_id_tld;
+pub(crate) use types::caip::is_valid_address;
+pub(crate) use types::{
+    AccountId, AssetId, ChainId, DataFetcher, DataSource, Platform, Target, TargetProcessedList,
+};
 
 lazy_static! {
     /// Global processing queue to prevent duplicated query. i.e. multiple same request from frontend.
@@ -127,7 +145,7 @@ pub async fn fetch_all(initial_target: Target) -> Result<(), Error> {
 /// Returns amount of identities just fetched for next iter.
 pub async fn fetch_one(target: &Target) -> Result<Vec<Target>, Error> {
     let mut up_next: TargetProcessedList = join_all(vec![
-        // Aggregation::fetch(target),
+        Aggregation::fetch(target),
         SybilList::fetch(target),
         Keybase::fetch(target),
         ProofClient::fetch(target),
@@ -140,6 +158,14 @@ pub async fn fetch_one(target: &Target) -> Result<Vec<Target>, Error> {
         Farcaster::fetch(target),
         SpaceId::fetch(target),
         Lens::fetch(target),
+        Crossbell::fetch(target),
+        SNS::fetch(target),
+        DNS::fetch(target),
+        Nostr::fetch(target),
+        Mastodon::fetch(target),
+        POAP::fetch(target),
+        ContractWallet::fetch(target),
+        TokenUri::fetch(target),
     ])
     .await
     .into_iter()
@@ -159,10 +185,36 @@ pub async fn fetch_one(target: &Target) -> Result<Vec<Target>, Error> {
 }
 
 /// Prefetch all prefetchable upstreams, e.g. SybilList.
+/// Also migrates Farcaster identities saved by username, a batch per run.
 pub async fn prefetch() -> Result<(), Error> {
     info!("Prefetching sybil_list ...");
-    sybil_list::prefetch().await?;
-    info!("Prefetch completed.");
+    let report = sybil_list::prefetch().await?;
+    info!(
+        total = report.total,
+        added = report.added,
+        delisted = report.delisted,
+        unchanged = report.unchanged,
+        "Prefetch completed."
+    );
+
+    let pool = new_connection_pool().await?;
+    match farcaster::migrate_username_identities(&pool).await {
+        Ok(migrated) if migrated > 0 => info!(migrated, "Farcaster identities migrated to fid."),
+        Ok(_) => {}
+        Err(err) => warn!("Fail to migrate Farcaster identities: {}", err),
+    }
+    Ok(())
+}
+
+/// Index transfer logs of contracts configured in `upstream.log_indexer`.
+pub async fn index_logs() -> Result<(), Error> {
+    let reports = log_indexer::index_all().await?;
+    let transfers: usize = reports.iter().map(|r| r.transfers).sum();
+    info!(
+        contracts = reports.len(),
+        transfers = transfers,
+        "Log indexing completed."
+    );
     Ok(())
 }
 
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error::Error;
+use crate::graph::edge::Proof;
+use crate::graph::vertex::Identity;
+use crate::graph::{create_identity_to_identity_two_way_binding, new_db_connection};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
+use crate::util::{make_client, naive_now, nostr_pubkey_to_hex, parse_body, request_with_timeout};
+use async_trait::async_trait;
+use hyper::{Body, Method};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+use uuid::Uuid;
+
+/// https://github.com/nostr-protocol/nips/blob/master/05.md
+#[derive(Deserialize, Debug, Clone)]
+pub struct Nip05Response {
+    /// `name => hex pubkey`
+    pub names: HashMap<String, String>,
+}
+
+/// Verifies NIP-05 identifiers (`name@domain`) by `https://{domain}/.well-known/nostr.json`.
+/// A verified identifier becomes a `Proof` between `Identity(DNS, domain)`
+/// and `Identity(Nostr, hex pubkey)`.
+pub struct Nostr {}
+
+#[async_trait]
+impl Fetcher for Nostr {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(Platform::Nostr, identity) => {
+                // Bare pubkeys cannot be looked up without a relay.
+                match identity.split_once('@') {
+                    Some((name, domain)) => fetch_by_nip05(name, domain).await,
+                    None => Ok(vec![]),
+                }
+            }
+            // Root identifier `_@domain` is displayed as `domain` in NIP-05.
+            Target::Identity(Platform::DNS, domain) => fetch_by_nip05("_", domain).await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Nostr, Platform::DNS])
+    }
+}
+
+async fn fetch_by_nip05(name: &str, domain: &str) -> Result<TargetProcessedList, Error> {
+    let name = name.to_lowercase();
+    let domain = domain.to_lowercase();
+    let pubkey = match fetch_nip05(&format!("https://{}", domain), &name).await? {
+        Some(pubkey) => pubkey,
+        None => return Ok(vec![]),
+    };
+    let nip05 = if name == "_" {
+        domain.clone()
+    } else {
+        format!("{}@{}", name, domain)
+    };
+
+    let db = new_db_connection().await?;
+    let dns: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::DNS,
+        identity: domain.clone(),
+        created_at: None,
+        display_name: Some(domain.clone()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: Some(format!("https://{}", domain)),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+    let nostr: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Nostr,
+        identity: pubkey.clone(),
+        created_at: None,
+        display_name: Some(nip05.clone()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    };
+    let proof: Proof = Proof {
+        uuid: Uuid::new_v4(),
+        source: DataSource::Nostr,
+        record_id: Some(nip05),
+        created_at: None,
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        deleted_at: None,
+    };
+    create_identity_to_identity_two_way_binding(&db, &dns, &nostr, &proof).await?;
+
+    Ok(vec![
+        Target::Identity(Platform::DNS, domain),
+        Target::Identity(Platform::Nostr, pubkey),
+    ])
+}
+
+/// Hex pubkey of NIP-05 identifier `name@domain` (`domain` for `_@domain`).
+/// `None` if it is not listed.
+pub async fn nip05_to_hex(nip05: &str) -> Result<Option<String>, Error> {
+    let (name, domain) = nip05.split_once('@').unwrap_or(("_", nip05));
+    fetch_nip05(
+        &format!("https://{}", domain.to_lowercase()),
+        &name.to_lowercase(),
+    )
+    .await
+}
+
+/// Look up `name` in `{base}/.well-known/nostr.json`.
+/// Returns hex pubkey of `name` if it is listed.
+async fn fetch_nip05(base: &str, name: &str) -> Result<Option<String>, Error> {
+    let client = make_client();
+    let uri: http::Uri = format!("{}/.well-known/nostr.json?name={}", base, name)
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("Nostr Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("Nostr fetch | error: {:?}", err.to_string()))
+    })?;
+    if !resp.status().is_success() {
+        debug!("Nostr fetch {} | status {}", base, resp.status());
+        return Ok(None);
+    }
+
+    let body: Nip05Response = parse_body(&mut resp).await?;
+    Ok(body
+        .names
+        .into_iter()
+        .find(|(n, _)| n.to_lowercase() == name)
+        .and_then(|(_, pubkey)| nostr_pubkey_to_hex(&pubkey)))
+}
@@ -0,0 +1,22 @@
+use crate::{error::Error, tests::spawn_json_server, upstream::nostr::fetch_nip05};
+use serde_json::json;
+
+const PUBKEY: &str = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d";
+
+#[tokio::test]
+async fn test_fetch_nip05() -> Result<(), Error> {
+    let base = spawn_json_server(vec![(
+        "/.well-known/nostr.json",
+        json!({
+            "names": { "Bob": PUBKEY, "_": PUBKEY, "broken": "not-a-pubkey" },
+            "relays": {}
+        }),
+    )]);
+
+    assert_eq!(fetch_nip05(&base, "bob").await?, Some(PUBKEY.to_string()));
+    assert_eq!(fetch_nip05(&base, "_").await?, Some(PUBKEY.to_string()));
+    assert_eq!(fetch_nip05(&base, "broken").await?, None);
+    assert_eq!(fetch_nip05(&base, "alice").await?, None);
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod tests;
+
+use crate::config::C;
+use crate::error::Error;
+use crate::graph::edge::Hold;
+use crate::graph::vertex::{
+    contract::{Chain, ContractCategory},
+    Contract, Identity,
+};
+use crate::graph::{create_identity_to_contract_record, new_db_connection};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
+use crate::util::{make_client, naive_now, parse_body, request_with_timeout};
+use aragog::DatabaseConnection;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use hyper::{Body, Method};
+use serde::Deserialize;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// https://documentation.poap.tech/reference/getactionsscan-5
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct POAPToken {
+    pub event: POAPEvent,
+    pub token_id: String,
+    pub owner: String,
+    /// `"xdai"` or `"mainnet"`. Only given by `/actions/scan`.
+    pub chain: Option<String>,
+    /// `"Layer1"` or `"Layer2"`. Only given by `/token/{tokenId}`.
+    pub layer: Option<String>,
+    /// `"2021-06-24 13:17:13"`
+    pub created: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct POAPEvent {
+    pub id: i64,
+    pub fancy_id: Option<String>,
+    pub name: String,
+    pub image_url: Option<String>,
+}
+
+impl POAPToken {
+    /// POAP tokens are minted on Gnosis (xDai) by default,
+    /// and can be migrated to Ethereum mainnet.
+    pub fn chain(&self) -> Option<Chain> {
+        let chain = self
+            .chain
+            .as_deref()
+            .or(self.layer.as_deref())
+            .unwrap_or("xdai");
+        match chain.to_lowercase().as_str() {
+            "xdai" | "gnosis" | "layer2" => Some(Chain::Gnosis),
+            "mainnet" | "homestead" | "ethereum" | "layer1" => Some(Chain::Ethereum),
+            _ => None,
+        }
+    }
+
+    pub fn created_at(&self) -> Option<NaiveDateTime> {
+        self.created
+            .as_ref()
+            .and_then(|c| NaiveDateTime::parse_from_str(c, "%Y-%m-%d %H:%M:%S").ok())
+    }
+}
+
+/// Lists POAP tokens of an Ethereum wallet through POAP API.
+/// Each token becomes a `Hold` from wallet to the POAP contract on the chain the token lives,
+/// with event ID / name recorded in the `Hold`.
+pub struct POAP {}
+
+#[async_trait]
+impl Fetcher for POAP {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(_, address) => {
+                fetch_tokens_by_address(&C.upstream.poap_api.url, address).await
+            }
+            Target::NFT(_, _, _, token_id) => {
+                fetch_owner_by_token(&C.upstream.poap_api.url, token_id).await
+            }
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Ethereum])
+            || target.in_nft_supported(
+                vec![ContractCategory::POAP],
+                vec![Chain::Gnosis, Chain::Ethereum],
+            )
+    }
+}
+
+async fn fetch_tokens_by_address(base: &str, address: &str) -> Result<TargetProcessedList, Error> {
+    let address = address.to_lowercase();
+    let tokens = fetch_scan(base, &address).await?;
+    if tokens.is_empty() {
+        debug!("POAP {} | No token found", address);
+        return Ok(vec![]);
+    }
+
+    let db = new_db_connection().await?;
+    for token in tokens.iter() {
+        save_token(&db, token).await?;
+    }
+
+    // Owner of every token is `address` itself, nothing new to fetch.
+    Ok(vec![])
+}
+
+async fn fetch_owner_by_token(base: &str, token_id: &str) -> Result<TargetProcessedList, Error> {
+    let token = match fetch_token(base, token_id).await? {
+        Some(token) => token,
+        None => return Ok(vec![]),
+    };
+
+    let db = new_db_connection().await?;
+    save_token(&db, &token).await?;
+
+    Ok(vec![Target::Identity(
+        Platform::Ethereum,
+        token.owner.to_lowercase(),
+    )])
+}
+
+async fn save_token(db: &DatabaseConnection, token: &POAPToken) -> Result<(), Error> {
+    let chain = match token.chain() {
+        Some(chain) => chain,
+        None => {
+            warn!(
+                "POAP token {} | Unknown chain {:?}",
+                token.token_id, token.chain
+            );
+            return Ok(());
+        }
+    };
+
+    let owner: Identity = Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Ethereum,
+        identity: token.owner.to_lowercase(),
+        created_at: None,
+        display_name: None,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![chain],
+        is_contract: None,
+    };
+    let contract: Contract = Contract {
+        uuid: Uuid::new_v4(),
+        category: ContractCategory::POAP,
+        address: ContractCategory::POAP.default_contract_address().unwrap(),
+        chain,
+        symbol: Some("POAP".to_string()),
+        name: Some("POAP".to_string()),
+        total_supply: None,
+        deployer: None,
+        verified: true,
+        updated_at: naive_now(),
+    };
+    let hold: Hold = Hold {
+        uuid: Uuid::new_v4(),
+        source: DataSource::POAP,
+        transaction: None,
+        id: token.token_id.clone(),
+        created_at: token.created_at(),
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        event_id: Some(token.event.id.to_string()),
+        event_name: Some(token.event.name.clone()),
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
+    };
+    create_identity_to_contract_record(db, &owner, &contract, &hold).await?;
+
+    Ok(())
+}
+
+/// `GET {base}/actions/scan/{address}`
+async fn fetch_scan(base: &str, address: &str) -> Result<Vec<POAPToken>, Error> {
+    match request(&format!("{}/actions/scan/{}", base, address)).await? {
+        Some(tokens) => Ok(tokens),
+        None => Ok(vec![]),
+    }
+}
+
+/// `GET {base}/token/{token_id}`
+async fn fetch_token(base: &str, token_id: &str) -> Result<Option<POAPToken>, Error> {
+    request(&format!("{}/token/{}", base, token_id)).await
+}
+
+/// Returns `None` if POAP API responds `404`.
+async fn request<T>(uri: &str) -> Result<Option<T>, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let client = make_client();
+    let uri: http::Uri = uri
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("accept", "application/json")
+        .header("X-API-Key", C.upstream.poap_api.api_key.as_str())
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("POAP Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("POAP fetch | error: {:?}", err.to_string()))
+    })?;
+    if resp.status() == hyper::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        warn!("POAP fetch error, status {}", resp.status());
+        return Err(Error::General(
+            format!("POAP Result Get Error: {}", resp.status()),
+            resp.status(),
+        ));
+    }
+
+    Ok(Some(parse_body(&mut resp).await?))
+}
@@ -0,0 +1,100 @@
+use crate::{
+    error::Error,
+    graph::{
+        edge::Hold,
+        new_db_connection,
+        vertex::contract::{Chain, ContractCategory},
+    },
+    tests::spawn_stub_server,
+    upstream::poap::{fetch_scan, fetch_token, fetch_tokens_by_address},
+};
+use serde_json::json;
+use uuid::Uuid;
+use warp::Filter;
+
+const OWNER: &str = "0x934b510d4c9103e6a87aef13b816fb080286d649";
+
+#[tokio::test]
+async fn test_fetch_scan() -> Result<(), Error> {
+    let event = json!({"id": 3, "fancy_id": "devcon5-2019", "name": "Devcon 5", "image_url": null});
+    let scan = warp::path!("actions" / "scan" / String).map(move |_addr: String| {
+        warp::reply::json(&json!([
+            {"event": event, "tokenId": "1024", "owner": OWNER, "chain": "xdai", "created": "2021-06-24 13:17:13"},
+            {"event": event, "tokenId": "42", "owner": OWNER, "chain": "mainnet", "created": "2019-10-08 09:00:00"},
+            {"event": event, "tokenId": "7", "owner": OWNER, "chain": "polygon", "created": null},
+        ]))
+    });
+    let token = warp::path!("token" / String).map(|token_id: String| {
+        if token_id != "1024" {
+            return warp::reply::with_status(
+                warp::reply::json(&json!({"message": "Token not found"})),
+                warp::http::StatusCode::NOT_FOUND,
+            );
+        }
+        warp::reply::with_status(
+            warp::reply::json(&json!({
+                "event": {"id": 3, "name": "Devcon 5"},
+                "tokenId": "1024",
+                "owner": OWNER,
+                "layer": "Layer2",
+                "created": "2021-06-24 13:17:13"
+            })),
+            warp::http::StatusCode::OK,
+        )
+    });
+    let base = spawn_stub_server(scan.or(token));
+
+    let tokens = fetch_scan(&base, OWNER).await?;
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0].event.name, "Devcon 5");
+    assert_eq!(tokens[0].chain(), Some(Chain::Gnosis));
+    assert_eq!(tokens[1].chain(), Some(Chain::Ethereum));
+    assert_eq!(tokens[2].chain(), None);
+    assert!(tokens[0].created_at().is_some());
+    assert!(tokens[2].created_at().is_none());
+
+    let token = fetch_token(&base, "1024")
+        .await?
+        .expect("Token should be found");
+    assert_eq!(token.chain(), Some(Chain::Gnosis));
+    assert!(fetch_token(&base, "1").await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_by_address() -> Result<(), Error> {
+    // Token IDs not seen by other tests.
+    let token_id = (Uuid::new_v4().as_u128() % 1_000_000_000_000).to_string();
+    let polygon_token_id = format!("{}1", token_id);
+    let scan_token_id = token_id.clone();
+    let scan_polygon_token_id = polygon_token_id.clone();
+    let scan = warp::path!("actions" / "scan" / String).map(move |addr: String| {
+        assert_eq!(addr, OWNER);
+        let event = json!({"id": 3, "fancy_id": "devcon5-2019", "name": "Devcon 5", "image_url": null});
+        warp::reply::json(&json!([
+            {"event": event, "tokenId": scan_token_id, "owner": OWNER, "chain": "xdai", "created": "2021-06-24 13:17:13"},
+            {"event": event, "tokenId": scan_polygon_token_id, "owner": OWNER, "chain": "polygon", "created": null},
+        ]))
+    });
+    let base = spawn_stub_server(scan);
+
+    let next_targets = fetch_tokens_by_address(&base, OWNER).await?;
+    assert!(next_targets.is_empty());
+
+    let db = new_db_connection().await?;
+    let contract = ContractCategory::POAP.default_contract_address().unwrap();
+    let found = Hold::find_by_id_chain_address(&db, &token_id, &Chain::Gnosis, &contract)
+        .await?
+        .expect("Hold record not found");
+    assert_eq!(found.event_id, Some("3".to_string()));
+    assert_eq!(found.event_name, Some("Devcon 5".to_string()));
+    // Tokens on chains other than Ethereum and Gnosis are skipped.
+    assert!(
+        Hold::find_by_id_chain_address(&db, &polygon_token_id, &Chain::Polygon, &contract)
+            .await?
+            .is_none()
+    );
+
+    Ok(())
+}
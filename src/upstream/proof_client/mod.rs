@@ -151,6 +151,8 @@ async fn fetch_connections_by_platform_identity(
                 avatar_url: None,
                 profile_url: None,
                 updated_at: naive_now(),
+                chains: vec![],
+                is_contract: None,
             };
 
             let from_record = from.create_or_update(&db).await?;
@@ -184,6 +186,8 @@ async fn fetch_connections_by_platform_identity(
                 avatar_url: None,
                 profile_url: None,
                 updated_at: naive_now(),
+                chains: vec![],
+                is_contract: None,
             };
             let to_record = to.create_or_update(&db).await?;
 
@@ -199,6 +203,7 @@ async fn fetch_connections_by_platform_identity(
                 )),
                 updated_at: naive_now(),
                 fetcher: DataFetcher::RelationService,
+                deleted_at: None,
             };
             pf.two_way_binding(&db, &from_record, &to_record).await?;
         }
@@ -7,19 +7,23 @@ use crate::{
         create_identity_to_contract_record,
         edge::hold::Hold,
         new_db_connection,
-        vertex::{contract::Chain, contract::ContractCategory, Contract, Identity},
+        vertex::{
+            contract::Chain, contract::ContractCategory, Contract, Identity, Token, TokenAttribute,
+            Vertex,
+        },
     },
-    upstream::{DataSource, Fetcher, Platform, Target, TargetProcessedList},
-    util::{make_client, naive_now, parse_body, request_with_timeout},
+    upstream::{eth_rpc, DataSource, Fetcher, Platform, Target, TargetProcessedList},
+    util::{ipfs_to_http, make_client, naive_now, parse_body, request_with_timeout},
 };
+use aragog::{DatabaseConnection, DatabaseRecord};
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDateTime};
-use futures::future::join_all;
 use http::uri::InvalidUri;
 use hyper::{Body, Method};
 use serde::Deserialize;
+use serde_json::Value;
 use std::str::FromStr;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use super::DataFetcher;
@@ -69,7 +73,11 @@ pub struct ActionItem {
 pub struct MetaData {
     pub id: Option<String>,
     pub name: Option<String>,
+    pub description: Option<String>,
     pub image: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<Value>,
+    /// Quantity moved by this action. Not the balance after it, so it's not saved as `Hold.amount`.
     pub value: Option<String>,
     pub symbol: Option<String>,
     pub standard: Option<String>,
@@ -77,7 +85,29 @@ pub struct MetaData {
     pub handle: Option<String>,
 }
 
-const PAGE_LIMIT: i64 = 500;
+const PAGE_LIMIT: usize = 500;
+/// Stop following `cursor` after this many pages.
+/// Unlike Lens, there is no resuming from a saved cursor: notes are replayed as a whole
+/// to work out what is still held, and replaying older pages on their own after newer
+/// ones would bring back holds that have since ended.
+const MAX_PAGES: usize = 50;
+/// Transferring to these addresses is burning.
+const BURN_ADDRESSES: [&str; 2] = [
+    "0x0000000000000000000000000000000000000000",
+    "0x000000000000000000000000000000000000dead",
+];
+
+/// How a collectible action changes the holding of the account being fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Minted to / transferred to the account.
+    In,
+    /// Transferred from the account to someone else.
+    Out,
+    /// Burned by the account.
+    Burn,
+}
+
 pub struct Rss3 {}
 
 #[async_trait]
@@ -89,7 +119,7 @@ impl Fetcher for Rss3 {
 
         match target {
             Target::Identity(platform, identity) => fetch_nfts_by_account(platform, identity).await,
-            Target::NFT(_, _, _, _) => todo!(),
+            Target::NFT(_, _, _, _) => Ok(vec![]),
         }
     }
 
@@ -102,27 +132,56 @@ async fn fetch_nfts_by_account(
     _platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
-    let mut cursor = String::from("");
+    let owner = identity.to_lowercase();
+    let mut notes: Vec<(NaiveDateTime, ResultItem)> =
+        fetch_notes(&C.upstream.rss3_service.url, &owner)
+            .await?
+            .into_iter()
+            .filter(|p| p.owner.to_lowercase() == owner)
+            .filter_map(|p| parse_timestamp(&p.timestamp).map(|t| (t, p)))
+            .collect();
+    if notes.is_empty() {
+        info!("Rss3 Response result is empty");
+        return Ok(vec![]);
+    }
+    // Replay the history from the oldest one, so the latest action wins.
+    notes.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let db = new_db_connection().await?;
+    let mut next_targets: TargetProcessedList = Vec::new();
+    for (timestamp, note) in notes.into_iter() {
+        match save_item(&db, &owner, timestamp, note).await {
+            Ok(targets) => {
+                for target in targets.into_iter() {
+                    if !next_targets.contains(&target) {
+                        next_targets.push(target);
+                    }
+                }
+            }
+            Err(err) => warn!("Rss3 {} | Fail to save note: {}", owner, err),
+        }
+    }
+
+    Ok(next_targets)
+}
+
+/// Fetch collectible notes of `identity`, following `cursor` till the end.
+async fn fetch_notes(base: &str, identity: &str) -> Result<Vec<ResultItem>, Error> {
     let client = make_client();
-    let mut next_targets = Vec::new();
-
-    loop {
-        let uri: http::Uri;
-        if cursor.len() == 0 {
-            uri = format!(
-                "{}/{}?tag=collectible&include_poap=true&refresh=true",
-                C.upstream.rss3_service.url, identity
-            )
-            .parse()
-            .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
-        } else {
-            uri = format!(
-                "{}/{}?tag=collectible&include_poap=true&refresh=true&cursor={}",
-                C.upstream.rss3_service.url, identity, cursor
-            )
+    let mut cursor: Option<String> = None;
+    let mut notes: Vec<ResultItem> = Vec::new();
+
+    for _ in 0..MAX_PAGES {
+        let mut uri = format!(
+            "{}/{}?tag=collectible&include_poap=true&refresh=true&limit={}",
+            base, identity, PAGE_LIMIT
+        );
+        if let Some(cursor) = cursor.as_ref() {
+            uri = format!("{}&cursor={}", uri, cursor);
+        }
+        let uri: http::Uri = uri
             .parse()
             .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
-        }
 
         let req = hyper::Request::builder()
             .method(Method::GET)
@@ -135,83 +194,104 @@ async fn fetch_nfts_by_account(
         })?;
 
         let body: Rss3Response = parse_body(&mut resp).await?;
-        if body.total == 0 {
-            info!("Rss3 Response result is empty");
-            break;
+        let count = body.result.len();
+        notes.extend(body.result);
+        // `total` is the size of current page, not of the whole history.
+        if count < PAGE_LIMIT || body.cursor.is_none() || body.cursor == cursor {
+            return Ok(notes);
         }
+        cursor = body.cursor;
+    }
 
-        let futures: Vec<_> = body
-            .result
-            .into_iter()
-            .filter(|p| p.owner == identity.to_lowercase())
-            .map(save_item)
-            .collect();
+    warn!(
+        "Rss3 {} | Stopped at {} pages, notes older than cursor {} are left out",
+        identity,
+        MAX_PAGES,
+        cursor.unwrap_or_default(),
+    );
+    Ok(notes)
+}
 
-        let targets: TargetProcessedList = join_all(futures)
-            .await
-            .into_iter()
-            .flat_map(|result| result.unwrap_or_default())
-            .collect();
+/// `"2022-01-02T03:04:05Z"` => `NaiveDateTime`
+fn parse_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| NaiveDateTime::from_timestamp(dt.timestamp(), 0))
+}
 
-        next_targets.extend(targets);
-        if body.cursor.is_none() || body.total < PAGE_LIMIT {
-            break;
-        } else {
-            cursor = body.cursor.unwrap();
+/// Direction of a collectible `action` for `owner`. `None` if `owner` is not involved.
+pub fn direction_of(owner: &str, action: &ActionItem) -> Option<Direction> {
+    let from = action.address_from.to_lowercase();
+    let to = action.address_to.to_lowercase();
+    match action.tag_type.as_str() {
+        "mint" if to == owner => Some(Direction::In),
+        "transfer" if to == owner => Some(Direction::In),
+        "transfer" if from == owner && BURN_ADDRESSES.contains(&to.as_str()) => {
+            Some(Direction::Burn)
         }
+        "transfer" if from == owner => Some(Direction::Out),
+        "burn" if from == owner => Some(Direction::Burn),
+        _ => None,
     }
-
-    Ok(next_targets)
 }
 
-async fn save_item(p: ResultItem) -> Result<TargetProcessedList, Error> {
-    let creataed_at = DateTime::parse_from_rfc3339(&p.timestamp).unwrap();
-    let created_at_naive = NaiveDateTime::from_timestamp(creataed_at.timestamp(), 0);
-    let db = new_db_connection().await?;
-
-    let from: Identity = Identity {
+fn ethereum_identity(address: &str, chain: Chain) -> Identity {
+    Identity {
         uuid: Some(Uuid::new_v4()),
         platform: Platform::Ethereum,
-        identity: p.owner.to_lowercase(),
-        created_at: Some(created_at_naive),
+        identity: address.to_lowercase(),
+        created_at: None,
         // Don't use ETH's wallet as display_name, use ENS reversed lookup instead.
         display_name: None,
         added_at: naive_now(),
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
-    };
+        chains: vec![chain],
+        is_contract: None,
+    }
+}
 
-    if p.actions.len() == 0 {
+/// Applies a collectible note of `owner` happened at `timestamp`:
+/// the receiver's `Hold` is opened, and every other `Hold` of the token is ended.
+/// ERC-1155 tokens have many holders, so only the sender's `Hold` is ended (see `close_1155_sender`).
+async fn save_item(
+    db: &DatabaseConnection,
+    owner: &str,
+    timestamp: NaiveDateTime,
+    p: ResultItem,
+) -> Result<TargetProcessedList, Error> {
+    if p.tag != "collectible" {
         return Ok(vec![]);
     }
-
+    // collectible (transfer, mint, burn) share the same UMS, but approve/revoke not.
     let found = p
         .actions
         .iter()
-        // collectible (transfer, mint, burn) share the same UMS, but approve/revoke not.
-        // we need to record is the `hold` relation, so burn is excluded
-        .filter(|a| {
-            (a.tag_type == "transfer" && p.tag_type == "transfer")
-                || (a.tag_type == "mint" && p.tag_type == "mint")
-        })
-        .find(|a| (p.tag == "collectible" && a.tag == "collectible"));
-    if found.is_none() {
-        return Ok(vec![]);
-    }
-    let real_action = found.unwrap();
+        .filter(|a| a.tag == "collectible" && a.tag_type == p.tag_type)
+        .find_map(|a| direction_of(owner, a).map(|d| (a, d)));
+    let (real_action, direction) = match found {
+        Some(found) => found,
+        None => return Ok(vec![]),
+    };
 
-    if real_action.metadata.symbol.is_none()
-        || real_action.metadata.symbol.as_ref().unwrap() == &String::from("ENS")
-    {
-        return Ok(vec![]);
-    }
+    let symbol = match real_action.metadata.symbol.as_ref() {
+        // ENS is fetched by TheGraph.
+        Some(symbol) if symbol != "ENS" => symbol.clone(),
+        _ => return Ok(vec![]),
+    };
+    let (contract_addr, nft_id) = match (
+        real_action.metadata.contract_address.as_ref(),
+        real_action.metadata.id.as_ref(),
+    ) {
+        (Some(address), Some(id)) => (address.to_lowercase(), id.clone()),
+        _ => return Ok(vec![]),
+    };
 
     let mut nft_category =
-        ContractCategory::from_str(real_action.metadata.standard.as_ref().unwrap().as_str())
+        ContractCategory::from_str(real_action.metadata.standard.as_deref().unwrap_or_default())
             .unwrap_or_default();
-
-    if real_action.tag_type == "poap".to_string() {
+    if p.tag_type == "poap" {
         nft_category = ContractCategory::POAP;
     }
 
@@ -220,38 +300,177 @@ async fn save_item(p: ResultItem) -> Result<TargetProcessedList, Error> {
         error!("Rss3 Fetch data | Unknown Chain, original data: {:?}", p);
         return Ok(vec![]);
     }
-    let contract_addr = real_action
-        .metadata
-        .contract_address
-        .as_ref()
-        .unwrap()
-        .to_lowercase();
-    let nft_id = real_action.metadata.id.as_ref().unwrap();
-
-    let to: Contract = Contract {
+
+    let contract: Contract = Contract {
         uuid: Uuid::new_v4(),
         category: nft_category,
         address: contract_addr.clone(),
         chain,
-        symbol: Some(real_action.metadata.symbol.as_ref().unwrap().clone()),
+        symbol: Some(symbol),
+        name: None,
+        total_supply: None,
+        deployer: None,
+        verified: false,
         updated_at: naive_now(),
     };
 
-    let hold: Hold = Hold {
-        uuid: Uuid::new_v4(),
+    let token = Token {
+        chain,
+        address: contract_addr.clone(),
+        token_id: nft_id.clone(),
+        name: real_action.metadata.name.clone(),
+        description: real_action.metadata.description.clone(),
+        image: real_action.metadata.image.as_deref().map(ipfs_to_http),
+        attributes: real_action
+            .metadata
+            .attributes
+            .iter()
+            .filter_map(TokenAttribute::from_json)
+            .collect(),
+        token_uri: None,
         source: DataSource::Rss3,
-        transaction: Some(p.hash),
-        id: nft_id.clone(),
-        created_at: Some(created_at_naive),
-        updated_at: naive_now(),
-        fetcher: DataFetcher::RelationService,
+        ..Default::default()
     };
-    create_identity_to_contract_record(&db, &from, &to, &hold).await?;
+    token.create_or_update(db).await?;
+
+    let is_1155 = nft_category == ContractCategory::ERC1155;
+    let receiver = match direction {
+        Direction::In => Some(owner.to_string()),
+        Direction::Out => Some(real_action.address_to.to_lowercase()),
+        Direction::Burn => None,
+    };
+    match receiver {
+        Some(receiver) => {
+            let hold: Hold = Hold {
+                uuid: Uuid::new_v4(),
+                source: DataSource::Rss3,
+                transaction: Some(p.hash.clone()),
+                id: nft_id.clone(),
+                created_at: Some(timestamp),
+                updated_at: naive_now(),
+                fetcher: DataFetcher::RelationService,
+                event_id: None,
+                event_name: None,
+                ended_at: None,
+                expired_at: None,
+                // RSS3 only tells how many are moved, so ERC-1155 balances are read on-chain.
+                amount: match is_1155 {
+                    true => balance_of_1155(chain, &contract_addr, &receiver, &nft_id).await,
+                    false => None,
+                },
+                decimals: None,
+            };
+            let (receiver_record, contract_record, _) = create_identity_to_contract_record(
+                db,
+                &ethereum_identity(&receiver, chain),
+                &contract,
+                &hold,
+            )
+            .await?;
+            if is_1155 {
+                close_1155_sender(
+                    db,
+                    chain,
+                    &contract_record.0,
+                    real_action,
+                    &nft_id,
+                    timestamp,
+                )
+                .await?;
+            } else {
+                Hold::close(
+                    db,
+                    &contract_record.0,
+                    &nft_id,
+                    Some(&receiver_record.0),
+                    timestamp,
+                )
+                .await?;
+            }
+        }
+        None => {
+            let contract_record = contract.create_or_update(db).await?;
+            if is_1155 {
+                close_1155_sender(
+                    db,
+                    chain,
+                    &contract_record.0,
+                    real_action,
+                    &nft_id,
+                    timestamp,
+                )
+                .await?;
+            } else {
+                Hold::close(db, &contract_record.0, &nft_id, None, timestamp).await?;
+            }
+        }
+    }
 
     Ok(vec![Target::NFT(
         chain,
         nft_category,
-        contract_addr.clone(),
-        nft_id.clone(),
+        contract_addr,
+        nft_id,
     )])
 }
+
+/// Ends the `Hold` of the ERC-1155 token `nft_id` by the sender of `action`, which happened at `timestamp`.
+/// If `rpc_url` of `chain` is configured, the sender's `balanceOf()` is read first,
+/// and its `Hold` is kept with the balance as `amount` if it still has some left.
+pub async fn close_1155_sender(
+    db: &DatabaseConnection,
+    chain: Chain,
+    contract: &DatabaseRecord<Contract>,
+    action: &ActionItem,
+    nft_id: &str,
+    timestamp: NaiveDateTime,
+) -> Result<(), Error> {
+    let sender = action.address_from.to_lowercase();
+    // Minted
+    if BURN_ADDRESSES.contains(&sender.as_str()) {
+        return Ok(());
+    }
+    let sender_record =
+        match Identity::find_by_platform_identity(db, &Platform::Ethereum, &sender).await? {
+            Some(found) => found,
+            None => return Ok(()),
+        };
+    match balance_of_1155(chain, &contract.address, &sender, nft_id).await {
+        Some(balance) if balance != "0" => {
+            if let Some(mut found) =
+                Hold::find_by_from_to_id(db, &sender_record.0, contract, nft_id).await?
+            {
+                if found.ended_at.is_none() && found.amount.as_ref() != Some(&balance) {
+                    found.amount = Some(balance);
+                    found.updated_at = naive_now();
+                    found.save(db).await?;
+                }
+            }
+        }
+        _ => {
+            Hold::close_of(db, &sender_record.0, contract, nft_id, timestamp).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads `balanceOf(holder, nft_id)` of an ERC-1155 `contract` on `chain`.
+/// `None` if `rpc_url` of `chain` is not configured or the call fails.
+async fn balance_of_1155(
+    chain: Chain,
+    contract: &str,
+    holder: &str,
+    nft_id: &str,
+) -> Option<String> {
+    let rpc = chain.info().and_then(|info| info.rpc_url.as_deref())?;
+    match eth_rpc::balance_of_1155(rpc, contract, holder, nft_id, None).await {
+        Ok(balance) => balance,
+        Err(err) => {
+            warn!(
+                "Rss3 {} | balanceOf {} #{} failed: {}",
+                holder, contract, nft_id, err
+            );
+            None
+        }
+    }
+}
@@ -3,10 +3,17 @@ use crate::{
     graph::edge::Hold,
     graph::new_db_connection,
     graph::vertex::{contract::Chain, Contract, Identity},
-    upstream::rss3::Rss3,
+    tests::spawn_stub_server,
+    upstream::rss3::{
+        direction_of, fetch_notes, save_item, ActionItem, Direction, ResultItem, Rss3, PAGE_LIMIT,
+    },
     upstream::Platform,
     upstream::{Fetcher, Target},
+    util::naive_now,
 };
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use warp::Filter;
 
 #[tokio::test]
 async fn test_smoke_nft_rss3() -> Result<(), Error> {
@@ -34,3 +41,155 @@ async fn test_smoke_nft_rss3() -> Result<(), Error> {
 
     Ok(())
 }
+
+fn action(tag_type: &str, from: &str, to: &str) -> ActionItem {
+    serde_json::from_value(json!({
+        "tag": "collectible",
+        "type": tag_type,
+        "index": 0,
+        "address_from": from,
+        "address_to": to,
+        "metadata": {}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_direction_of() {
+    let owner = "0xowner";
+    assert_eq!(
+        direction_of(
+            owner,
+            &action(
+                "mint",
+                "0x0000000000000000000000000000000000000000",
+                "0xOwner"
+            )
+        ),
+        Some(Direction::In)
+    );
+    assert_eq!(
+        direction_of(owner, &action("transfer", "0xother", "0xowner")),
+        Some(Direction::In)
+    );
+    assert_eq!(
+        direction_of(owner, &action("transfer", "0xowner", "0xother")),
+        Some(Direction::Out)
+    );
+    assert_eq!(
+        direction_of(
+            owner,
+            &action(
+                "transfer",
+                "0xowner",
+                "0x000000000000000000000000000000000000dEaD"
+            )
+        ),
+        Some(Direction::Burn)
+    );
+    assert_eq!(
+        direction_of(owner, &action("burn", "0xowner", "")),
+        Some(Direction::Burn)
+    );
+    assert_eq!(
+        direction_of(owner, &action("transfer", "0xother", "0xsomeone")),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_notes_follows_cursor() -> Result<(), Error> {
+    let notes = warp::path!("notes" / String)
+        .and(warp::query::<HashMap<String, String>>())
+        .map(|_owner: String, query: HashMap<String, String>| {
+            let limit: usize = query.get("limit").unwrap().parse().unwrap();
+            let (count, cursor) = match query.get("cursor").map(|c| c.as_str()) {
+                None => (limit, json!("page2")),
+                Some("page2") => (1, Value::Null),
+                Some(_) => (0, Value::Null),
+            };
+            let result: Vec<Value> = (0..count)
+                .map(|i| {
+                    json!({
+                        "timestamp": "2022-01-02T03:04:05Z",
+                        "owner": "0xowner",
+                        "address_from": "0xowner",
+                        "network": "ethereum",
+                        "tag": "collectible",
+                        "type": "transfer",
+                        "success": true,
+                        "hash": format!("0x{}", i),
+                        "actions": []
+                    })
+                })
+                .collect();
+            warp::reply::json(&json!({"total": count, "cursor": cursor, "result": result}))
+        });
+    let base = spawn_stub_server(notes);
+
+    let result = fetch_notes(&format!("{}/notes", base), "0xowner").await?;
+    assert_eq!(result.len(), PAGE_LIMIT + 1);
+    Ok(())
+}
+
+const ZERO: &str = "0x0000000000000000000000000000000000000000";
+
+fn transfer_note(owner: &str, from: &str, to: &str, contract: &str, standard: &str) -> ResultItem {
+    serde_json::from_value(json!({
+        "timestamp": "2022-01-02T03:04:05Z",
+        "hash": format!("0x{}{}", from, to),
+        "owner": owner,
+        "address_from": from,
+        "address_to": to,
+        "network": "ethereum",
+        "tag": "collectible",
+        "type": "transfer",
+        "success": true,
+        "actions": [{
+            "tag": "collectible",
+            "type": "transfer",
+            "index": 0,
+            "address_from": from,
+            "address_to": to,
+            "metadata": {
+                "id": "1",
+                "symbol": "TEST",
+                "standard": standard,
+                "contract_address": contract
+            }
+        }]
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_save_item_ends_erc1155_sender_only() -> Result<(), Error> {
+    let db = new_db_connection().await?;
+    let contract = format!("0x{}", uuid::Uuid::new_v4().simple());
+    let (alice, bob, carol) = ("0xa11ce", "0xb0b", "0xca201");
+    for (owner, from, to) in [
+        (carol, ZERO, carol),
+        (alice, ZERO, alice),
+        (alice, alice, bob),
+    ] {
+        let note = transfer_note(owner, from, to, &contract, "ERC1155");
+        save_item(&db, owner, naive_now(), note).await?;
+    }
+
+    let contract = Contract::find_by_chain_address(&db, &Chain::Ethereum, &contract)
+        .await?
+        .expect("Contract should be saved");
+    // No `rpc_url` of ethereum in test config, so Alice's hold is ended as RSS3 reports,
+    // and no balance is known.
+    for (holder, holding) in [(alice, false), (bob, true), (carol, true)] {
+        let identity = Identity::find_by_platform_identity(&db, &Platform::Ethereum, holder)
+            .await?
+            .expect("Holder should be saved");
+        let hold = Hold::find_by_from_to_id(&db, &identity, &contract, "1")
+            .await?
+            .expect("Hold should be saved");
+        assert_eq!(hold.ended_at.is_none(), holding, "{} holding it", holder);
+        assert_eq!(hold.amount, None);
+    }
+    Ok(())
+}
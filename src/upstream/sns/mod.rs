@@ -0,0 +1,238 @@
+#[cfg(test)]
+mod tests;
+
+use crate::config::C;
+use crate::error::Error;
+use crate::graph::edge::{hold::Hold, resolve::DomainNameSystem, Resolve};
+use crate::graph::new_db_connection;
+use crate::graph::vertex::Identity;
+use crate::graph::{create_domain_resolve_record, create_identity_to_identity_hold_record};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
+use crate::util::{is_solana_address, make_client, naive_now, parse_body, request_with_timeout};
+use aragog::DatabaseConnection;
+use async_trait::async_trait;
+use hyper::{Body, Method};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Every response of SNS SDK proxy is wrapped in `{ "s": "ok" | "error", "result": ... }`.
+/// https://github.com/Bonfida/sns-sdk#sns-sdk-proxy
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProxyResponse {
+    pub s: String,
+    pub result: Value,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DomainItem {
+    /// Public key of the domain account.
+    pub key: String,
+    /// Domain name without `.sol` suffix.
+    pub domain: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FavoriteDomain {
+    /// Public key of the domain account.
+    pub domain: String,
+    /// Domain name without `.sol` suffix.
+    pub reverse: String,
+    /// `true` if the favorite domain is no longer owned by this wallet.
+    pub stale: bool,
+}
+
+pub struct SNS {}
+
+#[async_trait]
+impl Fetcher for SNS {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(platform, identity) => match platform {
+                Platform::Solana => fetch_domains_by_owner(identity).await,
+                Platform::SNS => fetch_owner_by_domain(identity).await,
+                _ => Ok(vec![]),
+            },
+            Target::NFT(_, _, _, _) => Ok(vec![]),
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Solana, Platform::SNS])
+    }
+}
+
+async fn fetch_owner_by_domain(name: &str) -> Result<TargetProcessedList, Error> {
+    let name = normalize_name(name);
+    let owner: String = match request(&format!("resolve/{}", trim_suffix(&name))).await? {
+        Some(owner) => owner,
+        None => return Ok(vec![]),
+    };
+    if !is_solana_address(&owner) {
+        warn!("SNS: {} resolved to an invalid address {}", name, owner);
+        return Err(Error::NoResult);
+    }
+
+    let db = new_db_connection().await?;
+    save_domain(&db, &owner, &name).await?;
+
+    Ok(vec![Target::Identity(Platform::Solana, owner)])
+}
+
+async fn fetch_domains_by_owner(owner: &str) -> Result<TargetProcessedList, Error> {
+    if !is_solana_address(owner) {
+        return Err(Error::ParamError(format!(
+            "SNS: invalid Solana address {}",
+            owner
+        )));
+    }
+
+    let domains: Vec<DomainItem> = request(&format!("domains/{}", owner))
+        .await?
+        .unwrap_or_default();
+    let favorite: Option<FavoriteDomain> = request(&format!("favorite-domain/{}", owner))
+        .await
+        .unwrap_or_else(|err| {
+            debug!("SNS: no favorite domain for {}: {}", owner, err);
+            None
+        });
+    if domains.is_empty() && favorite.is_none() {
+        return Ok(vec![]);
+    }
+
+    let db = new_db_connection().await?;
+    let mut next_targets: TargetProcessedList = vec![];
+    for item in domains.iter() {
+        let name = normalize_name(&item.domain);
+        save_domain(&db, owner, &name).await?;
+        next_targets.push(Target::Identity(Platform::SNS, name));
+    }
+
+    if let Some(favorite) = favorite.filter(|f| !f.stale) {
+        let name = normalize_name(&favorite.reverse);
+        let sol_identity = solana_identity(owner, Some(name.clone()));
+        let sns_identity = sns_identity(&name);
+        let reverse = sns_resolve(&name);
+        // 'reverse' resolution maps from an address back to a name.
+        create_domain_resolve_record(&db, &sol_identity, &sns_identity, &reverse).await?;
+    }
+
+    next_targets.dedup();
+    Ok(next_targets)
+}
+
+/// Save `Hold` (wallet -> name) and 'regular' `Resolve` (name -> wallet) records.
+async fn save_domain(db: &DatabaseConnection, owner: &str, name: &str) -> Result<(), Error> {
+    let sol_identity = solana_identity(owner, None);
+    let sns_identity = sns_identity(name);
+    let hold: Hold = Hold {
+        uuid: Uuid::new_v4(),
+        source: DataSource::SNS,
+        transaction: None,
+        id: name.to_string(),
+        created_at: None,
+        updated_at: naive_now(),
+        fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
+    };
+    let resolve = sns_resolve(name);
+
+    create_identity_to_identity_hold_record(db, &sol_identity, &sns_identity, &hold).await?;
+    // 'regular' resolution involves mapping from a name to an address.
+    create_domain_resolve_record(db, &sns_identity, &sol_identity, &resolve).await?;
+    Ok(())
+}
+
+fn solana_identity(address: &str, display_name: Option<String>) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Solana,
+        // Base58 is case-sensitive. Keep it as-is.
+        identity: address.to_string(),
+        created_at: None,
+        display_name,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    }
+}
+
+fn sns_identity(name: &str) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::SNS,
+        identity: name.to_string(),
+        created_at: None,
+        display_name: Some(name.to_string()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: Some(format!(
+            "https://naming.bonfida.org/domain/{}",
+            trim_suffix(name)
+        )),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
+    }
+}
+
+fn sns_resolve(name: &str) -> Resolve {
+    Resolve {
+        uuid: Uuid::new_v4(),
+        source: DataSource::SNS,
+        system: DomainNameSystem::SNS,
+        name: name.to_string(),
+        fetcher: DataFetcher::RelationService,
+        updated_at: naive_now(),
+    }
+}
+
+/// `Bonfida`, `bonfida` and `bonfida.sol` are all stored as `bonfida.sol`.
+fn normalize_name(name: &str) -> String {
+    format!("{}.sol", trim_suffix(&name.to_lowercase()))
+}
+
+fn trim_suffix(name: &str) -> &str {
+    name.trim_end_matches(".sol")
+}
+
+/// Returns `None` if the proxy reports an error (e.g. domain not found).
+async fn request<T>(path: &str) -> Result<Option<T>, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let client = make_client();
+    let uri: http::Uri = format!("{}/{}", C.upstream.sns_api.url, path)
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("SNS Build Request Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("SNS fetch | error: {:?}", err.to_string()))
+    })?;
+
+    let body: ProxyResponse = parse_body(&mut resp).await?;
+    if body.s != "ok" {
+        debug!("SNS fetch {} | upstream error: {}", path, body.result);
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_value(body.result)?))
+}
@@ -0,0 +1,44 @@
+use crate::{
+    error::Error,
+    graph::{new_db_connection, vertex::Identity},
+    upstream::{sns::SNS, Fetcher, Platform, Target},
+};
+
+#[tokio::test]
+async fn test_fetch_owner_by_domain() -> Result<(), Error> {
+    let target = Target::Identity(Platform::SNS, "bonfida.sol".into());
+    let next = SNS::fetch(&target).await?;
+    assert_eq!(next.len(), 1);
+
+    let db = new_db_connection().await?;
+    Identity::find_by_platform_identity(&db, &Platform::SNS, "bonfida.sol")
+        .await?
+        .expect("Record not found");
+    Identity::find_by_platform_identity(&db, &Platform::Solana, &next[0].identity()?)
+        .await?
+        .expect("Record not found");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_domains_by_owner() -> Result<(), Error> {
+    let target = Target::Identity(
+        Platform::Solana,
+        "HKKp49qGWXd639QsuH7JiLijfVW5UtCVY4s1n2HANwEA".into(),
+    );
+    SNS::fetch(&target).await?;
+
+    let db = new_db_connection().await?;
+    Identity::find_by_platform_identity(&db, &target.platform()?, &target.identity()?)
+        .await?
+        .expect("Record not found");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_owner() {
+    let target = Target::Identity(Platform::Solana, "0xnot-a-solana-address".into());
+    assert!(SNS::fetch(&target).await.is_err());
+}
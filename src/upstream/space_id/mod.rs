@@ -4,19 +4,30 @@ use crate::{
     config::C,
     error::Error,
     graph::edge::{hold::Hold, resolve::DomainNameSystem, Resolve},
-    graph::vertex::Identity,
+    graph::vertex::{
+        contract::{Chain, ContractCategory},
+        Contract, Identity,
+    },
     graph::{
-        create_domain_resolve_record, create_identity_to_identity_hold_record, new_db_connection,
+        create_domain_resolve_record, create_identity_to_contract_record,
+        create_identity_to_identity_hold_record, new_db_connection,
+    },
+    upstream::{
+        eth_rpc::{self, parse_hex_u64},
+        DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList,
     },
-    upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList},
-    util::{make_client, naive_now, parse_body, request_with_timeout},
+    util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive},
 };
 
 // use super::types::target;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use gql_client::Client;
 use http::uri::InvalidUri;
 use hyper::{Body, Method, Request};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiny_keccak::{Hasher, Keccak};
 use tracing::{error, warn};
 use uuid::Uuid;
 
@@ -38,6 +49,68 @@ pub struct ReverseResolveResponse {
     pub name: Option<String>,
 }
 
+#[derive(Serialize)]
+struct OwnerQueryVars {
+    owner: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwnerQueryResponse {
+    domains: Vec<OwnedDomain>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OwnedDomain {
+    /// `null` or `[labelhash].bnb` if the label is unknown to the subgraph.
+    name: Option<String>,
+}
+
+const QUERY_BY_OWNER: &str = r#"
+    query NamesByOwner($owner: String!) {
+        domains(first: 1000, where: { owner: $owner }) {
+            name
+        }
+    }
+"#;
+
+/// A top-level domain served by SPACE ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceIdTld {
+    /// Suffix of names, e.g. `bnb` for `abc.bnb`.
+    pub suffix: &'static str,
+    /// `tld` param of SPACE ID API.
+    pub api_tld: &'static str,
+    /// Chain where names of this TLD are registered.
+    pub chain: Chain,
+    /// ERC721 registrar contract of this TLD.
+    pub registrar: &'static str,
+}
+
+/// `nameExpires(uint256)` of SPACE ID registrars, which are forked from ENS `BaseRegistrarImplementation`.
+pub const SELECTOR_NAME_EXPIRES: &str = "0xd6e4fa86";
+
+pub const TLDS: [SpaceIdTld; 2] = [
+    SpaceIdTld {
+        suffix: "bnb",
+        api_tld: "bnb",
+        chain: Chain::BNBSmartChain,
+        registrar: "0xe3b1d32e43ce8d658368e2cbff95d57ef39be8a6",
+    },
+    SpaceIdTld {
+        suffix: "arb",
+        api_tld: "arb1",
+        chain: Chain::Arbitrum,
+        registrar: "0x5d482d501b369f5ba034dec5c5fb7a50d2d6ca20",
+    },
+];
+
+/// Finds the TLD `name` belongs to. `None` if SPACE ID does not serve it.
+pub fn tld_of(name: &str) -> Option<SpaceIdTld> {
+    let (_, suffix) = name.rsplit_once('.')?;
+    let suffix = suffix.to_lowercase();
+    TLDS.iter().find(|tld| tld.suffix == suffix).copied()
+}
+
 pub struct SpaceId {}
 
 #[async_trait]
@@ -51,7 +124,8 @@ impl Fetcher for SpaceId {
             Target::Identity(platform, identity) => {
                 fetch_connections_by_platform_identity(platform, identity).await
             }
-            Target::NFT(_, _, _, _) => todo!(),
+            // Token IDs are hashed names, which cannot be resolved back by SPACE ID API.
+            Target::NFT(_, _, _, _) => Ok(vec![]),
         }
     }
 
@@ -70,195 +144,252 @@ async fn fetch_connections_by_platform_identity(
         _ => Ok(vec![]),
     }
 }
+
+/// Lists names of `identity` across all TLDs: its primary name, and every name it owns
+/// in the subgraph of the TLD (`upstream.spaceid_api.subgraphs`), if there is one.
+/// SPACE ID API itself has no lookup of all names owned by a wallet.
 async fn fetch_domain_by_address(
     _platform: &Platform,
     identity: &str,
 ) -> Result<TargetProcessedList, Error> {
     let db = new_db_connection().await?;
-    let name = get_name(identity).await?;
-    if name.is_none() {
-        // name=null, address does not have a valid primary name
-        return Ok(vec![]);
+    let address = identity.to_lowercase();
+    let mut next_targets: TargetProcessedList = vec![];
+    for tld in TLDS.iter() {
+        let eth_identity = address_identity(&address, tld.chain);
+        let primary = match get_name(&C.upstream.spaceid_api.url, tld, &address).await {
+            // `None` if address does not have a valid primary name
+            Ok(name) => name,
+            Err(err) => {
+                warn!(
+                    "SpaceId .{} | {} getName failed: {}",
+                    tld.suffix, address, err
+                );
+                None
+            }
+        };
+        let owned = match C.upstream.spaceid_api.subgraphs.get(tld.suffix) {
+            Some(subgraph) => get_owned_names(subgraph, tld, &address)
+                .await
+                .unwrap_or_else(|err| {
+                    warn!(
+                        "SpaceId .{} | {} owned names failed: {}",
+                        tld.suffix, address, err
+                    );
+                    vec![]
+                }),
+            None => vec![],
+        };
+
+        // Names owned but not the primary one may resolve to other wallets,
+        // which is found out when fetching them by name.
+        for name in owned
+            .into_iter()
+            .filter(|name| Some(name) != primary.as_ref())
+        {
+            save_hold(&db, tld, &eth_identity, &domain_identity(&name)).await?;
+            next_targets.push(Target::Identity(Platform::SpaceId, name));
+        }
+
+        let name = match primary {
+            Some(name) => name,
+            None => continue,
+        };
+        let sid_identity = domain_identity(&name);
+        let reverse: Resolve = Resolve {
+            uuid: Uuid::new_v4(),
+            source: DataSource::SpaceId,
+            system: DomainNameSystem::SpaceId,
+            name: name.clone(),
+            fetcher: DataFetcher::RelationService,
+            updated_at: naive_now(),
+        };
+
+        // A primary name must be resolved to the wallet itself.
+        save_domain(&db, tld, &eth_identity, &sid_identity).await?;
+        // 'reverse' resolution maps from an address back to a name.
+        create_domain_resolve_record(&db, &eth_identity, &sid_identity, &reverse).await?;
+
+        next_targets.push(Target::Identity(Platform::SpaceId, name));
     }
 
-    let eth_identity: Identity = Identity {
-        uuid: Some(Uuid::new_v4()),
-        platform: Platform::Ethereum,
-        identity: identity.to_lowercase(),
-        created_at: None,
-        display_name: None,
-        added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
-        updated_at: naive_now(),
-    };
+    Ok(next_targets)
+}
 
-    let sid_identity: Identity = Identity {
-        uuid: Some(Uuid::new_v4()),
-        platform: Platform::SpaceId,
-        identity: name.clone().unwrap(),
-        created_at: None,
-        display_name: None,
-        added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
-        updated_at: naive_now(),
+async fn fetch_address_by_domain(
+    _platform: &Platform,
+    identity: &str,
+) -> Result<TargetProcessedList, Error> {
+    let tld = match tld_of(identity) {
+        Some(tld) => tld,
+        None => {
+            warn!("SpaceId {} | Unsupported TLD", identity);
+            return Ok(vec![]);
+        }
     };
+    let name = identity.to_lowercase();
+    let address = get_address(&C.upstream.spaceid_api.url, &tld, &name)
+        .await?
+        .to_lowercase();
 
-    let hold: Hold = Hold {
-        uuid: Uuid::new_v4(),
-        source: DataSource::SpaceId,
-        transaction: None,
-        id: "".to_string(),
-        created_at: None,
-        updated_at: naive_now(),
-        fetcher: DataFetcher::RelationService,
-    };
+    let db = new_db_connection().await?;
+    let eth_identity = address_identity(&address, tld.chain);
+    let sid_identity = domain_identity(&name);
+    save_domain(&db, &tld, &eth_identity, &sid_identity).await?;
 
+    // lookup reverse resolve name
+    if get_name(&C.upstream.spaceid_api.url, &tld, &address).await? == Some(name.clone()) {
+        // 'reverse' resolution maps from an address back to a name.
+        let reverse: Resolve = Resolve {
+            uuid: Uuid::new_v4(),
+            source: DataSource::SpaceId,
+            system: DomainNameSystem::SpaceId,
+            name,
+            fetcher: DataFetcher::RelationService,
+            updated_at: naive_now(),
+        };
+        create_domain_resolve_record(&db, &eth_identity, &sid_identity, &reverse).await?;
+    }
+
+    return Ok(vec![Target::Identity(Platform::Ethereum, address)]);
+}
+
+/// Saves `Hold`s from wallet to the name and its registrar `Contract` on the chain of `tld`,
+/// and the 'regular' resolution from the name to the wallet.
+async fn save_domain(
+    db: &aragog::DatabaseConnection,
+    tld: &SpaceIdTld,
+    eth_identity: &Identity,
+    sid_identity: &Identity,
+) -> Result<(), Error> {
+    save_hold(db, tld, eth_identity, sid_identity).await?;
     let resolve: Resolve = Resolve {
         uuid: Uuid::new_v4(),
         source: DataSource::SpaceId,
         system: DomainNameSystem::SpaceId,
-        name: name.clone().unwrap(),
+        name: sid_identity.identity.clone(),
         fetcher: DataFetcher::RelationService,
         updated_at: naive_now(),
     };
-    let reverse: Resolve = Resolve {
+    // 'regular' resolution involves mapping from a name to an address.
+    create_domain_resolve_record(db, sid_identity, eth_identity, &resolve).await?;
+    Ok(())
+}
+
+/// Saves `Hold`s from wallet to the name and its registrar `Contract` on the chain of `tld`.
+async fn save_hold(
+    db: &aragog::DatabaseConnection,
+    tld: &SpaceIdTld,
+    eth_identity: &Identity,
+    sid_identity: &Identity,
+) -> Result<(), Error> {
+    let name = sid_identity.identity.clone();
+    let expired_at = match tld.chain.info().and_then(|info| info.rpc_url.clone()) {
+        Some(rpc) => name_expires(&rpc, tld, &name).await,
+        None => None,
+    };
+    let hold: Hold = Hold {
         uuid: Uuid::new_v4(),
         source: DataSource::SpaceId,
-        system: DomainNameSystem::SpaceId,
-        name: name.clone().unwrap(),
+        transaction: None,
+        id: name.clone(),
+        created_at: None,
+        updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at,
+        amount: None,
+        decimals: None,
+    };
+    let contract: Contract = Contract {
+        uuid: Uuid::new_v4(),
+        category: ContractCategory::ERC721,
+        address: tld.registrar.to_string(),
+        chain: tld.chain,
+        symbol: Some(tld.suffix.to_uppercase()),
+        name: None,
+        total_supply: None,
+        deployer: None,
+        verified: true,
         updated_at: naive_now(),
     };
 
     // hold record
-    create_identity_to_identity_hold_record(&db, &eth_identity, &sid_identity, &hold).await?;
-    // 'regular' resolution involves mapping from a name to an address.
-    create_domain_resolve_record(&db, &sid_identity, &eth_identity, &resolve).await?;
-    // 'reverse' resolution maps from an address back to a name.
-    create_domain_resolve_record(&db, &eth_identity, &sid_identity, &reverse).await?;
-
-    return Ok(vec![Target::Identity(
-        Platform::SpaceId,
-        name.clone().unwrap(),
-    )]);
+    create_identity_to_identity_hold_record(db, eth_identity, sid_identity, &hold).await?;
+    create_identity_to_contract_record(db, eth_identity, &contract, &hold).await?;
+    Ok(())
 }
 
-async fn fetch_address_by_domain(
-    _platform: &Platform,
-    identity: &str,
-) -> Result<TargetProcessedList, Error> {
-    let db = new_db_connection().await?;
-    let address = get_address(identity).await?;
+/// Expiry of `name` in the registrar of `tld`, read from the chain of `tld` through `rpc`.
+/// Token ID of a name is the keccak256 of its label, e.g. `abc` for `abc.bnb`.
+/// `None` if the name is not held by the registrar (e.g. a subname), or the call fails.
+pub async fn name_expires(rpc: &str, tld: &SpaceIdTld, name: &str) -> Option<NaiveDateTime> {
+    let label = name.to_lowercase();
+    let label = label.strip_suffix(&format!(".{}", tld.suffix))?;
+    if label.is_empty() || label.contains('.') {
+        return None;
+    }
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(label.as_bytes());
+    keccak.finalize(&mut hash);
+    let token_id: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    let data = format!("{}{}", SELECTOR_NAME_EXPIRES, token_id);
+    let result = match eth_rpc::eth_call(rpc, tld.registrar, &data, None).await {
+        Ok(result) => result,
+        Err(err) => {
+            warn!("SpaceId {} | nameExpires failed: {}", name, err);
+            return None;
+        }
+    };
+    // 0 if the name has never been registered.
+    match parse_hex_u64(&result)? {
+        0 => None,
+        expires => Some(timestamp_to_naive(expires as i64, 0)),
+    }
+}
 
-    let eth_identity: Identity = Identity {
+fn address_identity(address: &str, chain: Chain) -> Identity {
+    Identity {
         uuid: Some(Uuid::new_v4()),
         platform: Platform::Ethereum,
-        identity: address.clone().to_lowercase(),
+        identity: address.to_lowercase(),
         created_at: None,
         display_name: None,
         added_at: naive_now(),
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
-    };
+        chains: vec![chain],
+        is_contract: None,
+    }
+}
 
-    let sid_identity: Identity = Identity {
+fn domain_identity(name: &str) -> Identity {
+    Identity {
         uuid: Some(Uuid::new_v4()),
         platform: Platform::SpaceId,
-        identity: identity.to_string(),
+        identity: name.to_string(),
         created_at: None,
-        display_name: None,
+        display_name: Some(name.to_string()),
         added_at: naive_now(),
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
-    };
-
-    let hold: Hold = Hold {
-        uuid: Uuid::new_v4(),
-        source: DataSource::SpaceId,
-        transaction: None,
-        id: "".to_string(),
-        created_at: None,
-        updated_at: naive_now(),
-        fetcher: DataFetcher::RelationService,
-    };
-    let resolve: Resolve = Resolve {
-        uuid: Uuid::new_v4(),
-        source: DataSource::SpaceId,
-        system: DomainNameSystem::SpaceId,
-        name: identity.to_string(),
-        fetcher: DataFetcher::RelationService,
-        updated_at: naive_now(),
-    };
-
-    // hold record
-    create_identity_to_identity_hold_record(&db, &eth_identity, &sid_identity, &hold).await?;
-    // 'regular' resolution involves mapping from a name to an address.
-    create_domain_resolve_record(&db, &sid_identity, &eth_identity, &resolve).await?;
-
-    // lookup reverse resolve name
-    if let Some(domain) = get_name(&address).await? {
-        // 'reverse' resolution maps from an address back to a name.
-        let reverse: Resolve = Resolve {
-            uuid: Uuid::new_v4(),
-            source: DataSource::SpaceId,
-            system: DomainNameSystem::SpaceId,
-            name: domain,
-            fetcher: DataFetcher::RelationService,
-            updated_at: naive_now(),
-        };
-        create_domain_resolve_record(&db, &eth_identity, &sid_identity, &reverse).await?;
+        chains: vec![],
+        is_contract: None,
     }
-
-    return Ok(vec![Target::Identity(
-        Platform::Ethereum,
-        address.clone().to_lowercase(),
-    )]);
 }
 
 /// Resolve Names: https://docs.space.id/developer-guide/web3-name-sdk/sid-api#resolve-names
-async fn get_address(domain: &str) -> Result<String, Error> {
-    let client = make_client();
-    let uri: http::Uri = format!(
-        "{}/v1/getAddress?tld=bnb&domain={}",
-        C.upstream.spaceid_api.url.clone(),
-        domain
-    )
-    .parse()
-    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
-
-    let req = Request::builder()
-        .method(Method::GET)
-        .uri(uri)
-        .body(Body::empty())
-        .map_err(|_err| Error::ParamError(format!("SpaceId Build Request Error {}", _err)))?;
-
-    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
-        Error::ManualHttpClientError(format!("SpaceId fetch | error: {:?}", err.to_string()))
-    })?;
-
-    if !resp.status().is_success() {
-        let err_message = format!("SpaceId fetch error, statusCode: {}", resp.status());
-        error!(err_message);
-        return Err(Error::General(err_message, resp.status()));
-    }
-    let result = match parse_body::<ResolveResponse>(&mut resp).await {
-        Ok(result) => result,
-        Err(_) => {
-            let err: BadResponse = parse_body(&mut resp).await?;
-            // code=1, domain name is invalid
-            // code=1, rpc error
-            let err_message = format!(
-                "SpaceId fetch error, Code: {}, Message: {}",
-                err.code, err.msg
-            );
-            error!(err_message);
-            return Err(Error::General(err_message, resp.status()));
-        }
-    };
+async fn get_address(base: &str, tld: &SpaceIdTld, domain: &str) -> Result<String, Error> {
+    let result: ResolveResponse = request(&format!(
+        "{}/v1/getAddress?tld={}&domain={}",
+        base, tld.api_tld, domain
+    ))
+    .await?;
     if result.address == "0x0000000000000000000000000000000000000000" {
         // domain is valid but has not been registered.
         warn!(
@@ -271,15 +402,56 @@ async fn get_address(domain: &str) -> Result<String, Error> {
 }
 
 /// Reverse Resolve Names: https://docs.space.id/developer-guide/web3-name-sdk/sid-api#reverse-resolve-names
-async fn get_name(address: &str) -> Result<Option<String>, Error> {
+async fn get_name(base: &str, tld: &SpaceIdTld, address: &str) -> Result<Option<String>, Error> {
+    let result: ReverseResolveResponse = request(&format!(
+        "{}/v1/getName?tld={}&address={}",
+        base, tld.api_tld, address
+    ))
+    .await?;
+    Ok(result.name.filter(|name| !name.is_empty()))
+}
+
+/// Names of `tld` owned by `owner`, from the `subgraph` of `tld`.
+/// Names whose label is unknown to the subgraph are left out.
+async fn get_owned_names(
+    subgraph: &str,
+    tld: &SpaceIdTld,
+    owner: &str,
+) -> Result<Vec<String>, Error> {
+    let client = Client::new(subgraph);
+    let vars = OwnerQueryVars {
+        owner: owner.to_lowercase(),
+    };
+    let resp = client.query_with_vars::<OwnerQueryResponse, OwnerQueryVars>(QUERY_BY_OWNER, vars);
+    let data = match tokio::time::timeout(std::time::Duration::from_secs(5), resp).await {
+        Ok(resp) => resp.map_err(|err| {
+            Error::ManualHttpClientError(format!("SpaceId subgraph fetch | error: {:?}", err))
+        })?,
+        Err(_) => {
+            return Err(Error::ManualHttpClientError(
+                "SpaceId subgraph fetch | Timeout: no response in 5 seconds.".to_string(),
+            ))
+        }
+    };
+
+    Ok(data
+        .map(|data| data.domains)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|domain| domain.name)
+        .map(|name| name.to_lowercase())
+        .filter(|name| !name.starts_with('[') && tld_of(name) == Some(*tld))
+        .collect())
+}
+
+async fn request<T>(uri: &str) -> Result<T, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
     let client = make_client();
-    let uri: http::Uri = format!(
-        "{}/v1/getName?tld=bnb&address={}",
-        C.upstream.spaceid_api.url.clone(),
-        address
-    )
-    .parse()
-    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let uri: http::Uri = uri
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
 
     let req = Request::builder()
         .method(Method::GET)
@@ -297,18 +469,19 @@ async fn get_name(address: &str) -> Result<Option<String>, Error> {
         return Err(Error::General(err_message, resp.status()));
     }
 
-    let result = match parse_body::<ReverseResolveResponse>(&mut resp).await {
-        Ok(result) => result,
+    let body: Value = parse_body(&mut resp).await?;
+    match serde_json::from_value::<T>(body.clone()) {
+        Ok(result) => Ok(result),
         Err(_) => {
-            let err: BadResponse = parse_body(&mut resp).await?;
+            let err: BadResponse = serde_json::from_value(body)?;
+            // code=1, domain name is invalid
             // code=1, rpc error
             let err_message = format!(
                 "SpaceId fetch error, Code: {}, Message: {}",
                 err.code, err.msg
             );
             error!(err_message);
-            return Err(Error::General(err_message, resp.status()));
+            Err(Error::General(err_message, resp.status()))
         }
-    };
-    Ok(result.name)
+    }
 }
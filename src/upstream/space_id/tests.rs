@@ -1,14 +1,134 @@
 #[cfg(test)]
 mod tests {
+    use crate::config::C;
     use crate::error::Error;
-    use crate::upstream::space_id::{get_address, get_name};
+    use crate::graph::vertex::contract::Chain;
+    use crate::tests::{spawn_rpc_server, spawn_stub_server};
+    use crate::upstream::space_id::{
+        get_address, get_name, get_owned_names, name_expires, tld_of, SELECTOR_NAME_EXPIRES, TLDS,
+    };
+    use crate::util::timestamp_to_naive;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use warp::Filter;
+
+    #[test]
+    fn test_tld_of() {
+        assert_eq!(tld_of("sujiyan.bnb").unwrap().chain, Chain::BNBSmartChain);
+        assert_eq!(tld_of("Sujiyan.ARB").unwrap().api_tld, "arb1");
+        assert!(tld_of("sujiyan.eth").is_none());
+        assert!(tld_of("sujiyan").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_route_by_tld() -> Result<(), Error> {
+        let get_name_route = warp::path!("v1" / "getName")
+            .and(warp::query::<HashMap<String, String>>())
+            .map(|q: HashMap<String, String>| {
+                let name = match q.get("tld").map(|t| t.as_str()) {
+                    Some("bnb") => json!("stub.bnb"),
+                    Some("arb1") => json!("stub.arb"),
+                    _ => json!(null),
+                };
+                warp::reply::json(&json!({ "code": 0, "name": name }))
+            });
+        let get_address_route = warp::path!("v1" / "getAddress")
+            .and(warp::query::<HashMap<String, String>>())
+            .map(|q: HashMap<String, String>| {
+                if q.get("tld").map(|t| t.as_str()) == Some("arb1") {
+                    warp::reply::json(&json!({
+                        "code": 0,
+                        "address": "0x934B510D4C9103E6a87AEf13b816fb080286D649"
+                    }))
+                } else {
+                    warp::reply::json(&json!({ "code": 1, "msg": "invalid domain" }))
+                }
+            });
+        let base = spawn_stub_server(get_name_route.or(get_address_route));
+
+        let address = "0x934b510d4c9103e6a87aef13b816fb080286d649";
+        assert_eq!(
+            get_name(&base, &TLDS[0], address).await?,
+            Some("stub.bnb".to_string())
+        );
+        assert_eq!(
+            get_name(&base, &TLDS[1], address).await?,
+            Some("stub.arb".to_string())
+        );
+        let arb = tld_of("stub.arb").unwrap();
+        assert_eq!(
+            get_address(&base, &arb, "stub.arb").await?.to_lowercase(),
+            address
+        );
+        let bnb = tld_of("stub.bnb").unwrap();
+        assert!(get_address(&base, &bnb, "stub.bnb").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_name_expires() -> Result<(), Error> {
+        // keccak256("stub")
+        let token_id = "9254b1264efc54b3f0d9de32fa21732f6f17661924748869903eb331725b0e00";
+        let expected = format!("{}{}", SELECTOR_NAME_EXPIRES, token_id);
+        let rpc = spawn_rpc_server(move |_, params| {
+            Ok(match params[0]["data"].as_str() {
+                Some(data) if data == expected => json!(format!("0x{:064x}", 1735689600)),
+                _ => json!(format!("0x{:064x}", 0)),
+            })
+        });
+
+        let bnb = tld_of("stub.bnb").unwrap();
+        assert_eq!(
+            name_expires(&rpc, &bnb, "Stub.bnb").await,
+            Some(timestamp_to_naive(1735689600, 0))
+        );
+        // Never registered
+        assert_eq!(name_expires(&rpc, &bnb, "other.bnb").await, None);
+        // Subnames are not held by the registrar.
+        assert_eq!(name_expires(&rpc, &bnb, "sub.stub.bnb").await, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_owned_names() -> Result<(), Error> {
+        let subgraph = warp::post()
+            .and(warp::body::json())
+            .map(|body: serde_json::Value| {
+                assert_eq!(
+                    body["variables"]["owner"],
+                    "0x934b510d4c9103e6a87aef13b816fb080286d649"
+                );
+                warp::reply::json(&json!({
+                    "data": {
+                        "domains": [
+                            { "name": "Stub.bnb" },
+                            { "name": "other.bnb" },
+                            { "name": "[9254b1264efc54b3f0d9de32fa21732f6f17661924748869903eb331725b0e00].bnb" },
+                            { "name": null },
+                            { "name": "stub.arb" },
+                        ]
+                    }
+                }))
+            });
+        let base = spawn_stub_server(subgraph);
+
+        let bnb = tld_of("stub.bnb").unwrap();
+        let names =
+            get_owned_names(&base, &bnb, "0x934B510D4C9103E6a87AEf13b816fb080286D649").await?;
+        assert_eq!(names, vec!["stub.bnb", "other.bnb"]);
+        Ok(())
+    }
 
     #[tokio::test]
     async fn test_get_address() -> Result<(), Error> {
         // let domain = "nopayable.bnb";
         let domain = "sujiyan.bnb";
-        let address = get_address(&domain).await?;
-        println!("address: {:?}", address.to_lowercase());
+        let tld = tld_of(domain).unwrap();
+        let address = get_address(&C.upstream.spaceid_api.url, &tld, &domain).await?;
+        assert!(address.starts_with("0x"));
+        assert_eq!(address.len(), 42);
         Ok(())
     }
 
@@ -16,8 +136,12 @@ mod tests {
     async fn test_get_name() -> Result<(), Error> {
         // 0xB86fF7E3F4E6186DfD25cFF40605441D0c0481c4
         let address = "0x934b510d4c9103e6a87aef13b816fb080286d649";
-        let name = get_name(&address).await?;
-        println!("name: {:?}", name);
+        for tld in TLDS.iter() {
+            let name = get_name(&C.upstream.spaceid_api.url, tld, &address).await?;
+            if let Some(name) = name {
+                assert_eq!(tld_of(&name), Some(*tld));
+            }
+        }
         Ok(())
     }
 }
@@ -4,6 +4,7 @@ mod tests;
 
 use crate::config::C;
 use crate::error::Error;
+use crate::graph::checkpoint::Checkpoint;
 use crate::graph::edge::ProofRecord;
 use crate::graph::{edge::Proof, new_db_connection, vertex::Identity};
 use crate::graph::{Edge, Vertex};
@@ -16,6 +17,9 @@ use http::StatusCode;
 use hyper::{Body, Method};
 use serde::Deserialize;
 use serde_json::{Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
 use tracing::{debug, info};
 
 use uuid::Uuid;
@@ -51,11 +55,12 @@ pub struct ErrorResponse {
 
 pub struct SybilList {}
 
+/// Returns lowercased ETH address if saved.
 async fn save_item(
     db: &DatabaseConnection,
     eth_wallet_address: String,
     value: Value,
-) -> Option<(Platform, String)> {
+) -> Option<String> {
     let item: VerifiedItem = serde_json::from_value(value).ok()?;
 
     let from: Identity = Identity {
@@ -69,6 +74,8 @@ async fn save_item(
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
     let from_record = from.create_or_update(db).await.ok()?;
 
@@ -82,6 +89,8 @@ async fn save_item(
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
     let to_record = to.create_or_update(db).await.ok()?;
 
@@ -96,22 +105,87 @@ async fn save_item(
         )), // millisecond
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        deleted_at: None,
     };
 
     pf.two_way_binding(db, &from_record, &to_record)
         .await
         .ok()?;
-    Some((Platform::Twitter, item.twitter.handle.clone()))
+    Some(eth_wallet_address.to_lowercase())
 }
 
-/// Trigger a refetch from github.
-pub async fn prefetch() -> Result<(), Error> {
+/// Name of the `Checkpoint` of SybilList import.
+pub const CHECKPOINT_NAME: &str = "sybil_list";
+
+/// Result of a SybilList import.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Entries in the downloaded list.
+    pub total: usize,
+    /// Entries newly added (or changed) since last import.
+    pub added: usize,
+    /// Entries removed (or changed) from the list since last import, which are tombstoned.
+    pub delisted: usize,
+    /// `true` if the list is not changed since last import.
+    pub unchanged: bool,
+}
+
+/// `lowercased ETH address => tweet ID` of every valid entry in the list.
+pub type Snapshot = BTreeMap<String, String>;
+
+/// Builds snapshot of a downloaded list. Invalid entries are ignored.
+pub fn to_snapshot(list: &Map<String, Value>) -> Snapshot {
+    list.iter()
+        .filter_map(|(address, value)| {
+            let item: VerifiedItem = serde_json::from_value(value.clone()).ok()?;
+            Some((address.to_lowercase(), item.twitter.tweet_id))
+        })
+        .collect()
+}
+
+/// Compares two snapshots.
+/// Returns `(added addresses, delisted (address, tweet ID))`.
+/// An entry with its tweet changed shows up in both.
+pub fn diff_snapshot(
+    previous: &Snapshot,
+    current: &Snapshot,
+) -> (Vec<String>, Vec<(String, String)>) {
+    let added = current
+        .iter()
+        .filter(|(address, tweet_id)| previous.get(*address) != Some(*tweet_id))
+        .map(|(address, _)| address.clone())
+        .collect();
+    let delisted = previous
+        .iter()
+        .filter(|(address, tweet_id)| current.get(*address) != Some(*tweet_id))
+        .map(|(address, tweet_id)| (address.clone(), tweet_id.clone()))
+        .collect();
+    (added, delisted)
+}
+
+fn content_hash(content: &str) -> String {
+    // Only used to tell if the content changed, a changed hasher between builds just causes a full diff.
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Downloads the list. Returns `None` if it is not modified since `etag`,
+/// otherwise the new `ETag` (if any) and the content.
+async fn download(
+    uri: &str,
+    etag: Option<&str>,
+) -> Result<Option<(Option<String>, String)>, Error> {
     let client = make_client();
-    let uri: http::Uri = (C.upstream.sybil_service.url).parse().unwrap();
+    let uri: http::Uri = uri
+        .parse()
+        .map_err(|_err| Error::ParamError(format!("Uri format Error {}", _err)))?;
 
-    let req = hyper::Request::builder()
-        .method(Method::GET)
-        .uri(uri)
+    let mut req = hyper::Request::builder().method(Method::GET).uri(uri);
+    if let Some(etag) = etag {
+        req = req.header("If-None-Match", etag);
+    }
+    let req = req
         .body(Body::empty())
         .map_err(|_err| Error::ParamError(format!("SybilList Build Request Error {}", _err)))?;
 
@@ -119,6 +193,9 @@ pub async fn prefetch() -> Result<(), Error> {
         Error::ManualHttpClientError(format!("SybilList fetch | error: {:?}", err.to_string()))
     })?;
 
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
     if !resp.status().is_success() {
         let body: ErrorResponse = parse_body(&mut resp).await?;
         return Err(Error::General(
@@ -127,17 +204,102 @@ pub async fn prefetch() -> Result<(), Error> {
         ));
     }
 
-    // all records in sybil list
-    let body: Map<String, Value> = parse_body(&mut resp).await?;
+    let new_etag = resp
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let body = hyper::body::to_bytes(resp.into_body())
+        .await
+        .map_err(|err| Error::ManualHttpClientError(format!("SybilList fetch | error: {}", err)))?;
+    let content = String::from_utf8_lossy(&body).to_string();
 
-    // parse
+    Ok(Some((new_etag, content)))
+}
+
+/// Trigger a refetch from github.
+/// Only entries changed since last import are saved, and entries removed from the list are tombstoned.
+pub async fn prefetch() -> Result<ImportReport, Error> {
     let db = new_db_connection().await?;
-    let futures: Vec<_> = body
-        .into_iter()
-        .map(|(eth_wallet_address, value)| save_item(&db, eth_wallet_address, value))
+    let mut checkpoint = Checkpoint::find_by_name(&db, CHECKPOINT_NAME)
+        .await?
+        .map(|found| found.record)
+        .unwrap_or_else(|| Checkpoint::new(CHECKPOINT_NAME));
+    let previous: Snapshot =
+        serde_json::from_value(checkpoint.snapshot.clone()).unwrap_or_default();
+
+    let (etag, content) =
+        match download(&C.upstream.sybil_service.url, checkpoint.etag.as_deref()).await? {
+            Some(downloaded) => downloaded,
+            None => {
+                return Ok(ImportReport {
+                    total: previous.len(),
+                    unchanged: true,
+                    ..Default::default()
+                });
+            }
+        };
+    let hash = content_hash(&content);
+    if checkpoint.content_hash.as_ref() == Some(&hash) {
+        checkpoint.etag = etag;
+        checkpoint.upsert(&db).await?;
+        return Ok(ImportReport {
+            total: previous.len(),
+            unchanged: true,
+            ..Default::default()
+        });
+    }
+
+    // all records in sybil list
+    let list: Map<String, Value> = serde_json::from_str(&content)?;
+    let mut current = to_snapshot(&list);
+    let (added, delisted) = diff_snapshot(&previous, &current);
+
+    let to_add: HashSet<&String> = added.iter().collect();
+    let futures: Vec<_> = list
+        .iter()
+        .filter(|(address, _)| to_add.contains(&address.to_lowercase()))
+        .map(|(address, value)| save_item(&db, address.clone(), value.clone()))
+        .collect();
+    let saved: HashSet<String> = join_all(futures).await.into_iter().flatten().collect();
+    let added_count = saved.len();
+    // Entries failed to save are left out of snapshot, so they will be retried next time.
+    let failed: Vec<&String> = added
+        .iter()
+        .filter(|address| !saved.contains(*address))
         .collect();
-    let _ = join_all(futures).await;
-    Ok(())
+    for address in failed.iter() {
+        current.remove(*address);
+    }
+
+    let mut delisted_count = 0;
+    for (address, tweet_id) in delisted.iter() {
+        let tombstoned = Proof::tombstone(&db, &DataSource::SybilList, tweet_id).await?;
+        debug!(
+            "SybilList: {} (tweet {}) delisted, {} connections tombstoned",
+            address, tweet_id, tombstoned
+        );
+        delisted_count += 1;
+    }
+
+    // With failed entries, neither etag nor hash is kept, or the list would come back
+    // as `304 Not Modified` / unchanged next time and they would never be retried.
+    if failed.is_empty() {
+        checkpoint.etag = etag;
+        checkpoint.content_hash = Some(hash);
+    } else {
+        checkpoint.etag = None;
+        checkpoint.content_hash = None;
+    }
+    checkpoint.snapshot = serde_json::to_value(&current)?;
+    checkpoint.upsert(&db).await?;
+
+    Ok(ImportReport {
+        total: current.len(),
+        added: added_count,
+        delisted: delisted_count,
+        unchanged: false,
+    })
 }
 
 #[async_trait]
@@ -165,7 +327,8 @@ impl Fetcher for SybilList {
             Platform::Ethereum => {
                 let filter =
                     Filter::new(Comparison::field("_from").equals_str(found.unwrap().id()))
-                        .and(Comparison::field("source").equals_str(DataSource::SybilList));
+                        .and(Comparison::field("source").equals_str(DataSource::SybilList))
+                        .and(Comparison::field("deleted_at").is_null());
                 let result: QueryResult<EdgeRecord<Proof>> = EdgeRecord::<Proof>::query()
                     .filter(filter)
                     .call(&db)
@@ -186,7 +349,8 @@ impl Fetcher for SybilList {
             }
             Platform::Twitter => {
                 let filter = Filter::new(Comparison::field("_to").equals_str(found.unwrap().id()))
-                    .and(Comparison::field("source").equals_str(DataSource::SybilList));
+                    .and(Comparison::field("source").equals_str(DataSource::SybilList))
+                    .and(Comparison::field("deleted_at").is_null());
                 let result: QueryResult<EdgeRecord<Proof>> = EdgeRecord::<Proof>::query()
                     .filter(filter)
                     .call(&db)
@@ -1,12 +1,15 @@
 use crate::{
     error::Error,
     graph::{new_db_connection, vertex::Identity},
+    tests::spawn_stub_server,
     upstream::{
-        sybil_list::{prefetch, SybilList},
+        sybil_list::{diff_snapshot, download, prefetch, to_snapshot, Snapshot, SybilList},
         Target,
     },
     upstream::{Fetcher, Platform},
 };
+use serde_json::{json, Map, Value};
+use warp::Filter;
 
 #[tokio::test]
 async fn test_get_sybil_result() -> Result<(), Error> {
@@ -34,3 +37,61 @@ async fn test_get_sybil_result() -> Result<(), Error> {
 
     Ok(())
 }
+
+fn entry(tweet_id: &str) -> Value {
+    json!({ "twitter": { "timestamp": 1654669460431i64, "tweetID": tweet_id, "handle": "someone" } })
+}
+
+#[test]
+fn test_diff_snapshot() {
+    let mut list: Map<String, Value> = Map::new();
+    list.insert("0xAAAA".into(), entry("1"));
+    list.insert("0xbbbb".into(), entry("2"));
+    list.insert("0xcccc".into(), json!({ "broken": true }));
+    let previous = to_snapshot(&list);
+    assert_eq!(previous.len(), 2);
+    assert_eq!(previous.get("0xaaaa"), Some(&"1".to_string()));
+
+    // 0xaaaa removed, 0xbbbb tweet changed, 0xdddd added.
+    let mut current = Snapshot::new();
+    current.insert("0xbbbb".into(), "3".into());
+    current.insert("0xdddd".into(), "4".into());
+    let (added, delisted) = diff_snapshot(&previous, &current);
+    assert_eq!(added, vec!["0xbbbb".to_string(), "0xdddd".to_string()]);
+    assert_eq!(
+        delisted,
+        vec![
+            ("0xaaaa".to_string(), "1".to_string()),
+            ("0xbbbb".to_string(), "2".to_string())
+        ]
+    );
+
+    let (added, delisted) = diff_snapshot(&current, &current);
+    assert!(added.is_empty());
+    assert!(delisted.is_empty());
+}
+
+#[tokio::test]
+async fn test_download_with_etag() -> Result<(), Error> {
+    let list = warp::header::optional::<String>("if-none-match").map(|etag: Option<String>| {
+        if etag.as_deref() == Some("\"v1\"") {
+            warp::http::Response::builder()
+                .status(304)
+                .body("".to_string())
+                .unwrap()
+        } else {
+            warp::http::Response::builder()
+                .header("etag", "\"v1\"")
+                .body(json!({ "0xaaaa": entry("1") }).to_string())
+                .unwrap()
+        }
+    });
+    let base = spawn_stub_server(list);
+
+    let (etag, content) = download(&base, None).await?.expect("Should be downloaded");
+    assert_eq!(etag, Some("\"v1\"".to_string()));
+    assert!(content.contains("0xaaaa"));
+    assert!(download(&base, etag.as_deref()).await?.is_none());
+
+    Ok(())
+}
@@ -5,12 +5,13 @@ use crate::{
     config::C,
     error::Error,
     graph::{
-        create_identity_to_contract_record,
-        edge::{hold::Hold, resolve::DomainNameSystem, Resolve},
+        create_domain_own_record, create_domain_resolves_to_record,
+        create_domain_subdomain_record, create_identity_to_contract_record,
+        edge::{hold::Hold, resolve::DomainNameSystem, Own, Resolve, ResolvesTo, Subdomain},
         new_db_connection,
         vertex::{
-            contract::{Chain, ContractCategory},
-            Contract, ContractRecord, Identity,
+            contract::{Chain, ContractCategory, ENS_NAME_WRAPPER},
+            Contract, ContractRecord, Domain as DomainVertex, Identity, Token,
         },
         Edge, Vertex,
     },
@@ -19,6 +20,7 @@ use crate::{
 };
 use aragog::DatabaseConnection;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use gql_client::Client;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
@@ -38,8 +40,12 @@ struct QueryResponse {
 
 #[derive(Deserialize, Debug, Clone)]
 struct Domain {
+    /// Namehash of this ENS.
+    id: String,
     /// ENS name (`something.eth`)
     name: String,
+    /// Keccak256 of the first label. `null` if the label is unknown.
+    labelhash: Option<String>,
     /// Creation timestamp (in secods)
     #[serde(rename = "createdAt")]
     created_at: String,
@@ -50,6 +56,56 @@ struct Domain {
     resolved_address: Option<Account>,
     /// Owner info
     owner: Account,
+    /// Registration info. Only `.eth` 2LDs have this.
+    registration: Option<Registration>,
+    /// NameWrapper info. Only wrapped domains have this.
+    #[serde(skip)]
+    wrapper: Option<Wrapper>,
+}
+
+impl Domain {
+    /// Wrapped domains are held by NameWrapper, others by the base registrar.
+    fn contract_address(&self) -> String {
+        match self.wrapper {
+            Some(_) => ENS_NAME_WRAPPER.to_lowercase(),
+            None => ContractCategory::ENS.default_contract_address().unwrap(),
+        }
+    }
+
+    /// Metadata served by ENS. `None` if this domain is not an NFT, i.e. an unwrapped subdomain.
+    /// See also: https://metadata.ens.domains/docs
+    fn token_uri(&self) -> Option<String> {
+        // Token ID is namehash in NameWrapper, and labelhash in the base registrar.
+        let token_id = match (&self.wrapper, &self.registration) {
+            (Some(_), _) => &self.id,
+            (None, Some(_)) => self.labelhash.as_ref()?,
+            (None, None) => return None,
+        };
+        Some(format!(
+            "https://metadata.ens.domains/mainnet/{}/{}",
+            self.contract_address(),
+            token_id
+        ))
+    }
+
+    /// Registration expiry for `.eth` 2LDs, NameWrapper expiry for wrapped subdomains.
+    fn expired_at(&self) -> Option<NaiveDateTime> {
+        let expiry_date = match (&self.registration, &self.wrapper) {
+            (Some(registration), _) => &registration.expiry_date,
+            (None, Some(wrapper)) => &wrapper.expiry_date,
+            (None, None) => return None,
+        };
+        parse_timestamp(expiry_date)
+            .ok()
+            .filter(|expired_at| expired_at.timestamp() > 0)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Registration {
+    /// Expiration timestamp (in seconds), grace period excluded.
+    #[serde(rename = "expiryDate")]
+    expiry_date: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -57,6 +113,18 @@ struct WrappedDomain {
     name: String,
     owner: Account,
     domain: Domain,
+    #[serde(flatten)]
+    wrapper: Wrapper,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Wrapper {
+    /// Fuses burned on this domain.
+    fuses: u32,
+    /// Expiration timestamp (in seconds) in NameWrapper.
+    /// For `.eth` 2LDs, grace period included. `0` if it never expires.
+    #[serde(rename = "expiryDate")]
+    expiry_date: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -74,7 +142,9 @@ struct DomainEvent {
 const QUERY_BY_ENS: &str = r#"
         query OwnerAddressByENS($target: String!){
             domains(where: { name: $target }) {
+                id
                 name
+                labelhash
                 createdAt
                 events(first: 1) {
                     transactionID
@@ -82,6 +152,9 @@ const QUERY_BY_ENS: &str = r#"
                 resolvedAddress {
                   id
                 }
+                registration {
+                  expiryDate
+                }
                 owner{
                   id
                 }
@@ -89,7 +162,9 @@ const QUERY_BY_ENS: &str = r#"
             wrappedDomains(where: { name: $target }) {
               name
               domain {
+                id
                 name
+                labelhash
                 createdAt
                 events(first: 1) {
                     transactionID
@@ -97,6 +172,9 @@ const QUERY_BY_ENS: &str = r#"
                 resolvedAddress {
                   id
                 }
+                registration {
+                  expiryDate
+                }
                 owner{
                   id
                 }
@@ -104,6 +182,8 @@ const QUERY_BY_ENS: &str = r#"
               owner {
                 id
               }
+              fuses
+              expiryDate
             }
         }
     "#;
@@ -111,7 +191,9 @@ const QUERY_BY_ENS: &str = r#"
 const QUERY_BY_WALLET: &str = r#"
         query ENSByOwnerAddress($target: String!){
             domains(where: { owner: $target }) {
+                id
                 name
+                labelhash
                 createdAt
                 events(first: 1) {
                     transactionID
@@ -119,6 +201,9 @@ const QUERY_BY_WALLET: &str = r#"
                 resolvedAddress {
                   id
                 }
+                registration {
+                  expiryDate
+                }
                 owner {
                   id
                 }
@@ -126,7 +211,9 @@ const QUERY_BY_WALLET: &str = r#"
             wrappedDomains(where: { owner: $target }) {
               name
               domain {
+                id
                 name
+                labelhash
                 createdAt
                 events(first: 1) {
                     transactionID
@@ -134,6 +221,9 @@ const QUERY_BY_WALLET: &str = r#"
                 resolvedAddress {
                   id
                 }
+                registration {
+                  expiryDate
+                }
                 owner{
                   id
                 }
@@ -141,6 +231,8 @@ const QUERY_BY_WALLET: &str = r#"
               owner {
                 id
               }
+              fuses
+              expiryDate
             }
         }
     "#;
@@ -213,6 +305,7 @@ async fn perform_fetch(target: &Target) -> Result<TargetProcessedList, Error> {
         debug!(?target, domain = wd.name, "TheGraph: Wrapped ENS found.");
         let mut domain = wd.domain.clone();
         domain.owner = wd.owner;
+        domain.wrapper = Some(wd.wrapper);
         merged_domains.push(domain);
     }
     for domain in res.domains.into_iter() {
@@ -252,6 +345,8 @@ async fn perform_fetch(target: &Target) -> Result<TargetProcessedList, Error> {
                         avatar_url: None,
                         profile_url: None,
                         updated_at: naive_now(),
+                        chains: vec![],
+                        is_contract: None,
                     }
                     .create_or_update(&db)
                     .await?;
@@ -268,6 +363,18 @@ async fn perform_fetch(target: &Target) -> Result<TargetProcessedList, Error> {
                     resolve
                         .connect(&db, &resolve_target, &contract_record)
                         .await?;
+                    let resolves_to = ResolvesTo {
+                        source: DataSource::TheGraph,
+                        fetcher: DataFetcher::RelationService,
+                        ..Default::default()
+                    };
+                    create_domain_resolves_to_record(
+                        &db,
+                        &DomainVertex::new(DomainNameSystem::ENS, &domain.name),
+                        &resolve_target,
+                        &resolves_to,
+                    )
+                    .await?;
                 }
             }
             None => {
@@ -281,7 +388,7 @@ async fn perform_fetch(target: &Target) -> Result<TargetProcessedList, Error> {
             Target::Identity(_, _) => next_targets.push(Target::NFT(
                 Chain::Ethereum,
                 ContractCategory::ENS,
-                ContractCategory::ENS.default_contract_address().unwrap(),
+                domain.contract_address(),
                 domain.name.clone(),
             )),
             Target::NFT(_, _, _, _) => {
@@ -309,6 +416,7 @@ async fn create_or_update_own(
         .first() // TODO: really?
         .map(|event| event.transaction_id.clone());
     let ens_created_at = parse_timestamp(&domain.created_at).ok();
+    let ens_expired_at = domain.expired_at();
     let owner = Identity {
         uuid: Some(Uuid::new_v4()),
         platform: Platform::Ethereum,
@@ -319,13 +427,19 @@ async fn create_or_update_own(
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
     let conrtract = Contract {
         uuid: Uuid::new_v4(),
         category: ContractCategory::ENS,
-        address: ContractCategory::ENS.default_contract_address().unwrap(),
+        address: domain.contract_address(),
         chain: Chain::Ethereum,
         symbol: None,
+        name: None,
+        total_supply: None,
+        deployer: None,
+        verified: true,
         updated_at: naive_now(),
     };
     let ownership: Hold = Hold {
@@ -336,9 +450,69 @@ async fn create_or_update_own(
         created_at: ens_created_at,
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: ens_expired_at,
+        amount: None,
+        decimals: None,
     };
     let (owner_record, contract_record, _hold_record) =
         create_identity_to_contract_record(db, &owner, &conrtract, &ownership).await?;
+    // Domain moves between the base registrar and NameWrapper when it is (un)wrapped.
+    for address in ContractCategory::ENS.contract_addresses() {
+        if address == conrtract.address {
+            continue;
+        }
+        if let Some(other) =
+            Contract::find_by_chain_address(db, &Chain::Ethereum, &address).await?
+        {
+            Hold::close(db, &other.0, &domain.name, None, naive_now()).await?;
+        }
+    }
+    if let Some(token_uri) = domain.token_uri() {
+        Token {
+            chain: Chain::Ethereum,
+            address: conrtract.address.clone(),
+            token_id: domain.name.clone(),
+            name: Some(domain.name.clone()),
+            description: Some(format!("{}, an ENS name.", domain.name)),
+            image: Some(format!("{}/image", token_uri)),
+            token_uri: Some(token_uri),
+            source: DataSource::TheGraph,
+            ..Default::default()
+        }
+        .create_or_update(db)
+        .await?;
+    }
+    let own = Own {
+        source: DataSource::TheGraph,
+        fetcher: DataFetcher::RelationService,
+        ..Default::default()
+    };
+    let ens_domain = DomainVertex {
+        expired_at: ens_expired_at,
+        fuses: domain.wrapper.as_ref().map(|w| w.fuses),
+        ..DomainVertex::new(DomainNameSystem::ENS, &domain.name)
+    };
+    create_domain_own_record(db, &owner_record, &ens_domain, &own).await?;
+    if let Some(parent) = ens_domain.parent_name() {
+        let subdomain = Subdomain {
+            uuid: Uuid::new_v4(),
+            source: DataSource::TheGraph,
+            system: DomainNameSystem::ENS,
+            name: ens_domain.name.clone(),
+            fetcher: DataFetcher::RelationService,
+            updated_at: naive_now(),
+        };
+        create_domain_subdomain_record(
+            db,
+            &DomainVertex::new(DomainNameSystem::ENS, &parent),
+            &ens_domain,
+            &subdomain,
+        )
+        .await?;
+    }
 
     let resolve = Resolve {
         uuid: Uuid::new_v4(),
@@ -5,7 +5,7 @@ use crate::{
     graph::{
         edge::Hold,
         new_db_connection,
-        vertex::contract::Chain,
+        vertex::contract::{Chain, ENS_NAME_WRAPPER},
         vertex::Identity,
         vertex::{contract::ContractCategory, Contract},
     },
@@ -137,5 +137,16 @@ async fn test_wrapped_ens_find_by_ens() -> Result<(), Error> {
     let address_targets = TheGraph::fetch(&ens).instrument(log).await?;
     let _wrapped_ens = address_targets.iter().find(|t| t.identity().unwrap() == owner).unwrap();
 
+    // Wrapped domains are held by NameWrapper.
+    let hold = Hold::find_by_id_chain_address(
+        &db,
+        "nykma.eth",
+        &Chain::Ethereum,
+        &ENS_NAME_WRAPPER.to_lowercase(),
+    )
+    .await?
+    .expect("Record not found");
+    assert_eq!(hold.source, DataSource::TheGraph);
+
     Ok(())
 }
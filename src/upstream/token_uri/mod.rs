@@ -0,0 +1,354 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    config::C,
+    error::Error,
+    graph::{
+        new_db_connection,
+        vertex::{
+            contract::{Chain, ContractCategory},
+            Contract, Token, TokenAttribute, Vertex,
+        },
+    },
+    upstream::{
+        eth_rpc::{self, decimal_to_word, decode_string},
+        DataSource, Fetcher, Target, TargetProcessedList,
+    },
+    util::{ipfs_to_http, request_with_timeout},
+};
+use aragog::DatabaseConnection;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use http::{uri::InvalidUri, Response};
+use hyper::{
+    body::HttpBody as _,
+    client::{connect::dns::Name, HttpConnector},
+    service::Service,
+    Body, Client, Method, Request,
+};
+use hyper_tls::HttpsConnector;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    future::{ready, Ready},
+    net::{IpAddr, SocketAddr},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::net::lookup_host;
+use tracing::{debug, warn};
+
+/// `tokenURI(uint256)` of ERC721.
+pub const SELECTOR_TOKEN_URI: &str = "0xc87b56dd";
+/// `uri(uint256)` of ERC1155.
+pub const SELECTOR_URI: &str = "0x0e89341c";
+/// Metadata larger than this is refused.
+pub const MAX_METADATA_SIZE: usize = 1024 * 1024;
+/// Time to read a metadata body in.
+const BODY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Metadata JSON of ERC721 / ERC1155, with OpenSea's extensions.
+/// https://eips.ethereum.org/EIPS/eip-721
+/// https://docs.opensea.io/docs/metadata-standards
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TokenMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<Value>,
+}
+
+/// Reads metadata of NFTs from `tokenURI()` (ERC721) or `uri()` (ERC1155)
+/// on chains in `upstream.token_uri.chains`.
+pub struct TokenUri {}
+
+#[async_trait]
+impl Fetcher for TokenUri {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+
+        match target {
+            Target::Identity(_, _) => Ok(vec![]),
+            Target::NFT(chain, category, address, token_id) => {
+                let rpc = match chain.info().and_then(|i| i.rpc_url.clone()) {
+                    Some(rpc) => rpc,
+                    None => {
+                        warn!("TokenUri: no rpc_url configured for {}, skipped", chain);
+                        return Ok(vec![]);
+                    }
+                };
+                let db = new_db_connection().await?;
+                save_token(&db, &rpc, chain, category, address, token_id).await?;
+                Ok(vec![])
+            }
+        }
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_nft_supported(
+            vec![
+                ContractCategory::ERC721,
+                ContractCategory::ERC1155,
+                ContractCategory::POAP,
+            ],
+            C.upstream.token_uri.chains.clone(),
+        )
+    }
+}
+
+/// Saves metadata of `token_id` in `address` on `chain` (by its `rpc` node),
+/// unless it is fetched recently.
+pub async fn save_token(
+    db: &DatabaseConnection,
+    rpc: &str,
+    chain: &Chain,
+    category: &ContractCategory,
+    address: &str,
+    token_id: &str,
+) -> Result<(), Error> {
+    let address = address.to_lowercase();
+    if let Some(found) =
+        Token::find_by_chain_address_token_id(db, chain, &address, token_id).await?
+    {
+        if found.token_uri.is_some() && !found.is_outdated() {
+            return Ok(());
+        }
+    }
+
+    let word = decimal_to_word(token_id)
+        .ok_or_else(|| Error::ParamError(format!("TokenUri: invalid token ID {}", token_id)))?;
+    let selector = match category {
+        ContractCategory::ERC1155 => SELECTOR_URI,
+        _ => SELECTOR_TOKEN_URI,
+    };
+    let result = eth_rpc::eth_call(rpc, &address, &format!("{}{}", selector, word), None).await?;
+    let token_uri = match decode_string(&result).filter(|uri| !uri.is_empty()) {
+        Some(token_uri) => token_uri,
+        None => {
+            debug!(
+                "TokenUri: {} #{} on {} has no URI",
+                address, token_id, chain
+            );
+            return Ok(());
+        }
+    };
+    // ERC1155: clients replace `{id}` with the token ID in hex.
+    let token_uri = token_uri.replace("{id}", &word);
+    let metadata = fetch_metadata(&token_uri).await?;
+
+    Token {
+        chain: *chain,
+        address,
+        token_id: token_id.to_string(),
+        name: metadata.name,
+        description: metadata.description,
+        image: metadata
+            .image
+            .or(metadata.image_url)
+            .map(|i| ipfs_to_http(&i)),
+        attributes: metadata
+            .attributes
+            .iter()
+            .filter_map(TokenAttribute::from_json)
+            .collect(),
+        token_uri: Some(token_uri),
+        source: DataSource::RPC,
+        ..Default::default()
+    }
+    .create_or_update(db)
+    .await?;
+
+    // Collections not in `upstream.log_indexer` have their `name()` / `totalSupply()`
+    // and deployer read here.
+    if let Some(found) = Contract::find_by_chain_address(db, chain, &address).await? {
+        if found.name.is_none() {
+            let (name, total_supply) = eth_rpc::contract_metadata(rpc, &address).await;
+            let deployer = match found.deployer.clone() {
+                Some(deployer) => Some(deployer),
+                None => eth_rpc::deployer_of(rpc, &address)
+                    .await
+                    .unwrap_or_else(|err| {
+                        warn!("TokenUri: fail to find deployer of {}: {}", address, err);
+                        None
+                    }),
+            };
+            if name.is_some() || total_supply.is_some() || deployer.is_some() {
+                Contract {
+                    name,
+                    total_supply,
+                    deployer,
+                    ..Contract::clone(&found)
+                }
+                .create_or_update(db)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Metadata served at `token_uri`: a `data:` URI, or an HTTP / IPFS / Arweave URL.
+pub async fn fetch_metadata(token_uri: &str) -> Result<TokenMetadata, Error> {
+    if let Some(data) = token_uri.strip_prefix("data:") {
+        return parse_data_uri(data);
+    }
+
+    let uri: http::Uri = ipfs_to_http(token_uri)
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let client = pinned_client(check_public_uri(&uri).await?);
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("TokenUri Build Request Error {}", _err)))?;
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("TokenUri fetch | error: {:?}", err.to_string()))
+    })?;
+    if !resp.status().is_success() {
+        return Err(Error::General(
+            format!("TokenUri fetch error, status {}", resp.status()),
+            resp.status(),
+        ));
+    }
+    let body = read_body(&mut resp, MAX_METADATA_SIZE).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Reads body of `resp` in, up to `limit` bytes.
+pub async fn read_body(resp: &mut Response<Body>, limit: usize) -> Result<Vec<u8>, Error> {
+    let read = async {
+        let mut body: Vec<u8> = vec![];
+        while let Some(chunk) = resp.body_mut().data().await {
+            let chunk = chunk.map_err(|err| {
+                Error::ManualHttpClientError(format!("TokenUri fetch | error: {}", err))
+            })?;
+            if body.len() + chunk.len() > limit {
+                return Err(Error::ParamError(format!(
+                    "TokenUri: metadata larger than {} bytes",
+                    limit
+                )));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok::<_, Error>(body)
+    };
+    tokio::time::timeout(BODY_TIMEOUT, read)
+        .await
+        .map_err(|_| {
+            Error::ManualHttpClientError(format!("TokenUri fetch | no body in {:?}", BODY_TIMEOUT))
+        })?
+}
+
+/// Resolves any host to the addresses checked by `check_public_uri`.
+/// Connecting to a host must not look it up again, or it could be rebound to an internal address in between.
+#[derive(Clone)]
+pub struct PinnedResolver(Vec<IpAddr>);
+
+impl Service<Name> for PinnedResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = std::io::Error;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Port is set by `HttpConnector` afterwards.
+    fn call(&mut self, _name: Name) -> Self::Future {
+        let addrs: Vec<SocketAddr> = self.0.iter().map(|ip| SocketAddr::new(*ip, 0)).collect();
+        ready(Ok(addrs.into_iter()))
+    }
+}
+
+/// HTTP(S) client which only connects to `ips`, whatever the host of a request is.
+pub fn pinned_client(ips: Vec<IpAddr>) -> Client<HttpsConnector<HttpConnector<PinnedResolver>>> {
+    let mut http = HttpConnector::new_with_resolver(PinnedResolver(ips));
+    http.enforce_http(false);
+    Client::builder().build::<_, Body>(HttpsConnector::new_with_connector(http))
+}
+
+/// `tokenURI()` is set by anyone who deploys a contract, so only HTTP(S) URLs of public hosts
+/// are fetched. Otherwise it could make us request internal services.
+/// Returns addresses of the host, to connect to them only (see `PinnedResolver`).
+pub async fn check_public_uri(uri: &http::Uri) -> Result<Vec<IpAddr>, Error> {
+    let port = match uri.scheme_str() {
+        Some("https") => uri.port_u16().unwrap_or(443),
+        Some("http") => uri.port_u16().unwrap_or(80),
+        _ => {
+            return Err(Error::ParamError(format!(
+                "TokenUri: unsupported scheme {}",
+                uri
+            )))
+        }
+    };
+    let host = uri
+        .host()
+        .ok_or_else(|| Error::ParamError(format!("TokenUri: no host in {}", uri)))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let ips: Vec<IpAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => lookup_host((host, port))
+            .await
+            .map_err(|err| Error::ParamError(format!("TokenUri: resolve {} | {}", host, err)))?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+    if ips.is_empty() || !ips.iter().all(is_public_ip) {
+        return Err(Error::ParamError(format!(
+            "TokenUri: {} is not a public host",
+            host
+        )));
+    }
+    Ok(ips)
+}
+
+/// Not loopback, private, link-local, shared (CGNAT) or otherwise reserved.
+pub fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, _, _] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ipv4) = ip.to_ipv4_mapped() {
+                return is_public_ip(&IpAddr::V4(ipv4));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // Link-local fe80::/10
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// `application/json;base64,eyJuYW1lIjoi...` or `application/json,{"name":"..."}`
+pub fn parse_data_uri(data: &str) -> Result<TokenMetadata, Error> {
+    let (header, body) = data
+        .split_once(',')
+        .ok_or_else(|| Error::ParamError("TokenUri: invalid data URI".to_string()))?;
+    let json = if header.ends_with(";base64") {
+        BASE64
+            .decode(body)
+            .map_err(|_| Error::ParamError("TokenUri: invalid base64 data URI".to_string()))?
+    } else {
+        body.as_bytes().to_vec()
+    };
+    Ok(serde_json::from_slice(&json)?)
+}
@@ -0,0 +1,180 @@
+use crate::{
+    error::Error,
+    graph::{
+        new_db_connection,
+        vertex::{
+            contract::{Chain, ContractCategory},
+            Contract, Token, TokenAttribute, Vertex,
+        },
+    },
+    tests::{spawn_json_server, spawn_rpc_server},
+    upstream::{
+        eth_rpc::SELECTOR_NAME,
+        token_uri::{
+            fetch_metadata, is_public_ip, parse_data_uri, pinned_client, read_body, save_token,
+            MAX_METADATA_SIZE, SELECTOR_TOKEN_URI,
+        },
+        DataSource,
+    },
+    util::request_with_timeout,
+};
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// ABI encodes `s` as the only return value.
+fn encode_string(s: &str) -> String {
+    let hex: String = s.bytes().map(|b| format!("{:02x}", b)).collect();
+    let padded = (hex.len() + 63) / 64 * 64;
+    format!(
+        "0x{:0>64}{:0>64x}{:0<width$}",
+        "20",
+        s.len(),
+        hex,
+        width = padded
+    )
+}
+
+#[test]
+fn test_parse_data_uri() -> Result<(), Error> {
+    // {"name":"A"}
+    let metadata = parse_data_uri("application/json;base64,eyJuYW1lIjoiQSJ9")?;
+    assert_eq!(metadata.name, Some("A".into()));
+    let metadata = parse_data_uri(r#"application/json;utf8,{"name":"B","attributes":[]}"#)?;
+    assert_eq!(metadata.name, Some("B".into()));
+    assert!(parse_data_uri("application/json;base64").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_is_public_ip() {
+    let public = |ip: &str| is_public_ip(&ip.parse().unwrap());
+    assert!(public("93.184.216.34"));
+    assert!(public("2606:2800:220:1:248:1893:25c8:1946"));
+    assert!(!public("127.0.0.1"));
+    assert!(!public("10.0.0.1"));
+    assert!(!public("172.16.0.1"));
+    assert!(!public("192.168.1.1"));
+    // Cloud metadata service
+    assert!(!public("169.254.169.254"));
+    assert!(!public("100.64.0.1"));
+    assert!(!public("0.0.0.0"));
+    assert!(!public("::1"));
+    assert!(!public("fd00::1"));
+    assert!(!public("fe80::1"));
+    assert!(!public("::ffff:127.0.0.1"));
+}
+
+#[tokio::test]
+async fn test_fetch_metadata_refuses_internal_hosts() {
+    let server = spawn_json_server(vec![("/metadata/1", json!({"name": "A"}))]);
+    assert!(fetch_metadata(&format!("{}/metadata/1", server))
+        .await
+        .is_err());
+    assert!(fetch_metadata("http://localhost/metadata/1").await.is_err());
+    assert!(fetch_metadata("http://[::1]/metadata/1").await.is_err());
+    assert!(fetch_metadata("file:///etc/passwd").await.is_err());
+    assert!(fetch_metadata("gopher://example.com/1").await.is_err());
+}
+
+#[tokio::test]
+async fn test_pinned_client() -> Result<(), Error> {
+    let server = spawn_json_server(vec![("/metadata/1", json!({"name": "A"}))]);
+    let port = server.rsplit(':').next().unwrap();
+    // `.invalid` never resolves, so the request can only reach the pinned address.
+    let client = pinned_client(vec!["127.0.0.1".parse().unwrap()]);
+    let req = Request::get(format!("http://metadata.invalid:{}/metadata/1", port))
+        .body(Body::empty())
+        .unwrap();
+    let mut resp = request_with_timeout(&client, req).await?;
+    let body = read_body(&mut resp, MAX_METADATA_SIZE).await?;
+    assert_eq!(body, br#"{"name":"A"}"#.to_vec());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_body_limit() -> Result<(), Error> {
+    let mut resp = Response::new(Body::from(vec![b' '; 16]));
+    assert_eq!(read_body(&mut resp, 16).await?.len(), 16);
+    let mut resp = Response::new(Body::from(vec![b' '; 17]));
+    assert!(read_body(&mut resp, 16).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save_token() -> Result<(), Error> {
+    // Metadata servers on loopback are refused, so it is served as a `data:` URI.
+    let token_uri = format!(
+        "data:application/json,{}",
+        json!({
+            "name": "Token #1",
+            "description": "A token.",
+            "image": "ipfs://QmImage",
+            "attributes": [{"trait_type": "Level", "value": 5}],
+        })
+    );
+    let contract = format!("0x{:0>40}", &Uuid::new_v4().simple().to_string()[..20]);
+    let data = format!("{}{:0>64}", SELECTOR_TOKEN_URI, "1");
+    let encoded = encode_string(&token_uri);
+    let encoded_name = encode_string("Stub Collection");
+    let expected_contract = contract.clone();
+    let node = spawn_rpc_server(move |method, params| {
+        Ok(match method {
+            "eth_call"
+                if params[0]["to"] == json!(expected_contract)
+                    && params[0]["data"] == json!(data) =>
+            {
+                json!(encoded)
+            }
+            "eth_call"
+                if params[0]["to"] == json!(expected_contract)
+                    && params[0]["data"] == json!(SELECTOR_NAME) =>
+            {
+                json!(encoded_name)
+            }
+            _ => Value::Null,
+        })
+    });
+
+    let db = new_db_connection().await?;
+    Contract {
+        category: ContractCategory::ERC721,
+        address: contract.clone(),
+        chain: Chain::Ethereum,
+        ..Default::default()
+    }
+    .create_or_update(&db)
+    .await?;
+    save_token(
+        &db,
+        &node,
+        &Chain::Ethereum,
+        &ContractCategory::ERC721,
+        &contract,
+        "1",
+    )
+    .await?;
+    let token = Token::find_by_chain_address_token_id(&db, &Chain::Ethereum, &contract, "1")
+        .await?
+        .expect("Token should be saved");
+    assert_eq!(token.name, Some("Token #1".into()));
+    assert_eq!(token.description, Some("A token.".into()));
+    assert_eq!(token.image, Some("https://ipfs.io/ipfs/QmImage".into()));
+    assert_eq!(
+        token.attributes,
+        vec![TokenAttribute {
+            trait_type: "Level".into(),
+            value: "5".into()
+        }]
+    );
+    assert_eq!(token.token_uri, Some(token_uri));
+    assert_eq!(token.source, DataSource::RPC);
+
+    let found = Contract::find_by_chain_address(&db, &Chain::Ethereum, &contract)
+        .await?
+        .expect("Contract should be saved");
+    assert_eq!(found.name, Some("Stub Collection".into()));
+    // `totalSupply()` is not implemented.
+    assert_eq!(found.total_supply, None);
+    Ok(())
+}
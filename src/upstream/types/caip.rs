@@ -0,0 +1,455 @@
+use crate::{
+    error::Error,
+    graph::vertex::contract::{Chain, Contract, ContractCategory},
+    upstream::{eth_rpc::hex_to_decimal, Platform},
+    util::{is_evm_address, is_solana_address},
+};
+use bech32::{FromBase32, Variant};
+use std::{fmt, str::FromStr};
+
+/// Genesis hash (truncated) of Bitcoin mainnet.
+const BITCOIN_MAINNET: &str = "000000000019d6689c085ae165831e93";
+/// Genesis hash (truncated) of Dogecoin mainnet.
+const DOGECOIN_MAINNET: &str = "1a91e3dace36e2be3bf030a65679fe82";
+/// Chain ID of Tron mainnet.
+const TRON_MAINNET: &str = "0x2b6653dc";
+
+/// CAIP-2 blockchain ID, e.g. `eip155:1`.
+/// https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-2.md
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChainId {
+    pub namespace: String,
+    pub reference: String,
+}
+
+/// CAIP-19 asset ID, e.g. `eip155:1/erc721:0x06012c8cf97bead5deae237070f9587f8e7a266d/771769`.
+/// Without `token_id`, it is an asset type which stands for the whole contract.
+/// https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-19.md
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssetId {
+    pub chain_id: ChainId,
+    /// Asset namespace, e.g. `erc721`.
+    pub namespace: String,
+    /// Asset reference, i.e. contract address.
+    pub reference: String,
+    pub token_id: Option<String>,
+}
+
+/// CAIP-10 account ID, e.g. `eip155:1:0xab16a96d359ec26a11e2c2b3d8f8b8942d5bfcdb`.
+/// https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-10.md
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountId {
+    pub chain_id: ChainId,
+    pub address: String,
+}
+
+impl ChainId {
+    pub fn new(namespace: &str, reference: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            reference: reference.to_string(),
+        }
+    }
+
+    /// CAIP-2 ID of a `Chain` in the chain registry. `None` if it is not registered.
+    pub fn of_chain(chain: &Chain) -> Option<Self> {
+        chain.info()?.chain_id.parse().ok()
+    }
+
+    /// Mainnet of a wallet platform which has no `Chain` of its own.
+    /// EVM wallets (`Platform::Ethereum`) live on many chains, use `of_chain` for them.
+    pub fn of_platform(platform: &Platform) -> Option<Self> {
+        match platform {
+            Platform::Ethereum => Self::of_chain(&Chain::Ethereum),
+            Platform::Solana => Self::of_chain(&Chain::Solana),
+            Platform::Arweave => Self::of_chain(&Chain::Arweave),
+            Platform::Conflux => Self::of_chain(&Chain::Conflux),
+            Platform::Tron => Some(Self::new("tron", TRON_MAINNET)),
+            Platform::Bitcoin => Some(Self::new("bip122", BITCOIN_MAINNET)),
+            Platform::Dogecoin => Some(Self::new("bip122", DOGECOIN_MAINNET)),
+            _ => None,
+        }
+    }
+
+    /// Wallet platform of accounts on this chain.
+    pub fn platform(&self) -> Option<Platform> {
+        match (self.namespace.as_str(), self.reference.as_str()) {
+            ("eip155", _) => Some(Platform::Ethereum),
+            ("solana", _) => Some(Platform::Solana),
+            ("arweave", _) => Some(Platform::Arweave),
+            ("cfx", _) => Some(Platform::Conflux),
+            ("tron", _) => Some(Platform::Tron),
+            ("bip122", BITCOIN_MAINNET) => Some(Platform::Bitcoin),
+            ("bip122", DOGECOIN_MAINNET) => Some(Platform::Dogecoin),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.reference)
+    }
+}
+
+impl FromStr for ChainId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (namespace, reference) = s
+            .split_once(':')
+            .ok_or_else(|| Error::ParamError(format!("Invalid CAIP-2 chain ID: {}", s)))?;
+        let valid_namespace = (3..=8).contains(&namespace.len())
+            && namespace
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        let valid_reference = (1..=32).contains(&reference.len())
+            && reference
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_namespace || !valid_reference {
+            return Err(Error::ParamError(format!("Invalid CAIP-2 chain ID: {}", s)));
+        }
+        Ok(Self::new(namespace, reference))
+    }
+}
+
+impl AccountId {
+    /// Account of `address` on `chain`, or on mainnet of `platform` if `chain` is not given.
+    /// `None` if `platform` is not a wallet platform or `address` is invalid for it.
+    pub fn new(platform: &Platform, chain: Option<&Chain>, address: &str) -> Option<Self> {
+        let chain_id = match chain {
+            Some(chain) => ChainId::of_chain(chain)?,
+            None => ChainId::of_platform(platform)?,
+        };
+        if chain_id.platform() != Some(*platform) || !is_valid_address(platform, address) {
+            return None;
+        }
+        // `:` is not allowed in CAIP-10 addresses.
+        let address = match platform {
+            Platform::Conflux => address
+                .to_lowercase()
+                .trim_start_matches("cfx:")
+                .to_string(),
+            _ => address.to_string(),
+        };
+        Some(Self { chain_id, address })
+    }
+
+    /// `(Platform, identity)` this account is saved as.
+    pub fn platform_identity(&self) -> Option<(Platform, String)> {
+        let platform = self.chain_id.platform()?;
+        let identity = match platform {
+            Platform::Ethereum => self.address.to_lowercase(),
+            Platform::Conflux => format!("cfx:{}", self.address.to_lowercase()),
+            _ => self.address.clone(),
+        };
+        Some((platform, identity))
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.chain_id, self.address)
+    }
+}
+
+impl FromStr for AccountId {
+    type Err = Error;
+
+    /// Address is validated if the chain is a known one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::ParamError(format!("Invalid CAIP-10 account ID: {}", s));
+        let (chain_id, address) = s.rsplit_once(':').ok_or_else(invalid)?;
+        let chain_id: ChainId = chain_id.parse().map_err(|_| invalid())?;
+        if address.is_empty() || address.len() > 128 {
+            return Err(invalid());
+        }
+        if let Some(platform) = chain_id.platform() {
+            let identity = match platform {
+                Platform::Conflux => format!("cfx:{}", address),
+                _ => address.to_string(),
+            };
+            if !is_valid_address(&platform, &identity) {
+                return Err(invalid());
+            }
+        }
+        Ok(Self {
+            chain_id,
+            address: address.to_string(),
+        })
+    }
+}
+
+impl AssetId {
+    /// Asset type of `contract`. `None` if its chain or token standard has no CAIP-19 namespace.
+    pub fn of_contract(contract: &Contract) -> Option<Self> {
+        let chain_id = ChainId::of_chain(&contract.chain)?;
+        if chain_id.namespace != "eip155" {
+            return None;
+        }
+        let namespace = match contract.category {
+            ContractCategory::ERC721 | ContractCategory::ENS | ContractCategory::POAP => "erc721",
+            ContractCategory::ERC1155 => "erc1155",
+            ContractCategory::ERC20 => "erc20",
+            ContractCategory::Unknown => return None,
+        };
+        Some(Self {
+            chain_id,
+            namespace: namespace.to_string(),
+            reference: contract.address.to_lowercase(),
+            token_id: None,
+        })
+    }
+
+    /// Asset of token `id` in `contract`. Hex IDs are converted to decimal.
+    /// `None` if `id` is not a token ID, i.e. ENS names (their token ID is a hash of the label).
+    /// Fungible tokens have no token ID, so it's the asset type of `contract`.
+    pub fn of_token(contract: &Contract, id: &str) -> Option<Self> {
+        if contract.category.is_fungible() {
+            return Self::of_contract(contract);
+        }
+        let token_id = if id.starts_with("0x") {
+            hex_to_decimal(id)?
+        } else if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+            id.to_string()
+        } else {
+            return None;
+        };
+        // CAIP-19 token IDs are at most 78 chars, which is enough for any uint256.
+        if token_id.len() > 78 {
+            return None;
+        }
+        Some(Self {
+            token_id: Some(token_id),
+            ..Self::of_contract(contract)?
+        })
+    }
+}
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}:{}", self.chain_id, self.namespace, self.reference)?;
+        match &self.token_id {
+            Some(token_id) => write!(f, "/{}", token_id),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks `address` by the address format of wallet `platform`.
+/// Always `true` for non-wallet platforms.
+pub fn is_valid_address(platform: &Platform, address: &str) -> bool {
+    match platform {
+        Platform::Ethereum => is_evm_address(address),
+        Platform::Solana => is_solana_address(address),
+        // Base58Check, version byte 0x41
+        Platform::Tron => base58_version(address) == Some(0x41),
+        // Base58Check, P2PKH (0x1e) or P2SH (0x16)
+        Platform::Dogecoin => matches!(base58_version(address), Some(0x1e) | Some(0x16)),
+        // Base58Check P2PKH (0x00) / P2SH (0x05), or SegWit
+        Platform::Bitcoin => {
+            matches!(base58_version(address), Some(0x00) | Some(0x05)) || is_segwit_address(address)
+        }
+        // Base64URL of a SHA-256 hash
+        Platform::Arweave => {
+            address.len() == 43
+                && address
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        // CIP-37: `cfx:` + 42 chars in base32
+        Platform::Conflux => {
+            const CHARSET: &str = "abcdefghjkmnprstuvwxyz0123456789";
+            let address = address.to_lowercase();
+            address.strip_prefix("cfx:").map_or(false, |body| {
+                body.len() == 42 && body.chars().all(|c| CHARSET.contains(c))
+            })
+        }
+        _ => true,
+    }
+}
+
+/// Version byte of a 25-byte Base58Check address.
+/// `None` if its checksum mismatches.
+fn base58_version(address: &str) -> Option<u8> {
+    let payload = bs58::decode(address).with_check(None).into_vec().ok()?;
+    if payload.len() != 21 {
+        return None;
+    }
+    payload.first().copied()
+}
+
+/// Bitcoin mainnet SegWit address.
+/// Witness version 0 is Bech32 (BIP-173), later versions are Bech32m (BIP-350).
+fn is_segwit_address(address: &str) -> bool {
+    let (hrp, data, variant) = match bech32::decode(address) {
+        Ok(decoded) => decoded,
+        Err(_) => return false,
+    };
+    let (version, program_len) = match data.split_first() {
+        Some((version, program)) => (
+            version.to_u8(),
+            Vec::<u8>::from_base32(program).map_or(0, |program| program.len()),
+        ),
+        None => return false,
+    };
+    hrp == "bc"
+        && match (version, variant) {
+            (0, Variant::Bech32) => program_len == 20 || program_len == 32,
+            (1..=16, Variant::Bech32m) => (2..=40).contains(&program_len),
+            _ => false,
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_account_id() -> Result<(), Error> {
+        let account: AccountId = "eip155:137:0xAB16A96D359EC26A11E2C2B3D8F8B8942D5BFCDB".parse()?;
+        assert_eq!(account.chain_id, ChainId::new("eip155", "137"));
+        assert_eq!(
+            account.platform_identity(),
+            Some((
+                Platform::Ethereum,
+                "0xab16a96d359ec26a11e2c2b3d8f8b8942d5bfcdb".to_string()
+            ))
+        );
+
+        let account: AccountId =
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:HKKp49qGWXd639QsuH7JiLijfVW5UtCVY4s1n2HANwEA"
+                .parse()?;
+        assert_eq!(account.chain_id.platform(), Some(Platform::Solana));
+
+        assert!("eip155:1:not-an-address".parse::<AccountId>().is_err());
+        assert!("eip155:0xabc".parse::<AccountId>().is_err());
+        // Unknown chains are kept as-is.
+        assert!(
+            "cosmos:cosmoshub-3:cosmos1t2uflqwqe0fsj0shcfkrvpukewcw40yjj6hdc0"
+                .parse::<AccountId>()
+                .is_ok()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_account_id_of_identity() {
+        let address = "0xab16a96d359ec26a11e2c2b3d8f8b8942d5bfcdb";
+        assert_eq!(
+            AccountId::new(&Platform::Ethereum, None, address)
+                .unwrap()
+                .to_string(),
+            format!("eip155:1:{}", address)
+        );
+        assert_eq!(
+            AccountId::new(&Platform::Ethereum, Some(&Chain::Gnosis), address)
+                .unwrap()
+                .to_string(),
+            format!("eip155:100:{}", address)
+        );
+        assert_eq!(
+            AccountId::new(&Platform::Tron, None, "TLa2f6VPqDgRE67v1736s7bJ8Ray5wYjU7")
+                .unwrap()
+                .to_string(),
+            "tron:0x2b6653dc:TLa2f6VPqDgRE67v1736s7bJ8Ray5wYjU7"
+        );
+        // A wallet can't be on a chain of another family.
+        assert_eq!(
+            AccountId::new(&Platform::Ethereum, Some(&Chain::Solana), address),
+            None
+        );
+        assert_eq!(AccountId::new(&Platform::Twitter, None, "alice"), None);
+    }
+
+    #[test]
+    fn test_asset_id() {
+        let contract = Contract {
+            category: ContractCategory::ERC721,
+            address: "0x06012C8CF97BEAD5DEAE237070F9587F8E7A266D".into(),
+            chain: Chain::Ethereum,
+            ..Default::default()
+        };
+        assert_eq!(
+            AssetId::of_contract(&contract).unwrap().to_string(),
+            "eip155:1/erc721:0x06012c8cf97bead5deae237070f9587f8e7a266d"
+        );
+        assert_eq!(
+            AssetId::of_token(&contract, "771769").unwrap().to_string(),
+            "eip155:1/erc721:0x06012c8cf97bead5deae237070f9587f8e7a266d/771769"
+        );
+        assert_eq!(
+            AssetId::of_token(&contract, "0x10").unwrap().token_id,
+            Some("16".to_string())
+        );
+        assert_eq!(AssetId::of_token(&contract, "vitalik.eth"), None);
+
+        let token = Contract {
+            category: ContractCategory::ERC20,
+            address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".into(),
+            ..contract.clone()
+        };
+        assert_eq!(
+            AssetId::of_token(&token, &token.address)
+                .unwrap()
+                .to_string(),
+            "eip155:1/erc20:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+
+        let contract = Contract {
+            category: ContractCategory::ERC1155,
+            chain: Chain::Solana,
+            ..contract
+        };
+        assert_eq!(AssetId::of_contract(&contract), None);
+    }
+
+    #[test]
+    fn test_is_valid_address() {
+        assert!(is_valid_address(
+            &Platform::Bitcoin,
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"
+        ));
+        assert!(is_valid_address(
+            &Platform::Dogecoin,
+            "DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L"
+        ));
+        assert!(!is_valid_address(
+            &Platform::Dogecoin,
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"
+        ));
+        // Last character changed, i.e. broken checksum
+        assert!(!is_valid_address(
+            &Platform::Bitcoin,
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"
+        ));
+        assert!(is_valid_address(
+            &Platform::Bitcoin,
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"
+        ));
+        // Taproot (witness version 1) is Bech32m.
+        assert!(is_valid_address(
+            &Platform::Bitcoin,
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        ));
+        assert!(!is_valid_address(
+            &Platform::Bitcoin,
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"
+        ));
+        assert!(!is_valid_address(
+            &Platform::Tron,
+            "TLa2f6VPqDgRE67v1736s7bJ8Ray5wYjU8"
+        ));
+        assert!(is_valid_address(
+            &Platform::Arweave,
+            "vh-NTHVvlKZqRxc8LyyTNok65yQ55a_PJ1zWLb9G2JI"
+        ));
+        assert!(is_valid_address(
+            &Platform::Conflux,
+            "cfx:aak2rra2njvd77ezwjvx04kkds9fzagfe6ku8scz91"
+        ));
+        assert!(!is_valid_address(&Platform::Ethereum, "vitalik.eth"));
+        assert!(is_valid_address(&Platform::Twitter, "anything"));
+    }
+}
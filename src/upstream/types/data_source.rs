@@ -97,6 +97,53 @@ pub enum DataSource {
     #[graphql(name = "space_id")]
     SpaceId,
 
+    /// https://indexer.crossbell.io/docs
+    #[strum(serialize = "crossbell")]
+    #[serde(rename = "crossbell")]
+    #[graphql(name = "crossbell")]
+    Crossbell,
+
+    /// Solana Name Service
+    /// https://sns-sdk-proxy.bonfida.workers.dev
+    #[strum(serialize = "sns")]
+    #[serde(rename = "sns")]
+    #[graphql(name = "sns")]
+    SNS,
+
+    /// TXT records of a domain.
+    #[strum(serialize = "dns")]
+    #[serde(rename = "dns")]
+    #[graphql(name = "dns")]
+    DNS,
+
+    /// NIP-05 `/.well-known/nostr.json`
+    /// https://github.com/nostr-protocol/nips/blob/master/05.md
+    #[strum(serialize = "nostr")]
+    #[serde(rename = "nostr")]
+    #[graphql(name = "nostr")]
+    Nostr,
+
+    /// WebFinger and verified profile fields of a Mastodon instance.
+    /// https://docs.joinmastodon.org/spec/webfinger/
+    #[strum(serialize = "mastodon")]
+    #[serde(rename = "mastodon")]
+    #[graphql(name = "mastodon")]
+    Mastodon,
+
+    /// Proof of Attendance Protocol
+    /// https://documentation.poap.tech/reference
+    #[strum(serialize = "poap")]
+    #[serde(rename = "poap")]
+    #[graphql(name = "poap")]
+    POAP,
+
+    /// Read from chain directly through an Ethereum JSON-RPC node.
+    /// https://ethereum.org/en/developers/docs/apis/json-rpc/
+    #[strum(serialize = "rpc")]
+    #[serde(rename = "rpc")]
+    #[graphql(name = "rpc")]
+    RPC,
+
     /// Unknown
     #[strum(serialize = "unknown")]
     #[serde(rename = "unknown")]
@@ -97,6 +97,91 @@ pub enum Platform {
     #[graphql(name = "space_id")]
     SpaceId,
 
+    /// Crossbell character
+    #[strum(serialize = "crossbell")]
+    #[serde(rename = "crossbell")]
+    #[graphql(name = "crossbell")]
+    Crossbell,
+
+    /// Solana wallet, Base58-encoded ed25519 public key.
+    #[strum(serialize = "solana")]
+    #[serde(rename = "solana")]
+    #[graphql(name = "solana")]
+    Solana,
+
+    /// Solana Name Service (`.sol`)
+    #[strum(serialize = "sns")]
+    #[serde(rename = "sns")]
+    #[graphql(name = "sns")]
+    SNS,
+
+    /// Nostr public key, lowercase hex (`npub1...` is converted into hex).
+    #[strum(serialize = "nostr")]
+    #[serde(rename = "nostr")]
+    #[graphql(name = "nostr")]
+    Nostr,
+
+    /// Mastodon / fediverse account, `user@instance`
+    #[strum(serialize = "mastodon")]
+    #[serde(rename = "mastodon")]
+    #[graphql(name = "mastodon")]
+    Mastodon,
+
+    /// Tron wallet, Base58Check-encoded address (`T...`).
+    #[strum(serialize = "tron")]
+    #[serde(rename = "tron")]
+    #[graphql(name = "tron")]
+    Tron,
+
+    /// Dogecoin wallet, Base58Check-encoded address (`D...`).
+    #[strum(serialize = "dogecoin")]
+    #[serde(rename = "dogecoin")]
+    #[graphql(name = "dogecoin")]
+    Dogecoin,
+
+    /// Hacker News
+    #[strum(serialize = "hackernews")]
+    #[serde(rename = "hackernews")]
+    #[graphql(name = "hackernews")]
+    HackerNews,
+
+    /// Facebook
+    #[strum(serialize = "facebook")]
+    #[serde(rename = "facebook")]
+    #[graphql(name = "facebook")]
+    Facebook,
+
+    /// PGP key, identity is its fingerprint in lowercase hex.
+    #[strum(serialize = "pgp")]
+    #[serde(rename = "pgp")]
+    #[graphql(name = "pgp")]
+    PGP,
+
+    /// Bitcoin wallet, address kept as-is (case-sensitive).
+    #[strum(serialize = "bitcoin")]
+    #[serde(rename = "bitcoin")]
+    #[graphql(name = "bitcoin")]
+    Bitcoin,
+
+    /// Zcash wallet, address kept as-is (case-sensitive).
+    #[strum(serialize = "zcash")]
+    #[serde(rename = "zcash")]
+    #[graphql(name = "zcash")]
+    Zcash,
+
+    /// Arweave wallet, Base64URL-encoded address (43 chars).
+    #[strum(serialize = "arweave")]
+    #[serde(rename = "arweave")]
+    #[graphql(name = "arweave")]
+    Arweave,
+
+    /// Conflux Core Space wallet, CIP-37 base32 address (`cfx:...`).
+    /// Conflux eSpace is an EVM, whose wallets are `Ethereum`.
+    #[strum(serialize = "conflux")]
+    #[serde(rename = "conflux")]
+    #[graphql(name = "conflux")]
+    Conflux,
+
     /// Unknown
     #[strum(serialize = "unknown")]
     #[serde(rename = "unknown")]
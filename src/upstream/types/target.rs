@@ -89,6 +89,16 @@ impl Target {
         }
     }
 
+    pub fn nft_address(&self) -> Result<String, Error> {
+        match self {
+            Self::Identity(_, _) => Err(Error::General(
+                "Target: Get nft address error: Not an NFT".into(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )),
+            Self::NFT(_, _, address, _) => Ok(address.clone()),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn nft_id(&self) -> Result<String, Error> {
         match self {
@@ -3,19 +3,26 @@ mod tests;
 use crate::config::C;
 use crate::error::Error;
 use crate::graph::edge::Edge;
-use crate::graph::edge::Resolve;
 use crate::graph::edge::{hold::Hold, resolve::DomainNameSystem};
-use crate::graph::vertex::IdentityRecord;
+use crate::graph::edge::{Own, Proof, Resolve};
+use crate::graph::vertex::contract::{Chain, ContractCategory};
 use crate::graph::vertex::Vertex;
+use crate::graph::vertex::{Domain, IdentityRecord};
+use crate::graph::{connect_domain_resolve, create_domain_own_record};
 use crate::graph::{new_db_connection, vertex::Identity};
 use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, Target, TargetProcessedList};
-use crate::util::{make_client, naive_now, parse_body, request_with_timeout};
+use crate::util::{
+    ipfs_to_http, is_evm_address, is_solana_address, make_client, naive_now, parse_body,
+    request_with_timeout,
+};
 use aragog::DatabaseConnection;
 use async_trait::async_trait;
 use futures::future::join_all;
 use http::uri::InvalidUri;
 use hyper::{Body, Method};
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use tracing::{error, warn};
 use uuid::Uuid;
 
@@ -86,16 +93,117 @@ pub struct Meta {
     pub reverse: bool,
 }
 
+/// All records of a domain, i.e. `"crypto.ETH.address": "0x..."`.
+/// https://docs.unstoppabledomains.com/resolution/guides/records-reference/
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Records(pub HashMap<String, Value>);
+
+impl Records {
+    /// Non-empty string value of `key`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.0
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    }
+
+    /// Addresses in `crypto.{TICKER}.address` and `crypto.{TICKER}.version.{VERSION}.address`.
+    /// All EVM-compatible addresses are treated as `Platform::Ethereum`.
+    pub fn addresses(&self) -> Vec<(Platform, String)> {
+        let mut addresses: Vec<(Platform, String)> = vec![];
+        let found = self.0.keys().filter_map(|key| {
+            let ticker = key
+                .strip_prefix("crypto.")?
+                .strip_suffix(".address")?
+                .split('.')
+                .next()?;
+            let address = self.get(key)?;
+            if is_evm_address(&address) {
+                Some((Platform::Ethereum, address.to_lowercase()))
+            } else if ticker == "SOL" && is_solana_address(&address) {
+                Some((Platform::Solana, address))
+            } else {
+                None
+            }
+        });
+        // Same address may be given for many tickers.
+        for address in found {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+        addresses
+    }
+
+    /// Social accounts of the domain owner verified by UD, as `(platform, username, signature)`.
+    /// Unverified ones (i.e. without `validation.social.*.username`) are skipped.
+    /// Discord, YouTube and Telegram accounts are also reported, but have no `Platform` here.
+    pub fn socials(&self) -> Vec<(Platform, String, String)> {
+        [("twitter", Platform::Twitter), ("reddit", Platform::Reddit)]
+            .into_iter()
+            .filter_map(|(name, platform)| {
+                let username = self.get(&format!("social.{}.username", name))?;
+                let signature = self.get(&format!("validation.social.{}.username", name))?;
+                // Some are profile URLs, e.g. `https://www.reddit.com/user/brad`.
+                let username = username
+                    .trim_end_matches('/')
+                    .rsplit('/')
+                    .next()?
+                    .trim_start_matches('@')
+                    .to_lowercase();
+                if username.is_empty() {
+                    return None;
+                }
+                Some((platform, username, signature))
+            })
+            .collect()
+    }
+
+    /// Website of the domain: IPFS hosted, redirection, or UD profile page.
+    pub fn profile_url(&self, domain: &str) -> String {
+        self.get("ipfs.html.value")
+            .or_else(|| self.get("dweb.ipfs.hash"))
+            .map(|hash| ipfs_to_http(&format!("ipfs://{}", hash)))
+            .or_else(|| self.get("browser.redirect_url"))
+            .unwrap_or_else(|| format!("https://ud.me/{}", domain))
+    }
+
+    /// `social.picture.value` if it is an URI.
+    /// Most of them are NFT references (`{chainId}/erc721:{contract}/{tokenId}`), which are skipped.
+    pub fn avatar_url(&self) -> Option<String> {
+        self.get("social.picture.value")
+            .filter(|uri| uri.contains("://"))
+            .map(|uri| ipfs_to_http(&uri))
+    }
+}
+
+/// Metadata of a domain NFT.
+/// https://docs.unstoppabledomains.com/openapi/resolution/#operation/MetaDataController.getMetaData
 #[derive(Deserialize, Debug, Clone)]
-pub struct Records {
-    #[serde(rename = "crypto.BTC.address")]
-    pub btc_address: Option<String>,
-    #[serde(rename = "crypto.ETH.address")]
-    pub eth_address: Option<String>,
+pub struct TokenMetadata {
+    /// Domain name. Empty if token is not found.
+    pub name: Option<String>,
 }
 
 const UNKNOWN_OWNER: &str = "0x0000000000000000000000000000000000000000";
 
+/// UNS / CNS registries where UD domains are minted as ERC721 tokens.
+/// https://docs.unstoppabledomains.com/developer-toolkit/smart-contracts/uns-smart-contracts/
+const REGISTRIES: [(Chain, &str); 3] = [
+    (
+        Chain::Ethereum,
+        "0x049aba7510f45ba5b64ea9e658e342f904db358d",
+    ),
+    (
+        Chain::Ethereum,
+        "0xd1e5b0ff1287aa9f9a268759062e4ab08b9dacbe",
+    ),
+    (Chain::Polygon, "0xa9a6a3626993d487d2dbda3173cf58ca1a9d9e9f"),
+];
+
 pub struct UnstoppableDomains {}
 #[async_trait]
 impl Fetcher for UnstoppableDomains {
@@ -108,12 +216,22 @@ impl Fetcher for UnstoppableDomains {
             Target::Identity(platform, identity) => {
                 fetch_connections_by_platform_identity(platform, identity).await
             }
-            Target::NFT(_, _, _, _) => todo!(),
+            Target::NFT(_, _, _, token_id) => fetch_account_by_token_id(token_id).await,
         }
     }
 
     fn can_fetch(target: &Target) -> bool {
-        target.in_platform_supported(vec![Platform::UnstoppableDomains, Platform::Ethereum])
+        match target {
+            Target::Identity(_, _) => {
+                target.in_platform_supported(vec![Platform::UnstoppableDomains, Platform::Ethereum])
+            }
+            Target::NFT(chain, category, address, _) => {
+                *category == ContractCategory::ERC721
+                    && REGISTRIES.iter().any(|(registry_chain, registry)| {
+                        registry_chain == chain && *registry == address.to_lowercase()
+                    })
+            }
+        }
     }
 }
 
@@ -229,17 +347,7 @@ async fn save_domain(
     eth_record: &IdentityRecord,
     item: Item,
 ) -> Result<TargetProcessedList, Error> {
-    let identity: Identity = Identity {
-        uuid: Some(Uuid::new_v4()),
-        platform: Platform::UnstoppableDomains,
-        identity: item.id.clone(),
-        created_at: None,
-        display_name: Some(item.attributes.meta.domain.clone()),
-        added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
-        updated_at: naive_now(),
-    };
+    let identity = domain_identity(&item.id, &item.attributes.records);
     let hold: Hold = Hold {
         uuid: Uuid::new_v4(),
         source: DataSource::UnstoppableDomains,
@@ -248,6 +356,12 @@ async fn save_domain(
         created_at: None,
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
     };
     let domain_record = identity.create_or_update(&db).await?;
     hold.connect(db, eth_record, &domain_record).await?;
@@ -263,17 +377,137 @@ async fn save_domain(
 
     // 'regular' resolution involves mapping from a name to an address.
     resolve.connect(db, &domain_record, eth_record).await?;
-
+    save_domain_vertex(
+        db,
+        eth_record,
+        &domain_record,
+        &hold,
+        &resolve,
+        item.attributes.meta.reverse,
+    )
+    .await?;
+
+    let mut next_targets =
+        save_records(db, &item.id, &domain_record, &item.attributes.records).await?;
     if item.attributes.meta.reverse {
         // reverse = true
         // 'reverse' resolution maps from an address back to a name.
         resolve.connect(db, eth_record, &domain_record).await?;
-        return Ok(vec![Target::Identity(
+        next_targets.push(Target::Identity(
             Platform::UnstoppableDomains,
             item.attributes.meta.domain.clone(),
-        )]);
+        ));
+    }
+    Ok(next_targets)
+}
+
+/// Mirrors ownership and resolutions of a domain onto its `Domain` vertex.
+async fn save_domain_vertex(
+    db: &DatabaseConnection,
+    eth_record: &IdentityRecord,
+    domain_record: &IdentityRecord,
+    hold: &Hold,
+    resolve: &Resolve,
+    reverse: bool,
+) -> Result<(), Error> {
+    let domain = Domain {
+        token_id: Some(hold.id.clone()).filter(|id| !id.is_empty()),
+        ..Domain::new(DomainNameSystem::UnstoppableDomains, &resolve.name)
+    };
+    let own = Own {
+        source: hold.source,
+        fetcher: hold.fetcher,
+        ..Default::default()
+    };
+    create_domain_own_record(db, eth_record, &domain, &own).await?;
+    connect_domain_resolve(db, domain_record, eth_record, resolve).await?;
+    if reverse {
+        connect_domain_resolve(db, eth_record, domain_record, resolve).await?;
+    }
+    Ok(())
+}
+
+fn domain_identity(domain: &str, records: &Records) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::UnstoppableDomains,
+        identity: domain.to_string(),
+        created_at: None,
+        display_name: Some(domain.to_string()),
+        added_at: naive_now(),
+        avatar_url: records.avatar_url(),
+        profile_url: Some(records.profile_url(domain)),
+        updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     }
-    Ok(vec![])
+}
+
+/// Save `Resolve`s to addresses and `Proof`s to social accounts found in records of `domain`.
+async fn save_records(
+    db: &DatabaseConnection,
+    domain: &str,
+    domain_record: &IdentityRecord,
+    records: &Records,
+) -> Result<TargetProcessedList, Error> {
+    let mut next_targets: TargetProcessedList = vec![];
+    for (platform, address) in records.addresses().into_iter() {
+        let address_identity: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: address.clone(),
+            created_at: None,
+            display_name: None,
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let resolve: Resolve = Resolve {
+            uuid: Uuid::new_v4(),
+            source: DataSource::UnstoppableDomains,
+            system: DomainNameSystem::UnstoppableDomains,
+            name: domain.to_string(),
+            fetcher: DataFetcher::RelationService,
+            updated_at: naive_now(),
+        };
+        let address_record = address_identity.create_or_update(db).await?;
+        resolve.connect(db, domain_record, &address_record).await?;
+        next_targets.push(Target::Identity(platform, address));
+    }
+
+    for (platform, username, signature) in records.socials().into_iter() {
+        let social: Identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: username.clone(),
+            created_at: None,
+            display_name: None,
+            added_at: naive_now(),
+            avatar_url: None,
+            profile_url: None,
+            updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
+        };
+        let proof: Proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::UnstoppableDomains,
+            // Signature of the social account, set by UD after verification.
+            record_id: Some(signature),
+            created_at: None,
+            updated_at: naive_now(),
+            fetcher: DataFetcher::RelationService,
+            deleted_at: None,
+        };
+        let social_record = social.create_or_update(db).await?;
+        proof.connect(db, domain_record, &social_record).await?;
+        next_targets.push(Target::Identity(platform, username));
+    }
+
+    Ok(next_targets)
 }
 
 async fn fetch_domains_by_account(
@@ -298,6 +532,8 @@ async fn fetch_domains_by_account(
             avatar_url: None,
             profile_url: None,
             updated_at: naive_now(),
+            chains: vec![],
+            is_contract: None,
         };
         let eth_record = eth_identity.create_or_update(&db).await?;
         let futures: Vec<_> = result
@@ -384,19 +620,11 @@ async fn fetch_account_by_domain(
         avatar_url: None,
         profile_url: None,
         updated_at: naive_now(),
+        chains: vec![],
+        is_contract: None,
     };
 
-    let identity: Identity = Identity {
-        uuid: Some(Uuid::new_v4()),
-        platform: Platform::UnstoppableDomains,
-        identity: result.meta.domain.clone(),
-        created_at: None,
-        display_name: Some(result.meta.domain.clone()),
-        added_at: naive_now(),
-        avatar_url: None,
-        profile_url: None,
-        updated_at: naive_now(),
-    };
+    let identity = domain_identity(&result.meta.domain, &result.records);
 
     let hold: Hold = Hold {
         uuid: Uuid::new_v4(),
@@ -406,6 +634,12 @@ async fn fetch_account_by_domain(
         created_at: None,
         updated_at: naive_now(),
         fetcher: DataFetcher::RelationService,
+        event_id: None,
+        event_name: None,
+        ended_at: None,
+        expired_at: None,
+        amount: None,
+        decimals: None,
     };
 
     let eth_record = eth_identity.create_or_update(&db).await?;
@@ -423,6 +657,15 @@ async fn fetch_account_by_domain(
 
     // 'regular' resolution involves mapping from a name to an address.
     resolve.connect(&db, &domain_record, &eth_record).await?;
+    save_domain_vertex(
+        &db,
+        &eth_record,
+        &domain_record,
+        &hold,
+        &resolve,
+        result.meta.reverse,
+    )
+    .await?;
 
     if result.meta.reverse {
         // reverse = true
@@ -430,8 +673,62 @@ async fn fetch_account_by_domain(
         resolve.connect(&db, &eth_record, &domain_record).await?;
     }
 
-    Ok(vec![Target::Identity(
+    let mut next_targets =
+        save_records(&db, &result.meta.domain, &domain_record, &result.records).await?;
+    next_targets.push(Target::Identity(
         Platform::Ethereum,
         result.meta.owner.clone().unwrap().to_lowercase(),
-    )])
+    ));
+    Ok(next_targets)
+}
+
+/// `GET {url}/metadata/{token_id}`
+async fn fetch_metadata(token_id: &str) -> Result<TokenMetadata, Error> {
+    let client = make_client();
+    let uri: http::Uri = format!("{}/metadata/{}", C.upstream.unstoppable_api.url, token_id)
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(
+            "Authorization",
+            format!("Bearer {}", C.upstream.unstoppable_api.token),
+        )
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("Invalid Head Error {}", _err)))?;
+
+    let mut resp = request_with_timeout(&client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "UnstoppableDomains fetch | Fail to fetch metadata: {:?}",
+            err.to_string()
+        ))
+    })?;
+    if !resp.status().is_success() {
+        let err_message = format!(
+            "UnstoppableDomains fetch metadata | status: {}",
+            resp.status()
+        );
+        error!(err_message);
+        return Err(Error::General(err_message, resp.status()));
+    }
+
+    parse_body(&mut resp).await
+}
+
+/// Find domain name by NFT token ID, then fetch it as a domain.
+async fn fetch_account_by_token_id(token_id: &str) -> Result<TargetProcessedList, Error> {
+    let metadata = fetch_metadata(token_id).await?;
+    match metadata.name.filter(|name| !name.is_empty()) {
+        Some(domain) => {
+            let mut next_targets =
+                fetch_account_by_domain(&Platform::UnstoppableDomains, &domain).await?;
+            next_targets.push(Target::Identity(Platform::UnstoppableDomains, domain));
+            Ok(next_targets)
+        }
+        None => {
+            warn!("UnstoppableDomains token {} | No domain found", token_id);
+            Ok(vec![])
+        }
+    }
 }
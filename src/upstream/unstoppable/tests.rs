@@ -1,13 +1,81 @@
 use crate::{
     error::Error,
     graph::new_db_connection,
-    graph::vertex::{contract::Chain, Contract, Identity},
+    graph::vertex::{
+        contract::{Chain, ContractCategory},
+        Contract, Identity,
+    },
     upstream::unstoppable::UnstoppableDomains,
     upstream::Platform,
     upstream::{Fetcher, Target},
 };
 
-use super::fetch_domain;
+use super::{fetch_domain, fetch_owner, Records};
+use serde_json::json;
+
+#[test]
+fn test_records() {
+    let records: Records = serde_json::from_value(json!({
+        "crypto.ETH.address": "0x8AaD44321A86b170879d7A244c1e8d360c99DdA8",
+        "crypto.MATIC.version.MATIC.address": "0x8aad44321a86b170879d7a244c1e8d360c99dda8",
+        "crypto.FTM.version.OPERA.address": "0x2da822e59c68f4fb90a5f8dec39410602f45f35f",
+        "crypto.SOL.address": "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX",
+        "crypto.BTC.address": "bc1q359khn0phg58xgezyqsuuaha28zkwx047c0c3y",
+        "crypto.DOGE.address": "",
+        "social.twitter.username": "@Brad",
+        "validation.social.twitter.username": "0x2a1b3c",
+        "social.reddit.username": "https://www.reddit.com/user/Brad/",
+        "validation.social.reddit.username": "0x4d5e6f",
+        // No `Platform` for YouTube.
+        "social.youtube.username": "https://www.youtube.com/@brad",
+        "social.picture.value": "1/erc721:0xb47e3cd837ddf8e4c57f05d70ab865de6e193bbb/7804",
+        "ipfs.html.value": "QmdyBw5oTgCtTLQ18PbDvPL8iaLoEPhSyzD91q9XmgmAjb",
+        "dweb.ipfs.hash": null
+    }))
+    .unwrap();
+
+    let addresses = records.addresses();
+    assert_eq!(addresses.len(), 3);
+    assert!(addresses.contains(&(
+        Platform::Ethereum,
+        "0x8aad44321a86b170879d7a244c1e8d360c99dda8".to_string()
+    )));
+    assert!(addresses.contains(&(
+        Platform::Ethereum,
+        "0x2da822e59c68f4fb90a5f8dec39410602f45f35f".to_string()
+    )));
+    assert!(addresses.contains(&(
+        Platform::Solana,
+        "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX".to_string()
+    )));
+    assert_eq!(
+        records.socials(),
+        vec![
+            (
+                Platform::Twitter,
+                "brad".to_string(),
+                "0x2a1b3c".to_string()
+            ),
+            (Platform::Reddit, "brad".to_string(), "0x4d5e6f".to_string())
+        ]
+    );
+    // Claimed but not verified by UD.
+    let unverified: Records = serde_json::from_value(json!({
+        "social.twitter.username": "@Brad",
+        "validation.social.twitter.username": ""
+    }))
+    .unwrap();
+    assert!(unverified.socials().is_empty());
+    assert_eq!(records.avatar_url(), None);
+    assert_eq!(
+        records.profile_url("brad.crypto"),
+        "https://ipfs.io/ipfs/QmdyBw5oTgCtTLQ18PbDvPL8iaLoEPhSyzD91q9XmgmAjb"
+    );
+    assert_eq!(
+        Records::default().profile_url("brad.crypto"),
+        "https://ud.me/brad.crypto"
+    );
+}
 
 #[tokio::test]
 async fn test_fetch_domains_by_account() -> Result<(), Error> {
@@ -49,3 +117,21 @@ async fn test_fetch_account_by_domain() -> Result<(), Error> {
     print!("found: {:?}", found);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_fetch_account_by_token_id() -> Result<(), Error> {
+    let domain = fetch_owner("brad.crypto").await?;
+    let target = Target::NFT(
+        Chain::Ethereum,
+        ContractCategory::ERC721,
+        domain.meta.registry.expect("Registry not found"),
+        domain.meta.token_id.expect("Token ID not found"),
+    );
+    assert!(UnstoppableDomains::can_fetch(&target));
+    let _ = UnstoppableDomains::fetch(&target).await?;
+    let db = new_db_connection().await?;
+    Identity::find_by_platform_identity(&db, &Platform::UnstoppableDomains, "brad.crypto")
+        .await?
+        .expect("Record not found");
+    Ok(())
+}
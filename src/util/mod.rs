@@ -4,9 +4,14 @@ mod tests;
 use std::{collections::HashSet, hash::Hash};
 
 use crate::error::Error;
+use bech32::{FromBase32, Variant};
 use chrono::NaiveDateTime;
 use http::Response;
-use hyper::{body::HttpBody as _, client::HttpConnector, Body, Client, Request};
+use hyper::{
+    body::HttpBody as _,
+    client::{connect::Connect, HttpConnector},
+    Body, Client, Request,
+};
 use hyper_tls::HttpsConnector;
 use serde::Deserialize;
 
@@ -42,10 +47,13 @@ pub fn make_client() -> Client<HttpsConnector<HttpConnector>> {
     Client::builder().build::<_, hyper::Body>(https)
 }
 
-pub async fn request_with_timeout(
-    client: &Client<HttpsConnector<HttpConnector>>,
+pub async fn request_with_timeout<C>(
+    client: &Client<C>,
     req: Request<Body>,
-) -> Result<Response<Body>, Error> {
+) -> Result<Response<Body>, Error>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
     match tokio::time::timeout(DEFAULT_TIMEOUT, client.request(req)).await {
         Ok(resp) => match resp {
             Ok(resp) => Ok(resp),
@@ -75,6 +83,47 @@ where
     Ok(serde_json::from_str(body)?)
 }
 
+/// Solana address is a Base58-encoded ed25519 public key (32 bytes).
+pub fn is_solana_address(address: &str) -> bool {
+    (32..=44).contains(&address.len())
+        && bs58::decode(address)
+            .into_vec()
+            .map_or(false, |bytes| bytes.len() == 32)
+}
+
+/// Nostr public key in `npub1...` (NIP-19) or hex format into lowercase hex.
+pub fn nostr_pubkey_to_hex(pubkey: &str) -> Option<String> {
+    if pubkey.len() == 64 && pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(pubkey.to_lowercase());
+    }
+    match bech32::decode(pubkey).ok()? {
+        (hrp, data, Variant::Bech32) if hrp == "npub" => {
+            let bytes = Vec::<u8>::from_base32(&data).ok()?;
+            (bytes.len() == 32).then(|| bytes.iter().map(|b| format!("{:02x}", b)).collect())
+        }
+        _ => None,
+    }
+}
+
+/// `0x`-prefixed 20 bytes hex address, case insensitive.
+pub fn is_evm_address(address: &str) -> bool {
+    address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// `ipfs://CID/path` and `ar://TX` URIs into HTTP gateway URLs.
+/// Other URIs are returned as-is.
+pub fn ipfs_to_http(uri: &str) -> String {
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        format!("https://ipfs.io/ipfs/{}", path.trim_start_matches("ipfs/"))
+    } else if let Some(tx) = uri.strip_prefix("ar://") {
+        format!("https://arweave.net/{}", tx)
+    } else {
+        uri.to_string()
+    }
+}
+
 pub(crate) fn hashset_append<T>(set: &mut HashSet<T>, items: Vec<T>)
 where
     T: Eq + Clone + Hash,
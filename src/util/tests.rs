@@ -1 +1,63 @@
+use super::{ipfs_to_http, is_evm_address, is_solana_address, nostr_pubkey_to_hex};
 
+#[test]
+fn test_is_solana_address() {
+    assert!(is_solana_address(
+        "HKKp49qGWXd639QsuH7JiLijfVW5UtCVY4s1n2HANwEA"
+    ));
+    assert!(is_solana_address("11111111111111111111111111111111"));
+    assert!(!is_solana_address(
+        "0x934b510d4c9103e6a87aef13b816fb080286d649"
+    ));
+    assert!(!is_solana_address("bonfida.sol"));
+}
+
+#[test]
+fn test_nostr_pubkey_to_hex() {
+    let hex = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d";
+    assert_eq!(
+        nostr_pubkey_to_hex("npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6"),
+        Some(hex.to_string())
+    );
+    assert_eq!(
+        nostr_pubkey_to_hex(&hex.to_uppercase()),
+        Some(hex.to_string())
+    );
+    assert_eq!(nostr_pubkey_to_hex("bob@example.com"), None);
+    // Broken checksum
+    assert_eq!(
+        nostr_pubkey_to_hex("npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w7"),
+        None
+    );
+}
+
+#[test]
+fn test_ipfs_to_http() {
+    assert_eq!(
+        ipfs_to_http("ipfs://QmY9eSSExEXr5d9TtCSh5v3Lx5e3Wwc7avjRWt1VjuaBfM"),
+        "https://ipfs.io/ipfs/QmY9eSSExEXr5d9TtCSh5v3Lx5e3Wwc7avjRWt1VjuaBfM"
+    );
+    assert_eq!(
+        ipfs_to_http("ipfs://ipfs/QmY9eSSExEXr5d9TtCSh5v3Lx5e3Wwc7avjRWt1VjuaBfM/1.png"),
+        "https://ipfs.io/ipfs/QmY9eSSExEXr5d9TtCSh5v3Lx5e3Wwc7avjRWt1VjuaBfM/1.png"
+    );
+    assert_eq!(
+        ipfs_to_http("ar://2b7mlVaVqA1ZNtjhNdH6mDtP6NKPlVXe2zpzcrW3UjA"),
+        "https://arweave.net/2b7mlVaVqA1ZNtjhNdH6mDtP6NKPlVXe2zpzcrW3UjA"
+    );
+    assert_eq!(
+        ipfs_to_http("https://example.com/avatar.png"),
+        "https://example.com/avatar.png"
+    );
+}
+
+#[test]
+fn test_is_evm_address() {
+    assert!(is_evm_address("0x934b510d4c9103e6a87aef13b816fb080286d649"));
+    assert!(is_evm_address("0x934B510D4C9103E6a87aef13b816fb080286d649"));
+    assert!(!is_evm_address("934b510d4c9103e6a87aef13b816fb080286d649"));
+    assert!(!is_evm_address("0x934b510d4c9103e6a87aef13b816fb080286d64"));
+    assert!(!is_evm_address(
+        "0x934b510d4c9103e6a87aef13b816fb080286d64z"
+    ));
+}